@@ -0,0 +1,201 @@
+-- Schema as it stood before migrations were introduced (previously hand-applied from PG/*.txt).
+-- Written with IF NOT EXISTS so databases provisioned by hand can adopt the migrations.
+
+CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
+
+DO $$
+BEGIN
+    CREATE TYPE media_type AS ENUM ('image', 'video', 'gif');
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
+
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    first_name TEXT NOT NULL,
+    last_name TEXT NOT NULL,
+    username TEXT UNIQUE NOT NULL,
+    password TEXT NOT NULL,
+    mobile TEXT,
+    email TEXT UNIQUE NOT NULL,
+    organisation TEXT,
+    bio JSONB,
+    profile_pic TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_active BOOLEAN DEFAULT TRUE,
+    resume_url TEXT,
+    interests TEXT[],
+    provider TEXT,
+    provider_user_id TEXT,
+    access_token TEXT,
+    refresh_token TEXT,
+    oauth_email TEXT,
+    college TEXT,
+    batch TEXT,
+    stream TEXT,
+    linkedin TEXT,
+    github TEXT,
+    skills TEXT[],
+    work_experiences JSONB,
+    projects JSONB,
+    pronouns TEXT,
+    location TEXT,
+    hackingon TEXT,
+    learning TEXT,
+    availablefor TEXT
+);
+
+CREATE TABLE IF NOT EXISTS business_accounts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    company_name TEXT NOT NULL,
+    contact_person_name TEXT NOT NULL,
+    email TEXT UNIQUE NOT NULL,
+    password_hash TEXT NOT NULL,
+    address TEXT NOT NULL,
+    gst_number TEXT,
+    website TEXT,
+    phone TEXT,
+    logo_url TEXT,
+    description TEXT,
+    industry TEXT,
+    size TEXT,
+    is_verified BOOLEAN DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS communities (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name TEXT NOT NULL,
+    description JSONB,
+    creator_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    creator_business_id UUID REFERENCES business_accounts(id) ON DELETE SET NULL,
+    creator_type TEXT NOT NULL DEFAULT 'user' CHECK (creator_type IN ('user', 'business')),
+    banner_url TEXT,
+    is_public BOOLEAN DEFAULT TRUE,
+    is_active BOOLEAN DEFAULT TRUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS community_members (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    community_id UUID REFERENCES communities(id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT DEFAULT 'member',
+    joined_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (community_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS posts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    community_id UUID REFERENCES communities(id) ON DELETE CASCADE,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    title JSONB,
+    content JSONB,
+    post_type TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_active BOOLEAN DEFAULT TRUE,
+    tags TEXT[] DEFAULT '{}',
+    likes INT DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS post_media (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    media_type media_type NOT NULL,
+    url TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS shared_posts (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    shared_by UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    shared_with UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    message TEXT,
+    shared_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS post_likes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID REFERENCES posts(id) ON DELETE CASCADE,
+    user_id UUID,
+    business_id UUID REFERENCES business_accounts(id) ON DELETE CASCADE,
+    user_type TEXT CHECK (user_type IN ('user', 'business')),
+    liked_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_liked BOOLEAN DEFAULT TRUE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (post_id, user_id)
+);
+
+CREATE TABLE IF NOT EXISTS comments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    post_id UUID REFERENCES posts(id) ON DELETE CASCADE,
+    author_id UUID,
+    author_type TEXT DEFAULT 'user' CHECK (author_type IN ('user', 'business')),
+    content JSONB NOT NULL,
+    parent_id UUID REFERENCES comments(id) ON DELETE CASCADE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_active BOOLEAN DEFAULT TRUE
+);
+
+CREATE TABLE IF NOT EXISTS comment_likes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    comment_id UUID NOT NULL REFERENCES comments(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL,
+    user_type TEXT NOT NULL CHECK (user_type IN ('user', 'business')),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (comment_id, user_id, user_type)
+);
+
+CREATE TABLE IF NOT EXISTS followers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    follower_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    followed_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    followed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_following BOOLEAN DEFAULT TRUE,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (follower_id, followed_id)
+);
+
+CREATE TABLE IF NOT EXISTS user_badges (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    badge_name TEXT NOT NULL,
+    badge_icon TEXT,
+    awarded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS job_listings (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    posted_by UUID NOT NULL REFERENCES business_accounts(id) ON DELETE CASCADE,
+    applied_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    organisation TEXT NOT NULL,
+    title JSONB NOT NULL,
+    description JSONB NOT NULL,
+    location TEXT NOT NULL,
+    job_type TEXT CHECK (job_type IN ('remote', 'hybrid', 'onsite')),
+    notice_period TEXT,
+    min_experience INT,
+    skills TEXT[],
+    salary_range TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_active BOOLEAN DEFAULT TRUE,
+    job_posted_by TEXT
+);
+
+CREATE TABLE IF NOT EXISTS job_applications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    job_id UUID REFERENCES job_listings(id) ON DELETE CASCADE,
+    applicant_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    cover_letter TEXT,
+    resume_url TEXT,
+    application_date TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    status TEXT DEFAULT 'applied',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    is_active BOOLEAN DEFAULT TRUE,
+    applied_at TIMESTAMP DEFAULT NOW()
+);
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS user_identities (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(50) NOT NULL,
+    provider_user_id TEXT NOT NULL,
+    email TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_login_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (provider, provider_user_id),
+    UNIQUE (user_id, provider)
+);
+
+-- Accounts created by the old Google-only flow
+INSERT INTO user_identities (user_id, provider, provider_user_id, email)
+SELECT id, provider, provider_user_id, email
+FROM users
+WHERE provider IS NOT NULL AND provider <> 'local' AND provider_user_id IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+-- Logins started from a signed-in session with POST /auth/{provider}/link: the callback links
+-- the provider login to this user instead of matching it by email.
+ALTER TABLE oauth_states ADD COLUMN IF NOT EXISTS link_user_id UUID REFERENCES users(id) ON DELETE CASCADE;
@@ -0,0 +1,8 @@
+ALTER TABLE oauth_states ADD COLUMN IF NOT EXISTS redirect_to TEXT;
+
+CREATE TABLE IF NOT EXISTS login_codes (
+    code_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL
+);
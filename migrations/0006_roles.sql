@@ -0,0 +1,11 @@
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'user'
+    CHECK (role IN ('user', 'admin'));
+
+DO $$
+BEGIN
+    ALTER TABLE community_members ADD CONSTRAINT community_members_role_check
+        CHECK (role IN ('member', 'moderator', 'owner')) NOT VALID;
+EXCEPTION
+    WHEN duplicate_object THEN NULL;
+END
+$$;
@@ -0,0 +1,51 @@
+-- Cursor pagination walks every list on (timestamp, id). The timestamps all default to the insert
+-- time, but were nullable; backfill the gaps so every row has a key, then index the keys.
+
+UPDATE users SET created_at = 'epoch' WHERE created_at IS NULL;
+ALTER TABLE users ALTER COLUMN created_at SET NOT NULL;
+
+UPDATE business_accounts SET created_at = 'epoch' WHERE created_at IS NULL;
+ALTER TABLE business_accounts ALTER COLUMN created_at SET NOT NULL;
+
+UPDATE communities SET created_at = 'epoch' WHERE created_at IS NULL;
+ALTER TABLE communities ALTER COLUMN created_at SET NOT NULL;
+
+UPDATE community_members SET joined_at = 'epoch' WHERE joined_at IS NULL;
+ALTER TABLE community_members ALTER COLUMN joined_at SET NOT NULL;
+
+UPDATE posts SET created_at = 'epoch' WHERE created_at IS NULL;
+ALTER TABLE posts ALTER COLUMN created_at SET NOT NULL;
+
+UPDATE post_likes SET liked_at = 'epoch' WHERE liked_at IS NULL;
+ALTER TABLE post_likes ALTER COLUMN liked_at SET NOT NULL;
+
+UPDATE comments SET created_at = 'epoch' WHERE created_at IS NULL;
+ALTER TABLE comments ALTER COLUMN created_at SET NOT NULL;
+
+UPDATE followers SET followed_at = 'epoch' WHERE followed_at IS NULL;
+ALTER TABLE followers ALTER COLUMN followed_at SET NOT NULL;
+
+UPDATE user_badges SET awarded_at = 'epoch' WHERE awarded_at IS NULL;
+ALTER TABLE user_badges ALTER COLUMN awarded_at SET NOT NULL;
+
+UPDATE job_listings SET created_at = 'epoch' WHERE created_at IS NULL;
+ALTER TABLE job_listings ALTER COLUMN created_at SET NOT NULL;
+
+UPDATE job_applications SET application_date = COALESCE(applied_at, 'epoch') WHERE application_date IS NULL;
+ALTER TABLE job_applications ALTER COLUMN application_date SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_created_at_id ON users(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_business_accounts_created_at_id ON business_accounts(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_communities_created_at_id ON communities(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_community_members_joined_at_id ON community_members(joined_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_posts_created_at_id ON posts(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_post_likes_liked_at_id ON post_likes(liked_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_comments_created_at_id ON comments(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_followers_followed_at_id ON followers(followed_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_followers_followed ON followers(followed_id, followed_at DESC, follower_id DESC);
+CREATE INDEX IF NOT EXISTS idx_followers_follower ON followers(follower_id, followed_at DESC, followed_id DESC);
+CREATE INDEX IF NOT EXISTS idx_user_badges_awarded_at_id ON user_badges(awarded_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_job_listings_created_at_id ON job_listings(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_job_listings_posted_by ON job_listings(posted_by, created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_job_applications_job ON job_applications(job_id, application_date DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_job_applications_applicant ON job_applications(applicant_id, application_date DESC, id DESC);
@@ -0,0 +1,11 @@
+-- Posts a user has already seen; the home feed leaves them out.
+CREATE TABLE IF NOT EXISTS post_views (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    seen_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, post_id)
+);
+
+-- Lookups made for every feed candidate.
+CREATE INDEX IF NOT EXISTS idx_comments_post_id ON comments(post_id);
+CREATE INDEX IF NOT EXISTS idx_posts_author_id ON posts(author_id);
@@ -0,0 +1,26 @@
+-- Precomputed post rankings and trending tags, rebuilt by the background rankings job
+-- (src/rankings.rs). Requests only read these tables.
+
+CREATE TABLE IF NOT EXISTS post_scores (
+    post_id UUID PRIMARY KEY REFERENCES posts(id) ON DELETE CASCADE,
+    hot_score BIGINT NOT NULL,
+    trending_score BIGINT NOT NULL,
+    computed_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_post_scores_hot ON post_scores(hot_score DESC);
+CREATE INDEX IF NOT EXISTS idx_post_scores_trending ON post_scores(trending_score DESC);
+
+-- community_id is NULL for the global ranking.
+CREATE TABLE IF NOT EXISTS trending_tags (
+    window_hours INTEGER NOT NULL,
+    community_id UUID REFERENCES communities(id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    score DOUBLE PRECISION NOT NULL,
+    post_count INTEGER NOT NULL,
+    computed_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_trending_tags_scope ON trending_tags(window_hours, community_id, score DESC);
+
+-- Activity lookups of the job.
+CREATE INDEX IF NOT EXISTS idx_comments_created_at ON comments(created_at);
+CREATE INDEX IF NOT EXISTS idx_shared_posts_shared_at ON shared_posts(shared_at);
@@ -0,0 +1,37 @@
+-- Full-text search (src/search.rs). Every table gets a generated `search_vector`, kept up to date
+-- by Postgres on every insert and update. JSONB fields are indexed with all of their language
+-- keys; the 'simple' configuration is used because the content is multilingual (no stemming or
+-- stop words that only fit one language).
+
+-- array_to_string is only STABLE, which generated columns do not accept. For text[] it is
+-- immutable in practice.
+CREATE OR REPLACE FUNCTION search_text(items TEXT[]) RETURNS TEXT
+    LANGUAGE sql IMMUTABLE PARALLEL SAFE
+    AS $$ SELECT COALESCE(array_to_string(items, ' '), '') $$;
+
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(jsonb_to_tsvector('simple', COALESCE(title, '{}'), '["string"]'), 'A')
+    || setweight(to_tsvector('simple', search_text(tags)), 'B')
+    || setweight(jsonb_to_tsvector('simple', COALESCE(content, '{}'), '["string"]'), 'C')
+) STORED;
+CREATE INDEX IF NOT EXISTS idx_posts_search ON posts USING GIN (search_vector);
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', first_name || ' ' || last_name || ' ' || username), 'A')
+    || setweight(to_tsvector('simple', search_text(skills)), 'B')
+    || setweight(jsonb_to_tsvector('simple', COALESCE(bio, '{}'), '["string"]'), 'C')
+) STORED;
+CREATE INDEX IF NOT EXISTS idx_users_search ON users USING GIN (search_vector);
+
+ALTER TABLE communities ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', name), 'A')
+    || setweight(jsonb_to_tsvector('simple', COALESCE(description, '{}'), '["string"]'), 'C')
+) STORED;
+CREATE INDEX IF NOT EXISTS idx_communities_search ON communities USING GIN (search_vector);
+
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(jsonb_to_tsvector('simple', title, '["string"]'), 'A')
+    || setweight(to_tsvector('simple', search_text(skills) || ' ' || organisation), 'B')
+    || setweight(jsonb_to_tsvector('simple', description, '["string"]'), 'C')
+) STORED;
+CREATE INDEX IF NOT EXISTS idx_job_listings_search ON job_listings USING GIN (search_vector);
@@ -0,0 +1,19 @@
+-- Structured salaries so job listings can be filtered by a salary band. `salary_range` stays as
+-- the label shown to users; new and updated jobs get the numbers from `SalaryRange::parse`
+-- (src/models/job_list.rs). Existing rows keep NULL salaries until
+-- `powerbrilliant-admin backfill-salaries` parses their labels with the same function.
+
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS salary_min INTEGER;
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS salary_max INTEGER;
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS salary_currency CHAR(3);
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'job_listings_salary_check') THEN
+        ALTER TABLE job_listings ADD CONSTRAINT job_listings_salary_check
+            CHECK (salary_min >= 0 AND salary_min <= salary_max);
+    END IF;
+END $$;
+
+-- Location filter of GET /jobs (case-insensitive).
+CREATE INDEX IF NOT EXISTS idx_job_listings_location ON job_listings(lower(location));
@@ -0,0 +1,51 @@
+-- Application lifecycle: a fixed set of statuses (see `ApplicationStatus` in
+-- src/models/job_applications.rs for the allowed transitions) and the history of every change.
+
+UPDATE job_applications
+SET status = replace(lower(trim(status)), ' ', '_')
+WHERE status IS NOT NULL;
+UPDATE job_applications
+SET status = 'applied'
+WHERE status IS NULL OR status NOT IN (
+    'applied', 'under_review', 'shortlisted', 'interview', 'offered', 'hired', 'rejected', 'withdrawn'
+);
+ALTER TABLE job_applications ALTER COLUMN status SET NOT NULL;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'job_applications_status_check') THEN
+        ALTER TABLE job_applications ADD CONSTRAINT job_applications_status_check CHECK (status IN (
+            'applied', 'under_review', 'shortlisted', 'interview', 'offered', 'hired', 'rejected', 'withdrawn'
+        ));
+    END IF;
+END $$;
+
+-- One row per status change, the first one being the application itself (from_status NULL).
+-- The author is the applicant (user) or the business that posted the job; both NULL when
+-- unknown (changes made before this table existed).
+CREATE TABLE IF NOT EXISTS job_application_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    application_id UUID NOT NULL REFERENCES job_applications(id) ON DELETE CASCADE,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    changed_by_type TEXT CHECK (changed_by_type IN ('user', 'business')),
+    changed_by_user UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_by_business UUID REFERENCES business_accounts(id) ON DELETE SET NULL,
+    -- Shown to the applicant.
+    note TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_job_application_events_application
+    ON job_application_events(application_id, created_at, id);
+
+-- Existing applications: when they were made and, if it moved since, their current status.
+INSERT INTO job_application_events (application_id, from_status, to_status, changed_by_type, changed_by_user, created_at)
+SELECT id, NULL, 'applied', 'user', applicant_id, application_date
+FROM job_applications a
+WHERE NOT EXISTS (SELECT 1 FROM job_application_events e WHERE e.application_id = a.id);
+
+INSERT INTO job_application_events (application_id, from_status, to_status, created_at)
+SELECT id, 'applied', status, GREATEST(COALESCE(updated_at, application_date), application_date)
+FROM job_applications a
+WHERE status <> 'applied'
+  AND NOT EXISTS (SELECT 1 FROM job_application_events e WHERE e.application_id = a.id AND e.from_status IS NOT NULL);
@@ -0,0 +1,28 @@
+-- Applicant tracking for the business that posted a job: a rating per application and private
+-- notes. Neither is ever shown to the applicant.
+
+ALTER TABLE job_applications ADD COLUMN IF NOT EXISTS recruiter_rating SMALLINT;
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'job_applications_recruiter_rating_check') THEN
+        ALTER TABLE job_applications ADD CONSTRAINT job_applications_recruiter_rating_check
+            CHECK (recruiter_rating BETWEEN 1 AND 5);
+    END IF;
+END $$;
+
+-- Written by the job's poster (business) or an admin (user).
+CREATE TABLE IF NOT EXISTS job_application_notes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    application_id UUID NOT NULL REFERENCES job_applications(id) ON DELETE CASCADE,
+    written_by_type TEXT NOT NULL CHECK (written_by_type IN ('user', 'business')),
+    written_by_user UUID REFERENCES users(id) ON DELETE SET NULL,
+    written_by_business UUID REFERENCES business_accounts(id) ON DELETE SET NULL,
+    note TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_job_application_notes_application
+    ON job_application_notes(application_id, created_at DESC, id DESC);
+
+-- Dashboard filters: applications of a job by status.
+CREATE INDEX IF NOT EXISTS idx_job_applications_job_status ON job_applications(job_id, status);
@@ -0,0 +1,35 @@
+-- One application per applicant and job. Re-applying after a withdrawal reopens the same row
+-- (see JOB_REAPPLY_POLICY), so its history stays in one place.
+
+-- Earlier duplicates are merged into the one kept (the active one, else the newest): their
+-- status events and recruiter notes move over, and their rating fills a missing one.
+CREATE TEMP TABLE duplicate_applications ON COMMIT DROP AS
+SELECT id, kept_id FROM (
+    SELECT id, first_value(id) OVER (
+        PARTITION BY job_id, applicant_id
+        ORDER BY COALESCE(is_active, true) DESC, application_date DESC, id DESC
+    ) AS kept_id
+    FROM job_applications
+    WHERE job_id IS NOT NULL AND applicant_id IS NOT NULL
+) ranked
+WHERE id <> kept_id;
+
+UPDATE job_application_events e SET application_id = d.kept_id
+FROM duplicate_applications d WHERE e.application_id = d.id;
+
+UPDATE job_application_notes n SET application_id = d.kept_id
+FROM duplicate_applications d WHERE n.application_id = d.id;
+
+UPDATE job_applications kept
+SET recruiter_rating = (
+    SELECT dup.recruiter_rating
+    FROM duplicate_applications d JOIN job_applications dup ON dup.id = d.id
+    WHERE d.kept_id = kept.id AND dup.recruiter_rating IS NOT NULL
+    ORDER BY dup.application_date DESC, dup.id DESC
+    LIMIT 1
+)
+WHERE kept.recruiter_rating IS NULL AND kept.id IN (SELECT kept_id FROM duplicate_applications);
+
+DELETE FROM job_applications WHERE id IN (SELECT id FROM duplicate_applications);
+
+CREATE UNIQUE INDEX IF NOT EXISTS job_applications_job_applicant_key ON job_applications(job_id, applicant_id);
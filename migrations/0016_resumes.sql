@@ -0,0 +1,22 @@
+-- Uploaded resumes. Files live in RESUME_DIR (not under the public /uploads) and are served
+-- only to their owner and to the businesses whose jobs they were used to apply to.
+CREATE TABLE IF NOT EXISTS resumes (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    content_type TEXT NOT NULL CHECK (content_type IN (
+        'application/pdf',
+        'application/vnd.openxmlformats-officedocument.wordprocessingml.document'
+    )),
+    size_bytes INTEGER NOT NULL,
+    -- File name inside RESUME_DIR.
+    storage_key TEXT NOT NULL UNIQUE,
+    -- Listed in the user's library. Resumes uploaded for one application, and library entries
+    -- the user removed, stay so the applications using them keep their file.
+    in_library BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_resumes_user ON resumes(user_id, created_at DESC, id DESC);
+
+ALTER TABLE job_applications ADD COLUMN IF NOT EXISTS resume_id UUID REFERENCES resumes(id) ON DELETE SET NULL;
+CREATE INDEX IF NOT EXISTS idx_job_applications_resume ON job_applications(resume_id);
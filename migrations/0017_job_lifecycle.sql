@@ -0,0 +1,44 @@
+-- Job lifecycle: a status (see `JobStatus` in src/models/job_list.rs for the allowed
+-- transitions), an application deadline and an optional cap on applicants. `is_active` stays,
+-- kept true exactly while the job is published, for the queries that only want open jobs.
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'published';
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS closes_at TIMESTAMP;
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS max_applicants INTEGER;
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS closed_at TIMESTAMP;
+-- Jobs closed by their applications reaching max_applicants, rather than by the employer, so
+-- raising or removing the cap reopens them.
+ALTER TABLE job_listings ADD COLUMN IF NOT EXISTS closed_when_full BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE job_listings
+SET status = 'closed', closed_at = COALESCE(closed_at, updated_at)
+WHERE is_active = FALSE AND status = 'published';
+UPDATE job_listings SET is_active = (status = 'published') WHERE is_active IS DISTINCT FROM (status = 'published');
+
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'job_listings_status_check') THEN
+        ALTER TABLE job_listings ADD CONSTRAINT job_listings_status_check CHECK (status IN (
+            'draft', 'published', 'paused', 'closed', 'expired'
+        ));
+    END IF;
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'job_listings_max_applicants_check') THEN
+        ALTER TABLE job_listings ADD CONSTRAINT job_listings_max_applicants_check CHECK (max_applicants > 0);
+    END IF;
+END $$;
+
+-- What the closer scans for.
+CREATE INDEX IF NOT EXISTS idx_job_listings_closes_at
+    ON job_listings(closes_at) WHERE status IN ('published', 'paused');
+
+-- Messages to users about things that happened to them, such as a job they applied to closing.
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL,
+    job_id UUID REFERENCES job_listings(id) ON DELETE CASCADE,
+    application_id UUID REFERENCES job_applications(id) ON DELETE CASCADE,
+    read_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS idx_notifications_user ON notifications(user_id, created_at DESC, id DESC);
@@ -0,0 +1,77 @@
+use actix_cors::Cors;
+use actix_files::Files;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{middleware, web, App};
+use sqlx::PgPool;
+
+use crate::auth;
+use crate::config::Config;
+use crate::error;
+use crate::handlers::share_handler::share_post;
+use crate::oauth::OAuthProviders;
+use crate::routes::{
+    auth_routes, oauth_routes,
+    follower_routes,
+    post_likes_routes, comments_routes,
+    share_routes, user_badges_routes, user_routes, jobs_routes, job_application_routes, community_routes,
+    community_members_routes, post_routes, business_accounts_routes, feed_routes, search_routes,
+    ats_routes, resume_routes, notification_routes,
+};
+
+/// Builds the application with every route, middleware and shared state. Used by the server
+/// for each worker and by the integration tests, so both run the exact same app.
+pub fn create_app(
+    db: PgPool,
+    config: Config,
+    oauth_providers: web::Data<OAuthProviders>,
+) -> App<
+    impl ServiceFactory<
+        ServiceRequest,
+        Config = (),
+        Response = ServiceResponse<impl MessageBody>,
+        Error = actix_web::Error,
+        InitError = (),
+    >,
+> {
+    let cors = config
+        .allowed_origins
+        .iter()
+        .fold(Cors::default(), |cors, origin| cors.allowed_origin(origin))
+        .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
+        .allowed_headers(vec!["Content-Type", "Authorization"])
+        .max_age(3600);
+
+    App::new()
+        .wrap(middleware::from_fn(auth::authenticate))
+        .wrap(cors)
+        .app_data(web::Data::new(db))
+        .app_data(web::Data::new(config))
+        .app_data(error::json_config())
+        .app_data(error::path_config())
+        .app_data(error::query_config())
+        .service(
+            Files::new("/uploads", "./uploads").use_last_modified(true),
+        )
+        .configure(share_routes::config)
+        .app_data(oauth_providers)
+        .configure(auth_routes::config)
+        .configure(oauth_routes::config)
+        .configure(user_routes::config)
+        .configure(jobs_routes::config)
+        .configure(community_routes::config)
+        .configure(job_application_routes::config)
+        .configure(community_members_routes::config)
+        .configure(post_routes::config)
+        .configure(user_badges_routes::config)
+        .configure(comments_routes::config)
+        .configure(post_likes_routes::config)
+        .configure(follower_routes::config)
+        .configure(business_accounts_routes::config)
+        .configure(feed_routes::config)
+        .configure(search_routes::config)
+        .configure(ats_routes::config)
+        .configure(resume_routes::config)
+        .configure(notification_routes::config)
+        .service(web::resource("/share_post").route(web::post().to(share_post)))
+}
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::auth::{Principal, PrincipalKind};
+use crate::error::ApiError;
+use crate::models::job_applications::{
+    ApplicantFilters, ApplicationEvent, ApplicationNote, ApplicationStatus, JobApplication,
+};
+use crate::pagination::{Cursor, Page, PageParams};
+
+/// Most applications one bulk status change may touch.
+pub const MAX_BULK_APPLICATIONS: usize = 100;
+
+/// An application as the business that posted the job sees it in the dashboard.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Applicant {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub applicant_id: Uuid,
+    pub cover_letter: Option<String>,
+    pub resume_url: Option<String>,
+    pub application_date: NaiveDateTime,
+    pub status: String,
+    pub updated_at: Option<NaiveDateTime>,
+    pub first_name: String,
+    pub last_name: String,
+    /// Left out unless the applicant turned on show_email (or the viewer is an admin).
+    pub email: Option<String>,
+    /// Left out unless the applicant turned on show_mobile (or the viewer is an admin).
+    pub mobile: Option<String>,
+    pub profile_pic: Option<String>,
+    pub organisation: Option<String>,
+    pub location: Option<String>,
+    pub skills: Vec<String>,
+    /// Share (0-100) of the job's skills the applicant has; 0 when the job lists none.
+    pub match_score: i64,
+    /// The job's skills the applicant has, as the job spells them.
+    pub matched_skills: Vec<String>,
+    pub recruiter_rating: Option<i16>,
+    pub note_count: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApplicantSort {
+    Date,
+    Match,
+}
+
+pub struct ApplicantQuery {
+    pub statuses: Option<Vec<ApplicationStatus>>,
+    pub min_match: i64,
+    pub sort: ApplicantSort,
+}
+
+impl ApplicantQuery {
+    /// Validates the query string of the dashboard.
+    pub fn from_query(filters: ApplicantFilters) -> Result<Self, ApiError> {
+        let statuses = match filters.status.as_deref() {
+            None => None,
+            Some(list) => {
+                let statuses = list
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        ApplicationStatus::parse(s)
+                            .ok_or_else(|| ApiError::bad_request(format!("Unknown application status {}", s)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                (!statuses.is_empty()).then_some(statuses)
+            }
+        };
+        let sort = match filters.sort.as_deref() {
+            None | Some("date") => ApplicantSort::Date,
+            Some("match") => ApplicantSort::Match,
+            Some(_) => return Err(ApiError::bad_request("sort must be `date` or `match`")),
+        };
+        let min_match = filters.min_match.unwrap_or(0);
+        if !(0..=100).contains(&min_match) {
+            return Err(ApiError::bad_request("min_match must be between 0 and 100"));
+        }
+        Ok(ApplicantQuery { statuses, min_match, sort })
+    }
+}
+
+/// A status change and the history entry it wrote.
+#[derive(Serialize)]
+pub struct StatusChange {
+    pub application: JobApplication,
+    pub event: ApplicationEvent,
+}
+
+/// Applications a bulk change left alone, with why.
+#[derive(Serialize)]
+pub struct SkippedApplication {
+    pub id: Uuid,
+    pub status: String,
+    pub reason: String,
+}
+
+#[derive(Serialize)]
+pub struct FunnelStage {
+    pub status: ApplicationStatus,
+    /// Applications at this stage now.
+    pub current: i64,
+    /// Applications that got this far, including those that moved on, were rejected or withdrew later.
+    pub reached: i64,
+}
+
+#[derive(Serialize)]
+pub struct Funnel {
+    pub job_id: Uuid,
+    pub total: i64,
+    pub stages: Vec<FunnelStage>,
+    pub rejected: i64,
+    pub withdrawn: i64,
+}
+
+/// The job an application belongs to, for the permission check.
+pub async fn job_of_application(db: &PgPool, application_id: Uuid) -> Result<Uuid, ApiError> {
+    sqlx::query_scalar!("SELECT job_id FROM job_applications WHERE id = $1", application_id)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db("Error retrieving application"))?
+        .flatten()
+        .ok_or_else(|| ApiError::not_found("Application not found"))
+}
+
+/// One page of the applications to `job_id`, filtered and sorted for the dashboard. Contact
+/// details follow the applicants' privacy settings unless `show_contact` (admins).
+pub async fn list_applicants(
+    db: &PgPool,
+    job_id: Uuid,
+    query: &ApplicantQuery,
+    page: &PageParams,
+    show_contact: bool,
+) -> Result<Page<Applicant>, ApiError> {
+    let after = page.after()?;
+    let (after_clause, order) = match query.sort {
+        ApplicantSort::Date => ("(application_date, id) < ($5, $6)", "application_date DESC, id DESC"),
+        ApplicantSort::Match => (
+            "(match_score, application_date, id) < ($4, $5, $6)",
+            "match_score DESC, application_date DESC, id DESC",
+        ),
+    };
+    let rank = match (query.sort, after) {
+        (ApplicantSort::Match, Some(cursor)) => {
+            Some(cursor.rank.ok_or_else(|| ApiError::bad_request("Invalid cursor"))?)
+        }
+        _ => None,
+    };
+
+    let sql = format!(
+        r#"
+        WITH applicants AS (
+            SELECT a.id, a.job_id, a.applicant_id, a.cover_letter, a.resume_url, a.application_date,
+                a.status, a.updated_at, a.recruiter_rating,
+                u.first_name, u.last_name,
+                CASE WHEN u.show_email OR $8 THEN u.email END AS email,
+                CASE WHEN u.show_mobile OR $8 THEN u.mobile END AS mobile,
+                u.profile_pic, u.organisation, u.location,
+                COALESCE(u.skills, '{{}}') AS skills,
+                m.matched_skills,
+                CASE WHEN cardinality(j.skills) > 0
+                    THEN 100 * cardinality(m.matched_skills) / cardinality(j.skills) ELSE 0
+                END::bigint AS match_score,
+                (SELECT count(*) FROM job_application_notes n WHERE n.application_id = a.id) AS note_count
+            FROM job_applications a
+            JOIN job_listings j ON j.id = a.job_id
+            JOIN users u ON u.id = a.applicant_id
+            CROSS JOIN LATERAL (
+                SELECT ARRAY(
+                    SELECT s FROM unnest(COALESCE(j.skills, '{{}}')) s
+                    WHERE lower(s) IN (SELECT lower(us) FROM unnest(u.skills) us)
+                ) AS matched_skills
+            ) m
+            WHERE a.job_id = $1
+        )
+        SELECT * FROM applicants
+        WHERE ($2::text[] IS NULL OR status = ANY($2))
+          AND match_score >= $3
+          AND ($5::timestamp IS NULL OR {after_clause})
+        ORDER BY {order}
+        LIMIT $7
+        "#,
+    );
+
+    let statuses: Option<Vec<&str>> =
+        query.statuses.as_ref().map(|statuses| statuses.iter().map(|s| s.as_str()).collect());
+    let applicants = sqlx::query_as::<_, Applicant>(&sql)
+        .bind(job_id)
+        .bind(statuses)
+        .bind(query.min_match)
+        .bind(rank)
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(page.fetch_limit())
+        .bind(show_contact)
+        .fetch_all(db)
+        .await
+        .map_err(ApiError::db("Error fetching applications"))?;
+
+    Ok(Page::new(applicants, page, |a| match query.sort {
+        ApplicantSort::Date => Cursor::new(a.application_date, a.id),
+        ApplicantSort::Match => Cursor::ranked(a.match_score, a.application_date, a.id),
+    }))
+}
+
+/// How many of the job's applications are at, and got as far as, each stage of the pipeline.
+pub async fn funnel(db: &PgPool, job_id: Uuid) -> Result<Funnel, ApiError> {
+    let pipeline: Vec<&str> = ApplicationStatus::PIPELINE.iter().map(|s| s.as_str()).collect();
+    // The furthest stage comes from the history, so rejected and withdrawn applications still
+    // count for the stages they passed; stages skipped on the way count as passed too.
+    let rows: Vec<(String, i32, i64)> = sqlx::query_as(
+        r#"
+        SELECT a.status,
+            GREATEST(
+                COALESCE(array_position($2, a.status), 1),
+                COALESCE((
+                    SELECT max(array_position($2, e.to_status))
+                    FROM job_application_events e WHERE e.application_id = a.id
+                ), 1)
+            ) AS furthest,
+            count(*)
+        FROM job_applications a
+        WHERE a.job_id = $1
+        GROUP BY 1, 2
+        "#,
+    )
+    .bind(job_id)
+    .bind(&pipeline)
+    .fetch_all(db)
+    .await
+    .map_err(ApiError::db("Error counting applications"))?;
+
+    let mut current: HashMap<String, i64> = HashMap::new();
+    let mut furthest = [0i64; ApplicationStatus::PIPELINE.len()];
+    for (status, stage, count) in rows {
+        *current.entry(status).or_default() += count;
+        furthest[(stage.max(1) as usize - 1).min(furthest.len() - 1)] += count;
+    }
+    let stages = ApplicationStatus::PIPELINE
+        .iter()
+        .enumerate()
+        .map(|(i, status)| FunnelStage {
+            status: *status,
+            current: current.get(status.as_str()).copied().unwrap_or(0),
+            reached: furthest[i..].iter().sum(),
+        })
+        .collect();
+
+    Ok(Funnel {
+        job_id,
+        total: current.values().sum(),
+        stages,
+        rejected: current.get(ApplicationStatus::Rejected.as_str()).copied().unwrap_or(0),
+        withdrawn: current.get(ApplicationStatus::Withdrawn.as_str()).copied().unwrap_or(0),
+    })
+}
+
+/// Moves one application to `next` on behalf of the job's poster; 409 when the pipeline does
+/// not allow it (see [`ApplicationStatus::can_move_to`]).
+pub async fn change_status(
+    conn: &mut PgConnection,
+    application_id: Uuid,
+    next: ApplicationStatus,
+    principal: &Principal,
+    note: Option<&str>,
+) -> Result<StatusChange, ApiError> {
+    let current = sqlx::query_scalar!("SELECT status FROM job_applications WHERE id = $1 FOR UPDATE", application_id)
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(ApiError::db("Error updating application status"))?
+        .ok_or_else(|| ApiError::not_found("Application not found"))?;
+    let current = parse_status(&current)?;
+    if !current.can_move_to(next) {
+        return Err(ApiError::conflict(transition_error(current, next)));
+    }
+    apply_status(conn, application_id, current, next, principal, note).await
+}
+
+/// Moves the applications of `job_id` that can go to `next` and reports the others. All the
+/// ids must be applications to that job.
+pub async fn change_status_in_bulk(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    application_ids: &[Uuid],
+    next: ApplicationStatus,
+    principal: &Principal,
+    note: Option<&str>,
+) -> Result<(Vec<StatusChange>, Vec<SkippedApplication>), ApiError> {
+    // Locked in id order so concurrent bulk changes cannot deadlock.
+    let rows = sqlx::query!(
+        "SELECT id, status FROM job_applications WHERE job_id = $1 AND id = ANY($2) ORDER BY id FOR UPDATE",
+        job_id,
+        application_ids
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error updating application statuses"))?;
+    if let Some(missing) = application_ids.iter().find(|id| !rows.iter().any(|row| row.id == **id)) {
+        return Err(ApiError::not_found(format!("Application {} not found for this job", missing)));
+    }
+
+    let mut updated = Vec::new();
+    let mut skipped = Vec::new();
+    for row in rows {
+        let current = parse_status(&row.status)?;
+        if current.can_move_to(next) {
+            updated.push(apply_status(conn, row.id, current, next, principal, note).await?);
+        } else {
+            skipped.push(SkippedApplication { id: row.id, status: row.status, reason: transition_error(current, next) });
+        }
+    }
+    Ok((updated, skipped))
+}
+
+async fn apply_status(
+    conn: &mut PgConnection,
+    application_id: Uuid,
+    current: ApplicationStatus,
+    next: ApplicationStatus,
+    principal: &Principal,
+    note: Option<&str>,
+) -> Result<StatusChange, ApiError> {
+    let application = sqlx::query_as::<_, JobApplication>(
+        "UPDATE job_applications SET status = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+    )
+    .bind(application_id)
+    .bind(next.as_str())
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error updating application status"))?;
+    let event = record_status_change(conn, application_id, Some(current), next, principal, note).await?;
+    Ok(StatusChange { application, event })
+}
+
+/// Adds a status change made by `principal` to the application's history.
+pub async fn record_status_change(
+    conn: &mut PgConnection,
+    application_id: Uuid,
+    from: Option<ApplicationStatus>,
+    to: ApplicationStatus,
+    principal: &Principal,
+    note: Option<&str>,
+) -> Result<ApplicationEvent, ApiError> {
+    let note = note.map(str::trim).filter(|note| !note.is_empty());
+    sqlx::query_as::<_, ApplicationEvent>(
+        r#"
+        INSERT INTO job_application_events (
+            application_id, from_status, to_status,
+            changed_by_type, changed_by_user, changed_by_business, note
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+    .bind(application_id)
+    .bind(from.map(ApplicationStatus::as_str))
+    .bind(to.as_str())
+    .bind(principal.kind.as_str())
+    .bind((principal.kind == PrincipalKind::User).then_some(principal.id))
+    .bind((principal.kind == PrincipalKind::Business).then_some(principal.id))
+    .bind(note)
+    .fetch_one(conn)
+    .await
+    .map_err(ApiError::db("Error recording application history"))
+}
+
+/// Sets (or with `None` clears) the recruiter's 1-5 rating of an application.
+pub async fn rate(db: &PgPool, application_id: Uuid, rating: Option<i16>) -> Result<(), ApiError> {
+    if rating.is_some_and(|rating| !(1..=5).contains(&rating)) {
+        return Err(ApiError::bad_request("Rating must be between 1 and 5"));
+    }
+    sqlx::query!("UPDATE job_applications SET recruiter_rating = $2 WHERE id = $1", application_id, rating)
+        .execute(db)
+        .await
+        .map_err(ApiError::db("Error rating application"))?;
+    Ok(())
+}
+
+pub async fn add_note(
+    db: &PgPool,
+    application_id: Uuid,
+    principal: &Principal,
+    note: &str,
+) -> Result<ApplicationNote, ApiError> {
+    let note = note.trim();
+    if note.is_empty() {
+        return Err(ApiError::bad_request("Note must not be empty"));
+    }
+    sqlx::query_as::<_, ApplicationNote>(
+        r#"
+        INSERT INTO job_application_notes (application_id, written_by_type, written_by_user, written_by_business, note)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(application_id)
+    .bind(principal.kind.as_str())
+    .bind((principal.kind == PrincipalKind::User).then_some(principal.id))
+    .bind((principal.kind == PrincipalKind::Business).then_some(principal.id))
+    .bind(note)
+    .fetch_one(db)
+    .await
+    .map_err(ApiError::db("Error adding note"))
+}
+
+/// The notes on an application, newest first.
+pub async fn notes(db: &PgPool, application_id: Uuid, page: &PageParams) -> Result<Page<ApplicationNote>, ApiError> {
+    let after = page.after()?;
+    let notes = sqlx::query_as::<_, ApplicationNote>(
+        r#"
+        SELECT * FROM job_application_notes
+        WHERE application_id = $1
+          AND ($2::timestamp IS NULL OR (created_at, id) < ($2, $3))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(application_id)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
+    .fetch_all(db)
+    .await
+    .map_err(ApiError::db("Error fetching notes"))?;
+
+    Ok(Page::new(notes, page, |n| Cursor::new(n.created_at, n.id)))
+}
+
+/// Reads `job_applications.status`; the CHECK constraint keeps it to the known statuses.
+pub fn parse_status(status: &str) -> Result<ApplicationStatus, ApiError> {
+    ApplicationStatus::parse(status).ok_or_else(|| ApiError::internal(format!("Unknown application status {}", status)))
+}
+
+fn transition_error(current: ApplicationStatus, next: ApplicationStatus) -> String {
+    format!("Cannot move an application from {} to {}", current.as_str(), next.as_str())
+}
@@ -0,0 +1,307 @@
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::http::Method;
+use actix_web::{web, Error, FromRequest, HttpMessage, HttpRequest, ResponseError};
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::future::{ready, Ready};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::models::business_accounts::BusinessAccount;
+use crate::models::user::User;
+use crate::session;
+
+// Write endpoints that must stay reachable without a token (sign-up flows).
+const PUBLIC_WRITE_PATHS: &[&str] = &["/users/create", "/business-accounts/create"];
+const PUBLIC_WRITE_PREFIXES: &[&str] = &["/auth/"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrincipalKind {
+    User,
+    Business,
+}
+
+impl PrincipalKind {
+    /// Matches the `author_type` / `user_type` / `creator_type` strings stored in the DB.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrincipalKind::User => "user",
+            PrincipalKind::Business => "business",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "user" => Some(PrincipalKind::User),
+            "business" => Some(PrincipalKind::Business),
+            _ => None,
+        }
+    }
+}
+
+// The frontend decodes email/name/profile_pic straight from the token, so keep them.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub principal: PrincipalKind,
+    /// Row in `sessions` the token was issued for; revoking it invalidates the token.
+    pub sid: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub profile_pic: Option<String>,
+    pub exp: usize,
+}
+
+impl Claims {
+    pub fn for_user(user: &User, session_id: Uuid, config: &Config) -> Self {
+        Claims {
+            sub: user.id,
+            principal: PrincipalKind::User,
+            sid: session_id,
+            email: user.email.clone(),
+            name: Some(format!("{} {}", user.first_name, user.last_name)),
+            profile_pic: user.profile_pic.clone(),
+            exp: access_token_expiry(config),
+        }
+    }
+
+    pub fn for_business(account: &BusinessAccount, session_id: Uuid, config: &Config) -> Self {
+        Claims {
+            sub: account.id,
+            principal: PrincipalKind::Business,
+            sid: session_id,
+            email: account.email.clone(),
+            name: Some(account.company_name.clone()),
+            profile_pic: account.logo_url.clone(),
+            exp: access_token_expiry(config),
+        }
+    }
+}
+
+fn access_token_expiry(config: &Config) -> usize {
+    (Utc::now() + chrono::Duration::minutes(config.access_token_ttl_minutes)).timestamp() as usize
+}
+
+/// Body returned by the login and refresh endpoints.
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+    pub refresh_token: String,
+    /// Seconds until `token` expires.
+    pub expires_in: i64,
+    pub principal: PrincipalKind,
+    pub id: Uuid,
+}
+
+pub fn encode_token(claims: &Claims, config: &Config) -> Result<String, jsonwebtoken::errors::Error> {
+    encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )
+}
+
+pub fn decode_token(token: &str, config: &Config) -> Result<Claims, jsonwebtoken::errors::Error> {
+    decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map(|data| data.claims)
+}
+
+/// The authenticated caller, resolved from the bearer token by [`authenticate`].
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub id: Uuid,
+    pub kind: PrincipalKind,
+    pub session_id: Uuid,
+    /// Platform admin (`users.role = 'admin'`). Business accounts are never admins.
+    pub is_admin: bool,
+}
+
+impl Principal {
+    /// Returns the caller's user id, or a 403 error if the caller is a business account.
+    pub fn user_id(&self) -> Result<Uuid, ApiError> {
+        match self.kind {
+            PrincipalKind::User => Ok(self.id),
+            PrincipalKind::Business => Err(ApiError::forbidden("This action is only available to user accounts")),
+        }
+    }
+
+    /// True when the caller is the given user or business.
+    pub fn is(&self, kind: PrincipalKind, id: Uuid) -> bool {
+        self.kind == kind && self.id == id
+    }
+
+    /// Returns the caller's business id, or a 403 error if the caller is a user account.
+    pub fn business_id(&self) -> Result<Uuid, ApiError> {
+        match self.kind {
+            PrincipalKind::Business => Ok(self.id),
+            PrincipalKind::User => Err(ApiError::forbidden("This action is only available to business accounts")),
+        }
+    }
+}
+
+impl FromRequest for Principal {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Principal>()
+                .cloned()
+                .ok_or_else(|| ApiError::unauthorized("Authentication required")),
+        )
+    }
+}
+
+fn bearer_token(req: &ServiceRequest) -> Option<&str> {
+    req.headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+fn is_public_write(path: &str) -> bool {
+    PUBLIC_WRITE_PATHS.contains(&path)
+        || PUBLIC_WRITE_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+fn requires_auth(req: &ServiceRequest) -> bool {
+    let safe = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    !safe && !is_public_write(req.path())
+}
+
+async fn resolve_principal(db: &PgPool, claims: &Claims) -> Result<Option<Principal>, sqlx::Error> {
+    if !session::is_session_active(db, claims.sid, claims.principal, claims.sub).await? {
+        return Ok(None);
+    }
+
+    // `Some(is_admin)` when the account exists and is active.
+    let account = match claims.principal {
+        PrincipalKind::User => sqlx::query_scalar!(
+            "SELECT role = 'admin' FROM users WHERE id = $1 AND COALESCE(is_active, true)",
+            claims.sub
+        )
+        .fetch_optional(db)
+        .await?
+        .map(|is_admin| is_admin.unwrap_or(false)),
+        PrincipalKind::Business => sqlx::query_scalar!(
+            "SELECT EXISTS(SELECT 1 FROM business_accounts WHERE id = $1)",
+            claims.sub
+        )
+        .fetch_one(db)
+        .await?
+        .unwrap_or(false)
+        .then_some(false),
+    };
+
+    Ok(account.map(|is_admin| Principal {
+        id: claims.sub,
+        kind: claims.principal,
+        session_id: claims.sid,
+        is_admin,
+    }))
+}
+
+/// Validates the bearer token (when present) and stores the resolved [`Principal`]
+/// in the request extensions. Writes outside the public sign-up paths are rejected
+/// with 401 when no valid token is supplied.
+pub async fn authenticate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    let principal = match bearer_token(&req).map(str::to_owned) {
+        Some(token) => {
+            let config = req.app_data::<web::Data<Config>>().cloned();
+            let db = req.app_data::<web::Data<PgPool>>().cloned();
+            let (Some(config), Some(db)) = (config, db) else {
+                return Ok(reject(req, ApiError::internal("Authentication is not configured")));
+            };
+
+            let claims = match decode_token(&token, &config) {
+                Ok(claims) => claims,
+                Err(_) => return Ok(reject(req, ApiError::unauthorized("Invalid or expired token"))),
+            };
+
+            match resolve_principal(&db, &claims).await {
+                Ok(Some(principal)) => Some(principal),
+                Ok(None) => {
+                    return Ok(reject(req, ApiError::unauthorized("Session has been revoked or the account no longer exists")));
+                }
+                Err(e) => {
+                    eprintln!("Database error resolving principal: {:?}", e);
+                    return Ok(reject(req, ApiError::internal("Error validating token")));
+                }
+            }
+        }
+        None => None,
+    };
+
+    match principal {
+        Some(principal) => {
+            req.extensions_mut().insert(principal);
+        }
+        None if requires_auth(&req) => {
+            return Ok(reject(req, ApiError::unauthorized("Authentication required")));
+        }
+        None => {}
+    }
+
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+fn reject<B>(
+    req: ServiceRequest,
+    error: ApiError,
+) -> ServiceResponse<EitherBody<B>> {
+    req.into_response(error.error_response())
+        .map_into_right_body()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_claims() -> Claims {
+        Claims {
+            sub: Uuid::new_v4(),
+            principal: PrincipalKind::Business,
+            sid: Uuid::new_v4(),
+            email: "acme@example.com".to_string(),
+            name: Some("Acme Corp".to_string()),
+            profile_pic: None,
+            exp: (Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+        }
+    }
+
+    #[test]
+    fn test_token_roundtrip() {
+        let config = Config::default();
+        let claims = sample_claims();
+        let token = encode_token(&claims, &config).unwrap();
+        let decoded = decode_token(&token, &config).unwrap();
+        assert_eq!(decoded.sub, claims.sub);
+        assert_eq!(decoded.principal, PrincipalKind::Business);
+    }
+
+    #[test]
+    fn test_token_rejected_with_wrong_secret() {
+        let config = Config::default();
+        let token = encode_token(&sample_claims(), &config).unwrap();
+        let other = Config {
+            jwt_secret: "another_secret".to_string(),
+            ..Config::default()
+        };
+        assert!(decode_token(&token, &other).is_err());
+    }
+}
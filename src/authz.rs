@@ -0,0 +1,390 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::{Principal, PrincipalKind};
+use crate::error::ApiError;
+
+/// Role inside a community. The community creator is always its owner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommunityRole {
+    Member,
+    Moderator,
+    Owner,
+}
+
+impl CommunityRole {
+    /// Parses `community_members.role`.
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "member" => Some(CommunityRole::Member),
+            "moderator" => Some(CommunityRole::Moderator),
+            "owner" => Some(CommunityRole::Owner),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Edit,
+    Delete,
+}
+
+const PERMISSION_CHECK_FAILED: &str = "Error checking permissions";
+
+/// The caller's role in a community, or `None` when they are not a member.
+/// Fails with 404 when the community does not exist.
+pub async fn community_role(
+    db: &PgPool,
+    principal: &Principal,
+    community_id: Uuid,
+) -> Result<Option<CommunityRole>, ApiError> {
+    let community = sqlx::query!(
+        r#"
+        SELECT c.creator_id, c.creator_business_id, cm.role AS "member_role?"
+        FROM communities c
+        LEFT JOIN community_members cm ON cm.community_id = c.id AND cm.user_id = $2
+        WHERE c.id = $1
+        "#,
+        community_id,
+        principal.id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+    .ok_or_else(|| ApiError::not_found("community not found"))?;
+
+    let creator = match principal.kind {
+        PrincipalKind::User => community.creator_id,
+        PrincipalKind::Business => community.creator_business_id,
+    };
+    if creator == Some(principal.id) {
+        return Ok(Some(CommunityRole::Owner));
+    }
+
+    Ok(match principal.kind {
+        PrincipalKind::User => community.member_role.as_deref().and_then(CommunityRole::from_db),
+        PrincipalKind::Business => None,
+    })
+}
+
+/// Requires at least `required` in the community (admins always pass).
+pub async fn authorize_community(
+    db: &PgPool,
+    principal: &Principal,
+    community_id: Uuid,
+    required: CommunityRole,
+) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+    match community_role(db, principal, community_id).await? {
+        Some(role) if role >= required => Ok(()),
+        _ => Err(ApiError::forbidden(match required {
+            CommunityRole::Owner => "Only the community owner can do this",
+            _ => "Only community moderators can do this",
+        })),
+    }
+}
+
+/// Authors may edit and delete their posts; community moderators may also delete them.
+pub async fn authorize_post(
+    db: &PgPool,
+    principal: &Principal,
+    post_id: Uuid,
+    action: Action,
+) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let post = sqlx::query!("SELECT author_id, community_id FROM posts WHERE id = $1", post_id)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+        .ok_or_else(|| ApiError::not_found("post not found"))?;
+
+    if post.author_id.is_some_and(|author| principal.is(PrincipalKind::User, author)) {
+        return Ok(());
+    }
+    if action == Action::Delete
+        && let Some(community_id) = post.community_id
+        && community_role(db, principal, community_id).await? >= Some(CommunityRole::Moderator)
+    {
+        return Ok(());
+    }
+    Err(ApiError::forbidden("You can only modify your own posts"))
+}
+
+/// Authors may edit and delete their comments; moderators of the post's community may also delete them.
+pub async fn authorize_comment(
+    db: &PgPool,
+    principal: &Principal,
+    comment_id: Uuid,
+    action: Action,
+) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let comment = sqlx::query!(
+        r#"
+        SELECT c.author_id, c.author_type, p.community_id AS "community_id?"
+        FROM comments c
+        LEFT JOIN posts p ON p.id = c.post_id
+        WHERE c.id = $1
+        "#,
+        comment_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+    .ok_or_else(|| ApiError::not_found("comment not found"))?;
+
+    let author_kind = comment
+        .author_type
+        .as_deref()
+        .and_then(PrincipalKind::from_db)
+        .unwrap_or(PrincipalKind::User);
+    if comment.author_id.is_some_and(|author| principal.is(author_kind, author)) {
+        return Ok(());
+    }
+    if action == Action::Delete
+        && let Some(community_id) = comment.community_id
+        && community_role(db, principal, community_id).await? >= Some(CommunityRole::Moderator)
+    {
+        return Ok(());
+    }
+    Err(ApiError::forbidden("You can only modify your own comments"))
+}
+
+/// Only the business that posted a job may change it.
+pub async fn authorize_job(db: &PgPool, principal: &Principal, job_id: Uuid) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let posted_by = sqlx::query_scalar!("SELECT posted_by FROM job_listings WHERE id = $1", job_id)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+        .ok_or_else(|| ApiError::not_found("Job not found"))?;
+
+    if principal.is(PrincipalKind::Business, posted_by) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("Only the business that posted this job can modify it"))
+    }
+}
+
+/// An application is visible to the applicant and to the business that posted the job.
+pub async fn authorize_application_party(
+    db: &PgPool,
+    principal: &Principal,
+    application_id: Uuid,
+) -> Result<(), ApiError> {
+    let parties = sqlx::query!(
+        r#"
+        SELECT a.applicant_id, j.posted_by AS "posted_by?"
+        FROM job_applications a
+        LEFT JOIN job_listings j ON j.id = a.job_id
+        WHERE a.id = $1
+        "#,
+        application_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+    .ok_or_else(|| ApiError::not_found("Application not found"))?;
+
+    if principal.is_admin {
+        return Ok(());
+    }
+    let is_applicant = parties.applicant_id.is_some_and(|id| principal.is(PrincipalKind::User, id));
+    let is_poster = parties.posted_by.is_some_and(|id| principal.is(PrincipalKind::Business, id));
+    if is_applicant || is_poster {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("Only the applicant and the business that posted the job can view this application"))
+    }
+}
+
+/// A resume is visible to its owner and to the businesses whose jobs it was sent to.
+pub async fn authorize_resume(db: &PgPool, principal: &Principal, resume_id: Uuid) -> Result<(), ApiError> {
+    let resume = sqlx::query!(
+        r#"
+        SELECT r.user_id, EXISTS(
+            SELECT 1 FROM job_applications a
+            JOIN job_listings j ON j.id = a.job_id
+            WHERE a.resume_id = r.id AND j.posted_by = $2
+        ) AS "sent_to_viewer!"
+        FROM resumes r
+        WHERE r.id = $1
+        "#,
+        resume_id,
+        principal.id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+    .ok_or_else(|| ApiError::not_found("Resume not found"))?;
+
+    let is_owner = principal.is(PrincipalKind::User, resume.user_id);
+    let is_recipient = principal.kind == PrincipalKind::Business && resume.sent_to_viewer;
+    if principal.is_admin || is_owner || is_recipient {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("Only the applicant and the businesses they applied to can view this resume"))
+    }
+}
+
+/// Only the user themselves may change their account.
+pub async fn authorize_user_account(db: &PgPool, principal: &Principal, email: &str) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let user_id = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    if principal.is(PrincipalKind::User, user_id) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("You can only modify your own account"))
+    }
+}
+
+/// Only the user themselves may read their personalized data (feed, recommendations).
+pub fn authorize_own_data(principal: &Principal, user_id: Uuid) -> Result<(), ApiError> {
+    if principal.is_admin || principal.is(PrincipalKind::User, user_id) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("You can only view your own data"))
+    }
+}
+
+/// Only the business itself may change its account.
+pub async fn authorize_business_account(
+    db: &PgPool,
+    principal: &Principal,
+    email: &str,
+) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let business_id = sqlx::query_scalar!("SELECT id FROM business_accounts WHERE email = $1", email)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+        .ok_or_else(|| ApiError::not_found("Business account not found"))?;
+
+    if principal.is(PrincipalKind::Business, business_id) {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("You can only modify your own business account"))
+    }
+}
+
+/// Users join as plain members themselves; moderators add members; only the owner hands out
+/// the moderator or owner role.
+pub async fn authorize_add_member(
+    db: &PgPool,
+    principal: &Principal,
+    community_id: Uuid,
+    user_id: Uuid,
+    role: CommunityRole,
+) -> Result<(), ApiError> {
+    let caller_role = community_role(db, principal, community_id).await?;
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let allowed = match role {
+        CommunityRole::Member => {
+            principal.is(PrincipalKind::User, user_id) || caller_role >= Some(CommunityRole::Moderator)
+        }
+        CommunityRole::Moderator | CommunityRole::Owner => caller_role == Some(CommunityRole::Owner),
+    };
+    if allowed {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("You do not have permission to add this member"))
+    }
+}
+
+/// Only the community owner changes member roles.
+pub async fn authorize_update_member(
+    db: &PgPool,
+    principal: &Principal,
+    member_id: Uuid,
+) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let community_id = sqlx::query_scalar!("SELECT community_id FROM community_members WHERE id = $1", member_id)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+        .ok_or_else(|| ApiError::not_found("community member not found"))?;
+
+    match community_id {
+        Some(community_id) => authorize_community(db, principal, community_id, CommunityRole::Owner).await,
+        None => Err(ApiError::forbidden("Only the community owner can do this")),
+    }
+}
+
+/// Members may leave; moderators remove plain members; the owner removes anyone.
+pub async fn authorize_remove_member(
+    db: &PgPool,
+    principal: &Principal,
+    member_id: Uuid,
+) -> Result<(), ApiError> {
+    if principal.is_admin {
+        return Ok(());
+    }
+
+    let member = sqlx::query!(
+        "SELECT community_id, user_id, role FROM community_members WHERE id = $1",
+        member_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db(PERMISSION_CHECK_FAILED))?
+    .ok_or_else(|| ApiError::not_found("community member not found"))?;
+
+    if member.user_id.is_some_and(|user_id| principal.is(PrincipalKind::User, user_id)) {
+        return Ok(());
+    }
+    let Some(community_id) = member.community_id else {
+        return Err(ApiError::forbidden("You do not have permission to remove this member"));
+    };
+
+    let caller_role = community_role(db, principal, community_id).await?;
+    let member_role = member.role.as_deref().and_then(CommunityRole::from_db);
+    let allowed = caller_role == Some(CommunityRole::Owner)
+        || (caller_role == Some(CommunityRole::Moderator) && member_role <= Some(CommunityRole::Member));
+    if allowed {
+        Ok(())
+    } else {
+        Err(ApiError::forbidden("You do not have permission to remove this member"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_community_roles_are_ordered() {
+        assert!(CommunityRole::Owner > CommunityRole::Moderator);
+        assert!(CommunityRole::Moderator > CommunityRole::Member);
+        assert!(None < Some(CommunityRole::Member));
+        assert_eq!(CommunityRole::from_db("moderator"), Some(CommunityRole::Moderator));
+        assert_eq!(CommunityRole::from_db("admin"), None);
+    }
+}
@@ -0,0 +1,464 @@
+// Maintenance commands for the backend.
+// Run with: cargo run --bin powerbrilliant-admin -- <command>   (see --help)
+
+use std::env;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveTime, Utc};
+use clap::{Parser, Subcommand};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use server::auth::PrincipalKind;
+use server::config::{load_config, Config};
+use server::db::{init_db, run_migrations, MIGRATOR};
+use server::faker::{self, SeedOptions};
+use server::password::{hash_password, MIN_PASSWORD_LENGTH};
+use server::job_lifecycle;
+use server::models::job_list::SalaryRange;
+use server::rankings;
+use server::resumes;
+use server::session;
+
+type CommandResult = Result<(), Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(name = "powerbrilliant-admin", about = "Maintenance commands for the PowerBrilliant backend")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Apply pending database migrations
+    Migrate,
+    /// Insert a reproducible fake data set (users, follows, communities, posts, likes, comments,
+    /// jobs and applications). Run it against an empty database.
+    Seed {
+        /// RNG seed; the same seed and options always generate the same data
+        #[arg(long, default_value_t = 42)]
+        seed: u64,
+        #[arg(long, default_value_t = 100)]
+        users: usize,
+        #[arg(long, default_value_t = 10)]
+        businesses: usize,
+        #[arg(long, default_value_t = 50)]
+        communities: usize,
+        #[arg(long, default_value_t = 50)]
+        posts: usize,
+        #[arg(long, default_value_t = 100)]
+        jobs: usize,
+        /// Date of the newest generated row (YYYY-MM-DD); defaults to today
+        #[arg(long)]
+        anchor: Option<NaiveDate>,
+    },
+    /// Create an admin account, or promote the existing user with this email
+    CreateAdmin {
+        #[arg(long)]
+        email: String,
+        /// Required when the user does not exist yet
+        #[arg(long)]
+        password: Option<String>,
+        /// Defaults to the part of the email before the '@'
+        #[arg(long)]
+        username: Option<String>,
+        #[arg(long, default_value = "Admin")]
+        first_name: String,
+        #[arg(long, default_value = "User")]
+        last_name: String,
+    },
+    /// Mark a business account as verified
+    VerifyBusiness {
+        email: String,
+    },
+    /// Deactivate a user (by email or id) and revoke all of their sessions
+    DeactivateUser {
+        user: String,
+    },
+    /// Rebuild the indexes of the searchable tables (including the full-text GIN indexes of
+    /// /search) and refresh planner statistics
+    ReindexSearch,
+    /// Fill salary_min/salary_max/salary_currency of the jobs that only have a salary_range
+    /// label, parsed the way the API parses new labels. Run it once after upgrading.
+    BackfillSalaries,
+    /// Move the resumes that profiles and applications link under the public /uploads into
+    /// RESUME_DIR, so they are only served through GET /resumes/{id}/file
+    ImportLegacyResumes,
+    /// Recompute hot/trending post scores and trending tags now (the server also does this
+    /// every RANKINGS_REFRESH_SECONDS)
+    RefreshRankings,
+    /// Expire the jobs past their closes_at deadline now and notify their pending applicants
+    /// (the server also does this every JOB_CLOSE_CHECK_SECONDS)
+    CloseExpiredJobs,
+    /// Show the effective configuration and check the database connection
+    CheckConfig,
+}
+
+#[actix_web::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let config = load_config();
+
+    let result = match cli.command {
+        Command::CheckConfig => check_config(&config).await,
+        command => {
+            let db = init_db(&config).await;
+            run(command, &db, &config).await
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(command: Command, db: &PgPool, config: &Config) -> CommandResult {
+    match command {
+        Command::Migrate => {
+            run_migrations(db).await?;
+            println!("✅ Migrations: up to date ({} known)", MIGRATOR.iter().count());
+            Ok(())
+        }
+        Command::Seed { seed, users, businesses, communities, posts, jobs, anchor } => {
+            let anchor = anchor.unwrap_or_else(|| Utc::now().date_naive());
+            let options = SeedOptions {
+                seed,
+                users,
+                businesses,
+                communities,
+                posts,
+                jobs,
+                anchor: anchor.and_time(NaiveTime::MIN),
+            };
+            let summary = faker::seed(db, &options).await?;
+            println!("✅ Seeded with seed {} (password of every account: {})", seed, faker::SEED_PASSWORD);
+            println!("{:#?}", summary);
+            Ok(())
+        }
+        Command::CreateAdmin { email, password, username, first_name, last_name } => {
+            create_admin(db, &email, password, username, first_name, last_name).await
+        }
+        Command::VerifyBusiness { email } => verify_business(db, &email).await,
+        Command::DeactivateUser { user } => deactivate_user(db, &user).await,
+        Command::ReindexSearch => reindex_search(db).await,
+        Command::BackfillSalaries => backfill_salaries(db).await,
+        Command::ImportLegacyResumes => import_legacy_resumes(db, config).await,
+        Command::RefreshRankings => {
+            let summary = rankings::refresh(db, &config.trending_tag_windows, Utc::now().naive_utc()).await?;
+            println!(
+                "✅ Rankings refreshed: {} scored posts, {} trending tags",
+                summary.scored_posts, summary.trending_tags
+            );
+            Ok(())
+        }
+        Command::CloseExpiredJobs => {
+            let summary = job_lifecycle::close_expired(db, Utc::now().naive_utc()).await?;
+            println!(
+                "✅ Expired {} job(s), notified {} applicant(s)",
+                summary.expired_jobs, summary.notified_applicants
+            );
+            Ok(())
+        }
+        Command::CheckConfig => unreachable!("handled before connecting"),
+    }
+}
+
+async fn create_admin(
+    db: &PgPool,
+    email: &str,
+    password: Option<String>,
+    username: Option<String>,
+    first_name: String,
+    last_name: String,
+) -> CommandResult {
+    let email = email.to_lowercase();
+
+    let promoted = sqlx::query!(
+        "UPDATE users SET role = 'admin', updated_at = NOW() WHERE LOWER(email) = $1 RETURNING id",
+        email
+    )
+    .fetch_optional(db)
+    .await?;
+    if let Some(user) = promoted {
+        println!("✅ Promoted existing user {} ({}) to admin", email, user.id);
+        return Ok(());
+    }
+
+    let password = password.ok_or("No user with this email; pass --password to create one")?;
+    if password.len() < MIN_PASSWORD_LENGTH {
+        return Err(format!("Password must be at least {} characters", MIN_PASSWORD_LENGTH).into());
+    }
+    let password_hash = hash_password(&password).map_err(|e| format!("Password hashing error: {}", e))?;
+    let username = username.unwrap_or_else(|| email.split('@').next().unwrap_or("admin").to_string());
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO users (id, first_name, last_name, username, password, email, provider, role)
+        VALUES ($1, $2, $3, $4, $5, $6, 'local', 'admin')
+        RETURNING id
+        "#,
+        Uuid::new_v4(),
+        first_name,
+        last_name,
+        username,
+        password_hash,
+        email
+    )
+    .fetch_one(db)
+    .await?;
+
+    println!("✅ Created admin {} ({})", email, id);
+    Ok(())
+}
+
+async fn verify_business(db: &PgPool, email: &str) -> CommandResult {
+    let account = sqlx::query!(
+        r#"
+        UPDATE business_accounts SET is_verified = TRUE, updated_at = NOW()
+        WHERE LOWER(email) = $1
+        RETURNING id, company_name
+        "#,
+        email.to_lowercase()
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| format!("No business account with email {}", email))?;
+
+    println!("✅ Verified {} ({})", account.company_name, account.id);
+    Ok(())
+}
+
+async fn deactivate_user(db: &PgPool, user: &str) -> CommandResult {
+    // Accepts either the user id or the email address.
+    let id = sqlx::query_scalar!(
+        r#"
+        UPDATE users SET is_active = FALSE, updated_at = NOW()
+        WHERE id::text = $1 OR LOWER(email) = LOWER($1)
+        RETURNING id
+        "#,
+        user
+    )
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| format!("No user matches {}", user))?;
+
+    let revoked = session::revoke_all_sessions(db, PrincipalKind::User, id).await?;
+    println!("✅ Deactivated user {} and revoked {} session(s)", id, revoked);
+    Ok(())
+}
+
+// Tables behind /search; each has a generated `search_vector` with a GIN index.
+const SEARCHABLE_TABLES: [&str; 4] = ["users", "communities", "posts", "job_listings"];
+
+async fn reindex_search(db: &PgPool) -> CommandResult {
+    for table in SEARCHABLE_TABLES {
+        sqlx::query(&format!("REINDEX TABLE {table}")).execute(db).await?;
+        sqlx::query(&format!("ANALYZE {table}")).execute(db).await?;
+        println!("✅ Reindexed {}", table);
+    }
+    Ok(())
+}
+
+async fn backfill_salaries(db: &PgPool) -> CommandResult {
+    let labels = sqlx::query!(
+        r#"
+        SELECT id, salary_range AS "salary_range!" FROM job_listings
+        WHERE salary_range IS NOT NULL AND salary_min IS NULL AND salary_max IS NULL
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut filled = 0;
+    for row in &labels {
+        let Some(salary) = SalaryRange::parse(&row.salary_range) else {
+            continue;
+        };
+        sqlx::query!(
+            "UPDATE job_listings SET salary_min = $2, salary_max = $3, salary_currency = $4 WHERE id = $1",
+            row.id,
+            salary.min,
+            salary.max,
+            salary.currency
+        )
+        .execute(db)
+        .await?;
+        filled += 1;
+    }
+    println!("✅ Backfilled the salary of {} job(s); {} label(s) have no amount", filled, labels.len() - filled);
+    Ok(())
+}
+
+async fn import_legacy_resumes(db: &PgPool, config: &Config) -> CommandResult {
+    let links = sqlx::query!(
+        r#"
+        SELECT DISTINCT user_id AS "user_id!", url AS "url!" FROM (
+            SELECT id AS user_id, resume_url AS url FROM users
+            UNION ALL
+            SELECT applicant_id, resume_url FROM job_applications
+        ) links
+        WHERE user_id IS NOT NULL AND url LIKE '/uploads/%'
+        "#
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut imported = 0;
+    for link in &links {
+        let file_name = &link.url["/uploads/".len()..];
+        if file_name.is_empty() || file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+            println!("⚠️  Skipped {} of user {}: not a file in ./uploads", link.url, link.user_id);
+            continue;
+        }
+        let legacy_path = Path::new("./uploads").join(file_name);
+        let bytes = match std::fs::read(&legacy_path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                println!("⚠️  Skipped {} of user {}: {}", link.url, link.user_id, e);
+                continue;
+            }
+        };
+
+        let mut tx = db.begin().await?;
+        let resume = match resumes::store(&mut tx, &config.resume_dir, link.user_id, None, &bytes, true).await {
+            Ok(resume) => resume,
+            Err(e) => {
+                println!("⚠️  Skipped {} of user {}: {}", link.url, link.user_id, e);
+                continue;
+            }
+        };
+        sqlx::query!(
+            "UPDATE users SET resume_url = $3 WHERE id = $1 AND resume_url = $2",
+            link.user_id,
+            link.url,
+            resume.url
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE job_applications SET resume_id = $3, resume_url = $4 WHERE applicant_id = $1 AND resume_url = $2",
+            link.user_id,
+            link.url,
+            resume.id,
+            resume.url
+        )
+        .execute(&mut *tx)
+        .await?;
+        let still_linked = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM users WHERE resume_url = $1)
+                OR EXISTS (SELECT 1 FROM job_applications WHERE resume_url = $1) AS "linked!"
+            "#,
+            link.url
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        if !still_linked {
+            std::fs::remove_file(&legacy_path)?;
+        }
+        imported += 1;
+    }
+    println!("✅ Imported {} of {} legacy resume link(s) into {}", imported, links.len(), config.resume_dir);
+    Ok(())
+}
+
+async fn check_config(config: &Config) -> CommandResult {
+    let mut problems = Vec::new();
+
+    println!("🔍 Environment Variables:");
+    for (name, description, required) in [
+        ("DATABASE_URL", "PostgreSQL connection string", true),
+        ("GOOGLE_CLIENT_ID", "Google OAuth client ID", true),
+        ("GOOGLE_CLIENT_SECRET", "Google OAuth client secret", true),
+        ("OAUTH_REDIRECT_URL", "OAuth callback URL", true),
+        ("APP_ENV", "Application environment", false),
+        ("API_HOST", "Server host", false),
+        ("API_PORT", "Server port", false),
+        ("RUST_LOG", "Logging level", false),
+        ("JWT_SECRET", "JWT signing secret", false),
+        ("API_KEY", "General API key", false),
+        ("ALLOWED_ORIGINS", "CORS / redirect origins", false),
+        ("FRONTEND_REDIRECT_URL", "Frontend OAuth landing page", false),
+        ("RUN_MIGRATIONS", "Apply migrations on startup", false),
+        ("RANKINGS_REFRESH_SECONDS", "Interval of the rankings job", false),
+        ("TRENDING_TAG_WINDOWS", "Trending tag windows", false),
+        ("JOB_REAPPLY_POLICY", "Re-applying after a withdrawal", false),
+        ("RESUME_DIR", "Private resume storage", false),
+        ("JOB_CLOSE_CHECK_SECONDS", "Interval of the job expiry check", false),
+    ] {
+        match env::var(name) {
+            Ok(value) => println!("  ✅ {} = {} ({})", name, mask_sensitive(&value), description),
+            Err(_) if required => {
+                println!("  ❌ {} = <not set> ({})", name, description);
+                problems.push(format!("{} is required", name));
+            }
+            Err(_) => println!("  ➖ {} = <default> ({})", name, description),
+        }
+    }
+
+    println!("\n📊 Environment: {}", config.app_env);
+    println!("🔑 OAuth: google{}{}",
+        if config.github_oauth.is_some() { ", github" } else { "" },
+        if config.linkedin_oauth.is_some() { ", linkedin" } else { "" },
+    );
+    println!("🌐 Allowed origins: {}", config.allowed_origins.join(", "));
+
+    if config.is_production() {
+        if config.jwt_secret == Config::default().jwt_secret {
+            problems.push("JWT_SECRET must be changed in production".to_string());
+        }
+        if config.api_key == Config::default().api_key {
+            problems.push("API_KEY must be changed in production".to_string());
+        }
+    }
+
+    match PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(5))
+        .connect(&config.database_url)
+        .await
+    {
+        Ok(db) => {
+            println!("🔌 Database: reachable");
+            let applied: Vec<i64> = sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(&db)
+                .await
+                .unwrap_or_default();
+            let pending = MIGRATOR.iter().filter(|m| !applied.contains(&m.version)).count();
+            if pending == 0 {
+                println!("🗄️  Migrations: up to date");
+            } else {
+                println!("🗄️  Migrations: {} pending (run `powerbrilliant-admin migrate`)", pending);
+            }
+        }
+        Err(e) => problems.push(format!("Cannot connect to the database: {}", e)),
+    }
+
+    if problems.is_empty() {
+        println!("\n✅ Configuration looks good");
+        Ok(())
+    } else {
+        println!();
+        for problem in &problems {
+            println!("  ⚠️ {}", problem);
+        }
+        Err(format!("{} configuration problem(s) found", problems.len()).into())
+    }
+}
+
+fn mask_sensitive(value: &str) -> String {
+    if value.len() <= 8 {
+        "*".repeat(value.len().max(3))
+    } else {
+        format!("{}...{}", &value[..4], "*".repeat(value.len() - 4))
+    }
+}
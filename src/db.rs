@@ -1,7 +1,11 @@
+use sqlx::migrate::{MigrateError, Migrator};
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
 use crate::config::Config;
 
+/// The SQL files in `migrations/`, embedded at compile time.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
 pub async fn init_db(config: &Config) -> PgPool {
     PgPoolOptions::new()
         .max_connections(5)
@@ -9,3 +13,8 @@ pub async fn init_db(config: &Config) -> PgPool {
         .await
         .expect("Failed to connect to database")
 }
+
+/// Applies every migration that has not been recorded in `_sqlx_migrations` yet.
+pub async fn run_migrations(db: &PgPool) -> Result<(), MigrateError> {
+    MIGRATOR.run(db).await
+}
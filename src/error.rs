@@ -0,0 +1,195 @@
+use std::fmt;
+
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde_json::json;
+
+/// Error returned by every handler. Renders as
+/// `{"status": "error", "code": "<machine readable>", "message": "<human readable>"}`.
+///
+/// Database errors are logged where they are converted and never reach the client verbatim.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Unprocessable(String),
+    Internal(String),
+}
+
+// Postgres SQLSTATE codes we translate into client errors.
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+const NOT_NULL_VIOLATION: &str = "23502";
+const CHECK_VIOLATION: &str = "23514";
+const INVALID_TEXT_REPRESENTATION: &str = "22P02";
+
+impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError::BadRequest(message.into())
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        ApiError::Unauthorized(message.into())
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        ApiError::Forbidden(message.into())
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        ApiError::NotFound(message.into())
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        ApiError::Conflict(message.into())
+    }
+
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        ApiError::Unprocessable(message.into())
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        ApiError::Internal(message.into())
+    }
+
+    /// Maps a database error, using `context` as the message when the error is not one
+    /// the client can act on. Use as `.map_err(ApiError::db("Error retrieving users"))`.
+    pub fn db(context: &'static str) -> impl FnOnce(sqlx::Error) -> ApiError {
+        move |e| ApiError::from_sqlx(e, context)
+    }
+
+    fn from_sqlx(e: sqlx::Error, context: &str) -> Self {
+        if let sqlx::Error::RowNotFound = e {
+            return ApiError::not_found("Resource not found");
+        }
+        if let Some(db_error) = e.as_database_error() {
+            match db_error.code().as_deref() {
+                Some(UNIQUE_VIOLATION) => {
+                    return ApiError::conflict("A record with the same unique value already exists");
+                }
+                Some(FOREIGN_KEY_VIOLATION) => {
+                    return ApiError::unprocessable("A referenced record does not exist");
+                }
+                Some(NOT_NULL_VIOLATION) | Some(CHECK_VIOLATION) | Some(INVALID_TEXT_REPRESENTATION) => {
+                    return ApiError::unprocessable("One or more fields have an invalid value");
+                }
+                _ => {}
+            }
+        }
+        eprintln!("Database error ({}): {:?}", context, e);
+        ApiError::internal(context)
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unprocessable(_) => "unprocessable_entity",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::NotFound(message)
+            | ApiError::Conflict(message)
+            | ApiError::Unprocessable(message)
+            | ApiError::Internal(message) => message,
+        }
+    }
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        ApiError::from_sqlx(e, "Database error")
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status()).json(json!({
+            "status": "error",
+            "code": self.code(),
+            "message": self.message()
+        }))
+    }
+}
+
+/// Puts body/path/query extraction failures in the same envelope.
+pub fn json_config() -> actix_web::web::JsonConfig {
+    actix_web::web::JsonConfig::default()
+        .error_handler(|err, _| ApiError::bad_request(format!("Invalid JSON body: {}", err)).into())
+}
+
+pub fn path_config() -> actix_web::web::PathConfig {
+    actix_web::web::PathConfig::default()
+        .error_handler(|err, _| ApiError::bad_request(format!("Invalid path parameter: {}", err)).into())
+}
+
+pub fn query_config() -> actix_web::web::QueryConfig {
+    actix_web::web::QueryConfig::default()
+        .error_handler(|err, _| ApiError::bad_request(format!("Invalid query string: {}", err)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+
+    #[test]
+    fn test_row_not_found_maps_to_404() {
+        let error = ApiError::from(sqlx::Error::RowNotFound);
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error.code(), "not_found");
+    }
+
+    #[test]
+    fn test_other_database_errors_hide_details() {
+        let error = ApiError::db("Error retrieving users")(sqlx::Error::PoolTimedOut);
+        assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error.message(), "Error retrieving users");
+    }
+
+    #[actix_web::test]
+    async fn test_error_envelope() {
+        let response = ApiError::conflict("Email already registered").error_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({"status": "error", "code": "conflict", "message": "Email already registered"})
+        );
+    }
+}
@@ -1,294 +1,925 @@
-use fake::faker::{
-    internet::en::{SafeEmail, Username, Password},
-    name::en::{FirstName, LastName},
-    phone_number::en::CellNumber,
-    company::en::CompanyName,
-    lorem::en::{Sentence, Paragraph},
-};
+//! Reproducible fake data for development and load testing.
+//!
+//! Every value (ids included) comes from one RNG seeded with [`SeedOptions::seed`], so the same
+//! options always produce the same graph. Rows are generated table by table, keeping every
+//! reference valid (members belong to real communities, likers are members, replies are newer
+//! than their parent, ...), and are written with multi-row inserts inside one transaction.
+
+use std::collections::HashSet;
+
+use chrono::{Duration, NaiveDateTime};
+use fake::faker::address::en::CityName;
+use fake::faker::company::en::CompanyName;
 use fake::faker::job::en::Position;
-use fake::faker::boolean::en::Boolean;
-use fake::{Fake, Faker};
-use rand::seq::SliceRandom;
+use fake::faker::lorem::en::{Paragraph, Sentence};
+use fake::faker::name::en::{FirstName, LastName};
+use fake::faker::phone_number::en::CellNumber;
+use fake::rand::distr::weighted::WeightedIndex;
+use fake::rand::distr::Distribution;
+use fake::rand::rngs::StdRng;
+use fake::rand::seq::{index, IndexedRandom};
+use fake::rand::{Rng, SeedableRng};
+use fake::Fake;
+use serde_json::{json, Value};
+use sqlx::postgres::PgConnection;
+use sqlx::query_builder::Separated;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
-use sqlx::PgPool;
-use fake::faker::address::en::CityName;
-use chrono::Utc;
-use serde_json::json;
- 
-pub async fn seed_fake_users(pool: &PgPool, count: usize) {
-    for _ in 0..count {
-        let id = Uuid::new_v4();
-        let first_name: String = FirstName().fake();
-        let last_name: String = LastName().fake();
-        let username: String = Username().fake();
-        let password: String = Password(10..16).fake();
-        let email: String = SafeEmail().fake();
-        let mobile: String = CellNumber().fake();
-        let organisation: String = CompanyName().fake();
-        let bio = json!({
-            "about": Sentence(8..15).fake::<String>()
+
+use crate::password::hash_password;
+
+/// Password of every seeded user and business account.
+pub const SEED_PASSWORD: &str = "password123";
+
+pub struct SeedOptions {
+    pub seed: u64,
+    pub users: usize,
+    pub businesses: usize,
+    pub communities: usize,
+    pub posts: usize,
+    pub jobs: usize,
+    /// Newest timestamp in the data; rows are spread over the year before it.
+    pub anchor: NaiveDateTime,
+}
+
+/// Number of rows written per table.
+#[derive(Debug, Default)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub business_accounts: usize,
+    pub followers: usize,
+    pub communities: usize,
+    pub community_members: usize,
+    pub posts: usize,
+    pub post_likes: usize,
+    pub comments: usize,
+    pub comment_likes: usize,
+    pub job_listings: usize,
+    pub job_applications: usize,
+    pub job_application_events: usize,
+}
+
+const HISTORY_DAYS: i64 = 365;
+// Follower counts follow a Zipf distribution over a random ranking of the users.
+const ZIPF_EXPONENT: f64 = 1.0;
+const MAX_FOLLOWS_PER_USER: usize = 25;
+// Share of all users that joins the largest community; the n-th largest gets 1/n of that.
+const LARGEST_COMMUNITY_SHARE: f64 = 0.3;
+const MAX_LIKES_PER_POST: usize = 200;
+const MAX_COMMENTS_PER_POST: usize = 30;
+const REPLY_PROBABILITY: f64 = 0.4;
+const MAX_LIKES_PER_COMMENT: usize = 10;
+const MAX_APPLICATIONS_PER_JOB: usize = 50;
+
+const TOPICS: [&str; 16] = [
+    "rust", "webdev", "design", "ai", "data", "cloud", "devops", "security",
+    "mobile", "startups", "career", "opensource", "gaming", "fintech", "hardware", "product",
+];
+const COMMUNITY_SUFFIXES: [&str; 6] = ["Builders", "Circle", "Hub", "Guild", "Lab", "Collective"];
+const SKILLS: [&str; 16] = [
+    "Rust", "SQL", "React", "AWS", "Docker", "Kubernetes", "Python", "TypeScript",
+    "Node.js", "Go", "Java", "Figma", "Terraform", "PostgreSQL", "GraphQL", "Swift",
+];
+const INDUSTRIES: [&str; 6] = ["Software", "Finance", "Healthcare", "Education", "Retail", "Media"];
+const COMPANY_SIZES: [&str; 5] = ["1-10", "11-50", "51-200", "201-1000", "1000+"];
+const JOB_TYPES: [&str; 3] = ["remote", "hybrid", "onsite"];
+
+/// Pipeline an application moves through; each step may instead end in a rejection or withdrawal.
+const APPLICATION_PIPELINE: [&str; 6] = ["applied", "under_review", "shortlisted", "interview", "offered", "hired"];
+const ADVANCE_PROBABILITY: f64 = 0.5;
+const REJECT_PROBABILITY: f64 = 0.2;
+const WITHDRAW_PROBABILITY: f64 = 0.05;
+
+struct UserRow {
+    id: Uuid,
+    first_name: String,
+    last_name: String,
+    username: String,
+    email: String,
+    mobile: String,
+    organisation: String,
+    bio: Value,
+    profile_pic: String,
+    location: String,
+    skills: Vec<String>,
+    interests: Vec<String>,
+    resume_url: Option<String>,
+    is_active: bool,
+    show_email: bool,
+    created_at: NaiveDateTime,
+}
+
+struct BusinessRow {
+    id: Uuid,
+    company_name: String,
+    contact_person_name: String,
+    email: String,
+    address: String,
+    website: String,
+    industry: String,
+    size: String,
+    is_verified: bool,
+    created_at: NaiveDateTime,
+}
+
+struct FollowRow {
+    id: Uuid,
+    follower: usize,
+    followed: usize,
+    at: NaiveDateTime,
+}
+
+struct CommunityRow {
+    id: Uuid,
+    name: String,
+    description: Value,
+    creator: usize,
+    banner_url: String,
+    is_public: bool,
+    is_active: bool,
+    created_at: NaiveDateTime,
+}
+
+struct MemberRow {
+    id: Uuid,
+    community: usize,
+    user: usize,
+    role: &'static str,
+    joined_at: NaiveDateTime,
+}
+
+struct PostRow {
+    id: Uuid,
+    community: usize,
+    author: usize,
+    title: Value,
+    content: Value,
+    tags: Vec<String>,
+    likes: i32,
+    is_active: bool,
+    created_at: NaiveDateTime,
+}
+
+struct LikeRow {
+    id: Uuid,
+    target: usize,
+    user: usize,
+    at: NaiveDateTime,
+}
+
+struct CommentRow {
+    id: Uuid,
+    post: usize,
+    author: usize,
+    parent: Option<usize>,
+    content: Value,
+    created_at: NaiveDateTime,
+}
+
+struct JobRow {
+    id: Uuid,
+    business: usize,
+    title: Value,
+    description: Value,
+    location: String,
+    job_type: &'static str,
+    notice_period: String,
+    min_experience: i32,
+    skills: Vec<String>,
+    salary_min: i32,
+    salary_max: i32,
+    is_active: bool,
+    /// Deadline of some open jobs, always after the anchor so a fresh seed has nothing to expire.
+    closes_at: Option<NaiveDateTime>,
+    created_at: NaiveDateTime,
+}
+
+struct ApplicationRow {
+    id: Uuid,
+    job: usize,
+    applicant: usize,
+    cover_letter: String,
+    /// Status changes in order; the last one is the current status.
+    history: Vec<(&'static str, NaiveDateTime)>,
+    /// Ids of the `job_application_events` rows, one per history entry.
+    event_ids: Vec<Uuid>,
+}
+
+/// Generates the whole data set from `options` and inserts it in a single transaction.
+pub async fn seed(pool: &PgPool, options: &SeedOptions) -> Result<SeedSummary, sqlx::Error> {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let start = options.anchor - Duration::days(HISTORY_DAYS);
+    // Hashing once keeps large runs fast; the salt is the only non-reproducible value.
+    let password_hash = hash_password(SEED_PASSWORD).expect("hashing a constant password");
+
+    let users = gen_users(&mut rng, options.users, start, options.anchor);
+    let businesses = gen_businesses(&mut rng, options.businesses, start, options.anchor);
+    let follows = gen_follows(&mut rng, &users, options.anchor);
+    let communities = gen_communities(&mut rng, options.communities, &users, options.anchor);
+    let members = gen_members(&mut rng, &communities, &users, options.anchor);
+    let mut posts = gen_posts(&mut rng, options.posts, &communities, &members, options.anchor);
+    let post_likes = gen_post_likes(&mut rng, &mut posts, &members, options.anchor);
+    let comments = gen_comments(&mut rng, &posts, &members, options.anchor);
+    let comment_likes = gen_comment_likes(&mut rng, &comments, &users, options.anchor);
+    let jobs = gen_jobs(&mut rng, options.jobs, &businesses, options.anchor);
+    let applications = gen_applications(&mut rng, &jobs, &users, options.anchor);
+
+    let mut tx = pool.begin().await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO users (id, first_name, last_name, username, password, email, mobile, organisation, \
+         bio, profile_pic, location, skills, interests, resume_url, provider, is_active, show_email, \
+         created_at, updated_at) ",
+        19,
+        &users,
+        |mut b, u| {
+            b.push_bind(u.id)
+                .push_bind(&u.first_name)
+                .push_bind(&u.last_name)
+                .push_bind(&u.username)
+                .push_bind(&password_hash)
+                .push_bind(&u.email)
+                .push_bind(&u.mobile)
+                .push_bind(&u.organisation)
+                .push_bind(&u.bio)
+                .push_bind(&u.profile_pic)
+                .push_bind(&u.location)
+                .push_bind(&u.skills)
+                .push_bind(&u.interests)
+                .push_bind(&u.resume_url)
+                .push_bind("local")
+                .push_bind(u.is_active)
+                .push_bind(u.show_email)
+                .push_bind(u.created_at)
+                .push_bind(u.created_at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO business_accounts (id, company_name, contact_person_name, email, password_hash, address, \
+         website, industry, size, is_verified, created_at, updated_at) ",
+        12,
+        &businesses,
+        |mut b, a| {
+            b.push_bind(a.id)
+                .push_bind(&a.company_name)
+                .push_bind(&a.contact_person_name)
+                .push_bind(&a.email)
+                .push_bind(&password_hash)
+                .push_bind(&a.address)
+                .push_bind(&a.website)
+                .push_bind(&a.industry)
+                .push_bind(&a.size)
+                .push_bind(a.is_verified)
+                .push_bind(a.created_at)
+                .push_bind(a.created_at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO followers (id, follower_id, followed_id, followed_at, is_following, updated_at) ",
+        6,
+        &follows,
+        |mut b, f| {
+            b.push_bind(f.id)
+                .push_bind(users[f.follower].id)
+                .push_bind(users[f.followed].id)
+                .push_bind(f.at)
+                .push_bind(true)
+                .push_bind(f.at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO communities (id, name, description, creator_id, creator_type, banner_url, is_public, \
+         is_active, created_at) ",
+        9,
+        &communities,
+        |mut b, c| {
+            b.push_bind(c.id)
+                .push_bind(&c.name)
+                .push_bind(&c.description)
+                .push_bind(users[c.creator].id)
+                .push_bind("user")
+                .push_bind(&c.banner_url)
+                .push_bind(c.is_public)
+                .push_bind(c.is_active)
+                .push_bind(c.created_at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO community_members (id, community_id, user_id, role, joined_at) ",
+        5,
+        &members,
+        |mut b, m| {
+            b.push_bind(m.id)
+                .push_bind(communities[m.community].id)
+                .push_bind(users[m.user].id)
+                .push_bind(m.role)
+                .push_bind(m.joined_at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO posts (id, community_id, author_id, title, content, post_type, tags, likes, is_active, \
+         created_at) ",
+        10,
+        &posts,
+        |mut b, p| {
+            b.push_bind(p.id)
+                .push_bind(communities[p.community].id)
+                .push_bind(users[p.author].id)
+                .push_bind(&p.title)
+                .push_bind(&p.content)
+                .push_bind("text")
+                .push_bind(&p.tags)
+                .push_bind(p.likes)
+                .push_bind(p.is_active)
+                .push_bind(p.created_at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO post_likes (id, post_id, user_id, user_type, liked_at, is_liked, updated_at) ",
+        7,
+        &post_likes,
+        |mut b, l| {
+            b.push_bind(l.id)
+                .push_bind(posts[l.target].id)
+                .push_bind(users[l.user].id)
+                .push_bind("user")
+                .push_bind(l.at)
+                .push_bind(true)
+                .push_bind(l.at);
+        },
+    )
+    .await?;
+
+    // Parents come before their replies, so inserting in generation order satisfies the self-reference.
+    insert_batched(
+        &mut tx,
+        "INSERT INTO comments (id, post_id, author_id, author_type, content, parent_id, created_at) ",
+        7,
+        &comments,
+        |mut b, c| {
+            b.push_bind(c.id)
+                .push_bind(posts[c.post].id)
+                .push_bind(users[c.author].id)
+                .push_bind("user")
+                .push_bind(&c.content)
+                .push_bind(c.parent.map(|parent| comments[parent].id))
+                .push_bind(c.created_at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO comment_likes (id, comment_id, user_id, user_type, created_at) ",
+        5,
+        &comment_likes,
+        |mut b, l| {
+            b.push_bind(l.id)
+                .push_bind(comments[l.target].id)
+                .push_bind(users[l.user].id)
+                .push_bind("user")
+                .push_bind(l.at);
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO job_listings (id, posted_by, organisation, title, description, location, job_type, \
+         notice_period, min_experience, skills, salary_range, salary_min, salary_max, salary_currency, \
+         is_active, job_posted_by, created_at, updated_at, status, closes_at, closed_at) ",
+        21,
+        &jobs,
+        |mut b, j| {
+            let business = &businesses[j.business];
+            b.push_bind(j.id)
+                .push_bind(business.id)
+                .push_bind(&business.company_name)
+                .push_bind(&j.title)
+                .push_bind(&j.description)
+                .push_bind(&j.location)
+                .push_bind(j.job_type)
+                .push_bind(&j.notice_period)
+                .push_bind(j.min_experience)
+                .push_bind(&j.skills)
+                .push_bind(format!("{}-{} USD", j.salary_min, j.salary_max))
+                .push_bind(j.salary_min)
+                .push_bind(j.salary_max)
+                .push_bind("USD")
+                .push_bind(j.is_active)
+                .push_bind(&business.contact_person_name)
+                .push_bind(j.created_at)
+                .push_bind(j.created_at)
+                .push_bind(if j.is_active { "published" } else { "closed" })
+                .push_bind(j.closes_at)
+                .push_bind((!j.is_active).then_some(j.created_at));
+        },
+    )
+    .await?;
+
+    insert_batched(
+        &mut tx,
+        "INSERT INTO job_applications (id, job_id, applicant_id, cover_letter, resume_url, status, is_active, \
+         application_date, applied_at, updated_at) ",
+        10,
+        &applications,
+        |mut b, a| {
+            let (first_status, applied_at) = a.history[0];
+            let (status, updated_at) = *a.history.last().unwrap_or(&(first_status, applied_at));
+            b.push_bind(a.id)
+                .push_bind(jobs[a.job].id)
+                .push_bind(users[a.applicant].id)
+                .push_bind(&a.cover_letter)
+                .push_bind(&users[a.applicant].resume_url)
+                .push_bind(status)
+                .push_bind(status != "withdrawn")
+                .push_bind(applied_at)
+                .push_bind(applied_at)
+                .push_bind(updated_at);
+        },
+    )
+    .await?;
+
+    // The applicant applies and withdraws; every other change is made by the business.
+    let events: Vec<(usize, usize)> = applications
+        .iter()
+        .enumerate()
+        .flat_map(|(a, application)| (0..application.history.len()).map(move |h| (a, h)))
+        .collect();
+    insert_batched(
+        &mut tx,
+        "INSERT INTO job_application_events (id, application_id, from_status, to_status, changed_by_type, \
+         changed_by_user, changed_by_business, created_at) ",
+        8,
+        &events,
+        |mut b, &(a, h)| {
+            let application = &applications[a];
+            let (status, at) = application.history[h];
+            let by_applicant = h == 0 || status == "withdrawn";
+            b.push_bind(application.event_ids[h])
+                .push_bind(application.id)
+                .push_bind(h.checked_sub(1).map(|prev| application.history[prev].0))
+                .push_bind(status)
+                .push_bind(if by_applicant { "user" } else { "business" })
+                .push_bind(by_applicant.then(|| users[application.applicant].id))
+                .push_bind((!by_applicant).then(|| businesses[jobs[application.job].business].id))
+                .push_bind(at);
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(SeedSummary {
+        users: users.len(),
+        business_accounts: businesses.len(),
+        followers: follows.len(),
+        communities: communities.len(),
+        community_members: members.len(),
+        posts: posts.len(),
+        post_likes: post_likes.len(),
+        comments: comments.len(),
+        comment_likes: comment_likes.len(),
+        job_listings: jobs.len(),
+        job_applications: applications.len(),
+        job_application_events: events.len(),
+    })
+}
+
+// Postgres accepts at most 65535 bind parameters per statement.
+const MAX_BIND_PARAMS: usize = 65535;
+
+async fn insert_batched<'r, T>(
+    conn: &mut PgConnection,
+    insert: &str,
+    columns: usize,
+    rows: &'r [T],
+    mut push_row: impl FnMut(Separated<'_, 'r, Postgres, &'static str>, &'r T),
+) -> Result<(), sqlx::Error> {
+    for chunk in rows.chunks(MAX_BIND_PARAMS / columns) {
+        let mut builder = QueryBuilder::<Postgres>::new(insert);
+        builder.push_values(chunk, &mut push_row);
+        builder.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+fn gen_users(rng: &mut StdRng, count: usize, start: NaiveDateTime, end: NaiveDateTime) -> Vec<UserRow> {
+    (0..count)
+        .map(|i| {
+            let id = random_uuid(rng);
+            let first_name: String = FirstName().fake_with_rng(rng);
+            let last_name: String = LastName().fake_with_rng(rng);
+            // The index keeps usernames and emails unique however the names repeat.
+            let username = format!("{}{}{}", slug(&first_name), slug(&last_name), i);
+            UserRow {
+                id,
+                email: format!("{}@example.com", username),
+                profile_pic: format!("https://api.dicebear.com/6.x/identicon/svg?seed={}", username),
+                username,
+                first_name,
+                last_name,
+                mobile: CellNumber().fake_with_rng(rng),
+                organisation: CompanyName().fake_with_rng(rng),
+                bio: json!({ "en": Sentence(8..15).fake_with_rng::<String, _>(rng) }),
+                location: CityName().fake_with_rng(rng),
+                skills: pick(rng, &SKILLS, 2..6),
+                interests: pick(rng, &TOPICS, 2..5),
+                resume_url: rng
+                    .random_bool(0.6)
+                    .then(|| format!("https://example.com/resumes/{}.pdf", id)),
+                is_active: rng.random_bool(0.97),
+                show_email: rng.random_bool(0.3),
+                created_at: between(rng, start, end),
+            }
+        })
+        .collect()
+}
+
+fn gen_businesses(rng: &mut StdRng, count: usize, start: NaiveDateTime, end: NaiveDateTime) -> Vec<BusinessRow> {
+    (0..count)
+        .map(|i| {
+            let company_name: String = CompanyName().fake_with_rng(rng);
+            let domain = format!("{}{}.example.com", slug(&company_name), i);
+            BusinessRow {
+                id: random_uuid(rng),
+                contact_person_name: format!(
+                    "{} {}",
+                    FirstName().fake_with_rng::<String, _>(rng),
+                    LastName().fake_with_rng::<String, _>(rng)
+                ),
+                email: format!("hr@{}", domain),
+                website: format!("https://{}", domain),
+                company_name,
+                address: CityName().fake_with_rng(rng),
+                industry: INDUSTRIES.choose(rng).unwrap().to_string(),
+                size: COMPANY_SIZES.choose(rng).unwrap().to_string(),
+                is_verified: rng.random_bool(0.7),
+                created_at: between(rng, start, end),
+            }
+        })
+        .collect()
+}
+
+fn gen_follows(rng: &mut StdRng, users: &[UserRow], end: NaiveDateTime) -> Vec<FollowRow> {
+    if users.len() < 2 {
+        return Vec::new();
+    }
+    let popularity = zipf_weights(rng, users.len());
+    let followed_dist = WeightedIndex::new(&popularity).unwrap();
+    let max_follows = MAX_FOLLOWS_PER_USER.min(users.len() - 1);
+
+    let mut follows = Vec::new();
+    for follower in 0..users.len() {
+        let wanted = rng.random_range(0..=max_follows);
+        let mut seen = HashSet::new();
+        // Bounded retries: the most popular users are drawn over and over.
+        for _ in 0..wanted * 4 {
+            if seen.len() == wanted {
+                break;
+            }
+            let followed = followed_dist.sample(rng);
+            if followed != follower && seen.insert(followed) {
+                let since = users[follower].created_at.max(users[followed].created_at);
+                follows.push(FollowRow { id: random_uuid(rng), follower, followed, at: between(rng, since, end) });
+            }
+        }
+    }
+    follows
+}
+
+fn gen_communities(rng: &mut StdRng, count: usize, users: &[UserRow], end: NaiveDateTime) -> Vec<CommunityRow> {
+    if users.is_empty() {
+        return Vec::new();
+    }
+    (0..count)
+        .map(|i| {
+            let id = random_uuid(rng);
+            let topic = TOPICS[i % TOPICS.len()];
+            let creator = rng.random_range(0..users.len());
+            CommunityRow {
+                id,
+                name: format!("{} {} {}", topic, COMMUNITY_SUFFIXES.choose(rng).unwrap(), i / TOPICS.len() + 1),
+                description: json!({ "en": Sentence(6..12).fake_with_rng::<String, _>(rng) }),
+                creator,
+                banner_url: format!("https://picsum.photos/seed/{}/600/200", id),
+                is_public: rng.random_bool(0.85),
+                is_active: rng.random_bool(0.95),
+                created_at: between(rng, users[creator].created_at, end),
+            }
+        })
+        .collect()
+}
+
+fn gen_members(
+    rng: &mut StdRng,
+    communities: &[CommunityRow],
+    users: &[UserRow],
+    end: NaiveDateTime,
+) -> Vec<MemberRow> {
+    let mut members = Vec::new();
+    for (community, row) in communities.iter().enumerate() {
+        // Community sizes are Zipf-distributed as well: the n-th community gets 1/n of the largest.
+        let size = ((users.len() as f64 * LARGEST_COMMUNITY_SHARE) / (community + 1) as f64).ceil() as usize;
+        let size = size.clamp(1, users.len());
+        members.push(MemberRow {
+            id: random_uuid(rng),
+            community,
+            user: row.creator,
+            role: "owner",
+            joined_at: row.created_at,
         });
-        let bio_str = bio.to_string();
-        let profile_pic = format!("https://api.dicebear.com/6.x/identicon/svg?seed={}", username);
-        let provider = "local";
-        let provider_user_id = username.clone();
-        let access_token = None::<String>;
-        let refresh_token = None::<String>;
-        let oauth_email = None::<String>;
-        let created_at = Utc::now().naive_utc();
-        let updated_at = created_at;
-        let is_active: Option<bool> = Some(Boolean(50).fake::<bool>());
- 
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO users (
-                id, first_name, last_name, username, password,
-                mobile, email, organisation, profile_pic,
-                created_at, updated_at, is_active, bio,
-                provider, provider_user_id, access_token,
-                refresh_token, oauth_email
-            )
-            VALUES (
-                $1, $2, $3, $4, $5,
-                $6, $7, $8, $9,
-                $10, $11, $12, $13,
-                $14, $15, $16,
-                $17, $18
-            )
-            "#,
-            id,
-            first_name,
-            last_name,
-            username,
-            password,
-            Some(mobile),
-            email,
-            Some(organisation),
-            Some(profile_pic),
-            created_at,
-            updated_at,
-            is_active,
-          Some(&bio),// <-- fix here
-            Some(provider),
-            Some(provider_user_id),
-            access_token,
-            refresh_token,
-            oauth_email,
-        )
-        .execute(pool)
-        .await;
- 
-        match result {
-            Ok(_) => println!("✅ Created user: {username}"),
-            Err(e) => println!("⚠️ Error inserting user {}: {:?}", username, e),
+
+        let moderators = rng.random_range(0..=2);
+        for (n, user) in index::sample(rng, users.len(), size).into_iter().enumerate() {
+            if user == row.creator {
+                continue;
+            }
+            let since = row.created_at.max(users[user].created_at);
+            members.push(MemberRow {
+                id: random_uuid(rng),
+                community,
+                user,
+                role: if n < moderators { "moderator" } else { "member" },
+                joined_at: between(rng, since, end),
+            });
         }
     }
+    members
 }
- 
- 
-pub async fn seed_fake_communities(pool: &PgPool, count: usize) {
-    // Fetch all user IDs
-    let user_ids: Vec<Uuid> = match sqlx::query_scalar!("SELECT id FROM users")
-        .fetch_all(pool)
-        .await
-    {
-        Ok(ids) => ids,
-        Err(e) => {
-            println!("⚠️ Error fetching user ids: {:?}", e);
-            return;
+
+fn gen_posts(
+    rng: &mut StdRng,
+    count: usize,
+    communities: &[CommunityRow],
+    members: &[MemberRow],
+    end: NaiveDateTime,
+) -> Vec<PostRow> {
+    if communities.is_empty() {
+        return Vec::new();
+    }
+    let by_community = members_by_community(communities.len(), members);
+    // Bigger communities get proportionally more posts.
+    let community_dist = WeightedIndex::new(by_community.iter().map(Vec::len)).unwrap();
+
+    (0..count)
+        .map(|_| {
+            let community = community_dist.sample(rng);
+            let member = &members[*by_community[community].choose(rng).unwrap()];
+            let mut tags = pick(rng, &TOPICS, 0..3);
+            let topic = communities[community].name.split(' ').next().unwrap_or_default().to_string();
+            if !tags.contains(&topic) {
+                tags.insert(0, topic);
+            }
+            PostRow {
+                id: random_uuid(rng),
+                community,
+                author: member.user,
+                title: json!({ "en": Sentence(3..8).fake_with_rng::<String, _>(rng) }),
+                content: json!({ "en": Paragraph(1..4).fake_with_rng::<String, _>(rng) }),
+                tags,
+                likes: 0,
+                is_active: rng.random_bool(0.97),
+                created_at: between(rng, member.joined_at, end),
+            }
+        })
+        .collect()
+}
+
+/// Likes come from members of the post's community; also fills in `posts.likes`.
+fn gen_post_likes(
+    rng: &mut StdRng,
+    posts: &mut [PostRow],
+    members: &[MemberRow],
+    end: NaiveDateTime,
+) -> Vec<LikeRow> {
+    let communities = posts.iter().map(|p| p.community).max().map_or(0, |max| max + 1);
+    let by_community = members_by_community(communities, members);
+
+    let mut likes = Vec::new();
+    for (target, post) in posts.iter_mut().enumerate() {
+        let pool = &by_community[post.community];
+        let wanted = heavy_tailed(rng, MAX_LIKES_PER_POST.min(pool.len()));
+        for i in index::sample(rng, pool.len(), wanted) {
+            let member = &members[pool[i]];
+            let since = post.created_at.max(member.joined_at);
+            likes.push(LikeRow { id: random_uuid(rng), target, user: member.user, at: between(rng, since, end) });
         }
-    };
- 
-    if user_ids.is_empty() {
-        println!("⚠️ No users found. Cannot create communities without users.");
-        return;
+        post.likes = wanted as i32;
     }
- 
-    for _ in 0..count {
-        let id = Uuid::new_v4();
-        let name: String = CompanyName().fake();
-        let description = json!({
-            "about": Sentence(8..15).fake::<String>()
-        });
-        let creator_id = user_ids[rand::random::<usize>() % user_ids.len()];
-        let banner_url = Some(format!("https://picsum.photos/seed/{}/600/200", id));
-        let is_public = Boolean(80).fake::<bool>(); // 80% chance true
-        let is_active = Boolean(90).fake::<bool>(); // 90% chance true
-        let created_at = Utc::now().naive_utc();
- 
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO communities (
-                id, name, description, creator_id, banner_url,
-                is_public, is_active, created_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            "#,
-            id,
-            name,
-            description,
-            creator_id,
-            banner_url,
-            is_public,
-            is_active,
-            created_at,
-        )
-        .execute(pool)
-        .await;
- 
-        match result {
-            Ok(_) => println!("✅ Created community: {name}"),
-            Err(e) => println!("⚠️ Error inserting community {}: {:?}", name, e),
+    likes
+}
+
+/// Comment threads: each comment either starts a thread or replies to an earlier comment
+/// on the same post.
+fn gen_comments(rng: &mut StdRng, posts: &[PostRow], members: &[MemberRow], end: NaiveDateTime) -> Vec<CommentRow> {
+    let communities = posts.iter().map(|p| p.community).max().map_or(0, |max| max + 1);
+    let by_community = members_by_community(communities, members);
+
+    let mut comments: Vec<CommentRow> = Vec::new();
+    for (post, row) in posts.iter().enumerate() {
+        let first = comments.len();
+        // Posts with more likes tend to get more comments.
+        let wanted = heavy_tailed(rng, MAX_COMMENTS_PER_POST) + row.likes as usize / 10;
+        for _ in 0..wanted {
+            let member = &members[*by_community[row.community].choose(rng).unwrap()];
+            let parent = (comments.len() > first && rng.random_bool(REPLY_PROBABILITY))
+                .then(|| rng.random_range(first..comments.len()));
+            let since = parent
+                .map_or(row.created_at, |parent| comments[parent].created_at)
+                .max(member.joined_at);
+            comments.push(CommentRow {
+                id: random_uuid(rng),
+                post,
+                author: member.user,
+                parent,
+                content: json!({ "en": Sentence(4..20).fake_with_rng::<String, _>(rng) }),
+                created_at: between(rng, since, end),
+            });
         }
     }
+    comments
 }
- 
- 
-pub async fn seed_fake_posts(pool: &PgPool, count: usize) {
-    // Fetch all user IDs
-    let user_ids: Vec<Uuid> = match sqlx::query_scalar!("SELECT id FROM users")
-        .fetch_all(pool)
-        .await
-    {
-        Ok(ids) => ids,
-        Err(e) => {
-            println!("⚠️ Error fetching user ids: {:?}", e);
-            return;
+
+fn gen_comment_likes(rng: &mut StdRng, comments: &[CommentRow], users: &[UserRow], end: NaiveDateTime) -> Vec<LikeRow> {
+    let mut likes = Vec::new();
+    for (target, comment) in comments.iter().enumerate() {
+        let wanted = heavy_tailed(rng, MAX_LIKES_PER_COMMENT.min(users.len()));
+        for user in index::sample(rng, users.len(), wanted) {
+            likes.push(LikeRow { id: random_uuid(rng), target, user, at: between(rng, comment.created_at, end) });
         }
-    };
- 
-    // Fetch all community IDs
-    let community_ids: Vec<Uuid> = match sqlx::query_scalar!("SELECT id FROM communities")
-        .fetch_all(pool)
-        .await
-    {
-        Ok(ids) => ids,
-        Err(e) => {
-            println!("⚠️ Error fetching community ids: {:?}", e);
-            return;
+    }
+    likes
+}
+
+fn gen_jobs(rng: &mut StdRng, count: usize, businesses: &[BusinessRow], end: NaiveDateTime) -> Vec<JobRow> {
+    if businesses.is_empty() {
+        return Vec::new();
+    }
+    (0..count)
+        .map(|_| {
+            let business = rng.random_range(0..businesses.len());
+            let salary_min = rng.random_range(30..120) * 1000;
+            let mut job = JobRow {
+                id: random_uuid(rng),
+                business,
+                title: json!({ "en": Position().fake_with_rng::<String, _>(rng) }),
+                description: json!({ "en": Paragraph(2..5).fake_with_rng::<String, _>(rng) }),
+                location: CityName().fake_with_rng(rng),
+                job_type: JOB_TYPES.choose(rng).unwrap(),
+                notice_period: format!("{} weeks", rng.random_range(1..=4)),
+                min_experience: rng.random_range(0..10),
+                skills: pick(rng, &SKILLS, 3..6),
+                salary_min,
+                salary_max: salary_min + rng.random_range(10..60) * 1000,
+                is_active: rng.random_bool(0.85),
+                closes_at: None,
+                created_at: between(rng, businesses[business].created_at, end),
+            };
+            if job.is_active && rng.random_bool(0.5) {
+                job.closes_at = Some(end + Duration::days(rng.random_range(7..60)));
+            }
+            job
+        })
+        .collect()
+}
+
+fn gen_applications(rng: &mut StdRng, jobs: &[JobRow], users: &[UserRow], end: NaiveDateTime) -> Vec<ApplicationRow> {
+    let mut applications = Vec::new();
+    for (job, row) in jobs.iter().enumerate() {
+        let wanted = heavy_tailed(rng, MAX_APPLICATIONS_PER_JOB.min(users.len()));
+        for applicant in index::sample(rng, users.len(), wanted) {
+            let applied_at = between(rng, row.created_at.max(users[applicant].created_at), end);
+            let id = random_uuid(rng);
+            let cover_letter = Paragraph(1..3).fake_with_rng(rng);
+            let history = gen_status_history(rng, applied_at, end);
+            let event_ids = history.iter().map(|_| random_uuid(rng)).collect();
+            applications.push(ApplicationRow { id, job, applicant, cover_letter, history, event_ids });
         }
-    };
- 
-    if user_ids.is_empty() || community_ids.is_empty() {
-        println!("⚠️ No users or communities found. Cannot create posts without them.");
-        return;
     }
- 
-    for _ in 0..count {
-        let id = Uuid::new_v4();
-        let author_id = user_ids[rand::random::<usize>() % user_ids.len()];
-        let community_id = community_ids[rand::random::<usize>() % community_ids.len()];
-        let title = json!(Sentence(3..8).fake::<String>());
-        let content = json!(Paragraph(2..5).fake::<String>());
-        let post_type = "text".to_string();
-        let created_at = Utc::now().naive_utc();
-        let is_active = Boolean(90).fake::<bool>();
- 
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO posts (
-                id, community_id, author_id, title, content, post_type, created_at, is_active
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            "#,
-            id,
-            community_id,
-            author_id,
-            title,
-            content,
-            post_type,
-            created_at,
-            is_active,
-        )
-        .execute(pool)
-        .await;
- 
-        match result {
-            Ok(_) => println!("✅ Created post in community {community_id} by user {author_id}"),
-            Err(e) => println!("⚠️ Error inserting post: {:?}", e),
+    applications
+}
+
+/// Walks the pipeline from "applied": at each stage the application advances, is rejected,
+/// is withdrawn by the applicant, or stays where it is.
+fn gen_status_history(
+    rng: &mut StdRng,
+    applied_at: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Vec<(&'static str, NaiveDateTime)> {
+    let mut history = vec![(APPLICATION_PIPELINE[0], applied_at)];
+    for next in &APPLICATION_PIPELINE[1..] {
+        let at = between(rng, history.last().unwrap().1, end);
+        let roll: f64 = rng.random();
+        if roll < WITHDRAW_PROBABILITY {
+            history.push(("withdrawn", at));
+            break;
+        } else if roll < WITHDRAW_PROBABILITY + REJECT_PROBABILITY {
+            history.push(("rejected", at));
+            break;
+        } else if roll < WITHDRAW_PROBABILITY + REJECT_PROBABILITY + ADVANCE_PROBABILITY {
+            history.push((next, at));
+        } else {
+            break;
         }
     }
+    history
 }
- 
- 
-pub async fn seed_fake_jobs(pool: &PgPool, count: usize) {
-    // Fetch all user IDs
-    let user_ids: Vec<Uuid> = match sqlx::query_scalar!("SELECT id FROM users")
-        .fetch_all(pool)
-        .await
-    {
-        Ok(ids) => ids,
-        Err(e) => {
-            println!("⚠️ Error fetching user ids: {:?}", e);
-            return;
+
+fn members_by_community(communities: usize, members: &[MemberRow]) -> Vec<Vec<usize>> {
+    let mut by_community = vec![Vec::new(); communities];
+    for (i, member) in members.iter().enumerate() {
+        if member.community < communities {
+            by_community[member.community].push(i);
         }
-    };
- 
-    if user_ids.is_empty() {
-        println!("⚠️ No users found. Cannot create jobs without users.");
-        return;
     }
- 
-    let job_types = vec!["remote", "hybrid", "onsite"];
-    let skills_pool = vec![
-        "Rust", "SQL", "React", "AWS", "Docker", "Kubernetes", "Python", "TypeScript", "Node.js"
-    ];
- 
-    // Use the provided valid business account UUID for posted_by
-    let job_posted_by = Uuid::parse_str("bff17406-d7d9-4821-8314-a2ad5eb28253").unwrap();
-
-    for _ in 0..count {
-        let id = Uuid::new_v4();
-        let organisation: String = CompanyName().fake();
-        let title = json!(Position().fake::<String>());
-        let description = json!(Paragraph(2..5).fake::<String>());
-        let location: String = CityName().fake();
-        let job_type = Some(job_types.choose(&mut rand::thread_rng()).unwrap().to_string());
-        let notice_period = Some(format!("{} weeks", (1..4).fake::<u8>()));
-        let min_experience = Some((0..10).fake::<i32>());
-        let salary_range = Some(format!("{}-{} USD", (40..80).fake::<u32>(), (81..150).fake::<u32>()));
-        let skills: Vec<String> = skills_pool
-            .choose_multiple(&mut rand::thread_rng(), 3)
-            .cloned()
-            .map(String::from)
-            .collect();
-        let created_at = Utc::now().naive_utc();
-        let updated_at = created_at;
-        let is_active = Some(true);
-       let job_posted_by = Uuid::parse_str("bff17406-d7d9-4821-8314-a2ad5eb28253").unwrap();
-
-        let result = sqlx::query!(
-            r#"
-            INSERT INTO job_listings (
-                id, organisation, title, description, location, job_type, skills, notice_period,
-                min_experience, salary_range, created_at, updated_at, is_active, posted_by
-            )
-            VALUES (
-                $1, $2, $3, $4, $5, $6, $7, $8,
-                $9, $10, $11, $12, $13, $14
-            )
-            "#,
-            id,
-            organisation,
-            title,
-            description,
-            location,
-            job_type,
-            &skills,
-            notice_period,
-            min_experience,
-            salary_range,
-            created_at,
-            updated_at,
-            is_active,
-            job_posted_by, // <-- always use the valid business account UUID
-        )
-        .execute(pool)
-        .await;
- 
-        match result {
-            Ok(_) => println!("✅ Created job: {organisation} - {title}"),
-            Err(e) => println!("⚠️ Error inserting job: {:?}", e),
+    by_community
+}
+
+/// Weights 1/rank^s over a random ranking, so a few entries get most of the draws.
+fn zipf_weights(rng: &mut StdRng, count: usize) -> Vec<f64> {
+    let mut ranks: Vec<usize> = (1..=count).collect();
+    fake::rand::seq::SliceRandom::shuffle(ranks.as_mut_slice(), rng);
+    ranks.into_iter().map(|rank| 1.0 / (rank as f64).powf(ZIPF_EXPONENT)).collect()
+}
+
+/// A count in `0..=max` that is usually small and occasionally close to `max` (log-uniform).
+fn heavy_tailed(rng: &mut StdRng, max: usize) -> usize {
+    if max == 0 {
+        return 0;
+    }
+    let x: f64 = rng.random();
+    (((max + 1) as f64).powf(x) - 1.0).floor() as usize
+}
+
+fn pick(rng: &mut StdRng, from: &[&str], count: std::ops::Range<usize>) -> Vec<String> {
+    let count = rng.random_range(count);
+    from.choose_multiple(rng, count).map(|s| s.to_string()).collect()
+}
+
+fn between(rng: &mut StdRng, start: NaiveDateTime, end: NaiveDateTime) -> NaiveDateTime {
+    let span = (end - start).num_seconds();
+    if span <= 0 {
+        return end;
+    }
+    start + Duration::seconds(rng.random_range(0..=span))
+}
+
+fn random_uuid(rng: &mut impl Rng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.random()).into_uuid()
+}
+
+fn slug(value: &str) -> String {
+    value.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn anchor() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_same_seed_generates_same_users() {
+        let start = anchor() - Duration::days(HISTORY_DAYS);
+        let first = gen_users(&mut StdRng::seed_from_u64(7), 20, start, anchor());
+        let second = gen_users(&mut StdRng::seed_from_u64(7), 20, start, anchor());
+        let ids = |users: &[UserRow]| users.iter().map(|u| (u.id, u.email.clone())).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+    }
+
+    #[test]
+    fn test_status_history_is_ordered_and_ends_in_a_known_status() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let start = anchor() - Duration::days(30);
+        for _ in 0..200 {
+            let history = gen_status_history(&mut rng, start, anchor());
+            assert_eq!(history[0].0, "applied");
+            assert!(history.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+            let last = history.last().unwrap().0;
+            assert!(APPLICATION_PIPELINE.contains(&last) || last == "rejected" || last == "withdrawn");
         }
     }
-}
\ No newline at end of file
+}
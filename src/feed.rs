@@ -0,0 +1,172 @@
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams, RANK_SCALE};
+
+// Affinity of a post to the reader: who wrote it, where, and how well its tags match.
+const FOLLOWED_AUTHOR_WEIGHT: f64 = 3.0;
+const JOINED_COMMUNITY_WEIGHT: f64 = 2.0;
+const INTEREST_MATCH_WEIGHT: f64 = 1.0;
+const MAX_INTEREST_MATCHES: i32 = 3;
+// Engagement adds ln(1 + likes + COMMENT_LIKE_WEIGHT * comment likes) times this weight.
+const ENGAGEMENT_WEIGHT: f64 = 0.5;
+const COMMENT_LIKE_WEIGHT: f64 = 0.5;
+// The score halves every HALF_LIFE_HOURS; posts older than the window are not considered.
+const HALF_LIFE_HOURS: f64 = 24.0;
+const WINDOW_DAYS: i32 = 30;
+
+/// A post in the home feed, with the reasons it was picked.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct FeedPost {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub author_id: Uuid,
+    pub title: serde_json::Value,
+    pub content: serde_json::Value,
+    pub post_type: String,
+    pub created_at: NaiveDateTime,
+    pub tags: Vec<String>,
+    pub like_count: i64,
+    pub comment_like_count: i64,
+    pub media_urls: Vec<String>,
+    pub from_followed_author: bool,
+    pub from_joined_community: bool,
+    pub interest_matches: i32,
+    #[serde(skip)]
+    pub score: i64,
+}
+
+/// One page of `user_id`'s home feed: posts by people they follow, in communities they joined
+/// or tagged with their interests, best first. Their own posts and posts they have marked as
+/// seen are left out.
+///
+/// Scores depend on the time and on likes, so every page is ranked as of the moment the first
+/// page was built (carried in the cursor); only likes given up to then count.
+pub async fn feed_page(db: &PgPool, user_id: Uuid, page: &PageParams) -> Result<Page<FeedPost>, ApiError> {
+    let after = page.after()?;
+    let as_of = match after {
+        Some(cursor) => cursor.as_of.ok_or_else(|| ApiError::bad_request("Invalid cursor"))?,
+        None => Utc::now().naive_utc(),
+    };
+
+    let query = format!(
+        r#"
+        WITH reader AS (
+            SELECT COALESCE(interests, '{{}}') AS interests FROM users WHERE id = $1
+        ),
+        candidates AS (
+            SELECT
+                p.id,
+                p.community_id,
+                p.author_id,
+                p.title,
+                p.content,
+                p.post_type,
+                p.created_at,
+                COALESCE(p.tags, '{{}}') AS tags,
+                EXISTS(SELECT 1 FROM followers f
+                       WHERE f.follower_id = $1 AND f.followed_id = p.author_id) AS from_followed_author,
+                EXISTS(SELECT 1 FROM community_members cm
+                       WHERE cm.user_id = $1 AND cm.community_id = p.community_id) AS from_joined_community,
+                cardinality(ARRAY(
+                    SELECT UNNEST(p.tags) INTERSECT SELECT UNNEST(reader.interests)
+                )) AS interest_matches
+            FROM posts p, reader
+            WHERE p.author_id <> $1
+              AND p.is_active
+              AND p.created_at <= $2
+              AND p.created_at > $2 - make_interval(days => {window_days})
+              AND NOT EXISTS(SELECT 1 FROM post_views v WHERE v.user_id = $1 AND v.post_id = p.id)
+        ),
+        relevant AS (
+            SELECT * FROM candidates
+            WHERE from_followed_author OR from_joined_community OR interest_matches > 0
+        ),
+        post_like_counts AS (
+            SELECT pl.post_id, COUNT(*) AS n
+            FROM post_likes pl JOIN relevant r ON r.id = pl.post_id
+            WHERE pl.is_liked = true AND pl.liked_at <= $2
+            GROUP BY pl.post_id
+        ),
+        comment_like_counts AS (
+            SELECT c.post_id, COUNT(*) AS n
+            FROM comments c
+            JOIN relevant r ON r.id = c.post_id
+            JOIN comment_likes cl ON cl.comment_id = c.id
+            WHERE cl.created_at <= $2
+            GROUP BY c.post_id
+        ),
+        engaged AS (
+            SELECT r.*,
+                COALESCE(plc.n, 0) AS like_count,
+                COALESCE(clc.n, 0) AS comment_like_count
+            FROM relevant r
+            LEFT JOIN post_like_counts plc ON plc.post_id = r.id
+            LEFT JOIN comment_like_counts clc ON clc.post_id = r.id
+        ),
+        scored AS (
+            SELECT *,
+                ARRAY(
+                    SELECT pm.url FROM post_media pm WHERE pm.post_id = engaged.id ORDER BY pm.created_at
+                ) AS media_urls,
+                round({scale} * (
+                    (CASE WHEN from_followed_author THEN {followed} ELSE 0 END)
+                    + (CASE WHEN from_joined_community THEN {community} ELSE 0 END)
+                    + {interest} * LEAST(interest_matches, {max_matches})
+                    + {engagement} * ln(1 + like_count + {comment_like} * comment_like_count)
+                ) * power(0.5, EXTRACT(EPOCH FROM ($2 - created_at)) / 3600 / {half_life}))::bigint AS score
+            FROM engaged
+        )
+        SELECT * FROM scored
+        WHERE $3::bigint IS NULL OR (score, created_at, id) < ($3, $4, $5)
+        ORDER BY score DESC, created_at DESC, id DESC
+        LIMIT $6
+        "#,
+        window_days = WINDOW_DAYS,
+        scale = RANK_SCALE,
+        followed = FOLLOWED_AUTHOR_WEIGHT,
+        community = JOINED_COMMUNITY_WEIGHT,
+        interest = INTEREST_MATCH_WEIGHT,
+        max_matches = MAX_INTEREST_MATCHES,
+        engagement = ENGAGEMENT_WEIGHT,
+        comment_like = COMMENT_LIKE_WEIGHT,
+        half_life = HALF_LIFE_HOURS,
+    );
+
+    let posts = sqlx::query_as::<_, FeedPost>(&query)
+        .bind(user_id)
+        .bind(as_of)
+        .bind(after.and_then(|c| c.rank))
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(page.fetch_limit())
+        .fetch_all(db)
+        .await
+        .map_err(ApiError::db("Error building feed"))?;
+
+    Ok(Page::new(posts, page, |post| Cursor {
+        as_of: Some(as_of),
+        ..Cursor::ranked(post.score, post.created_at, post.id)
+    }))
+}
+
+/// Marks posts as seen by `user_id` so the feed stops showing them. Returns how many were new.
+pub async fn mark_seen(db: &PgPool, user_id: Uuid, post_ids: &[Uuid]) -> Result<u64, ApiError> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO post_views (user_id, post_id)
+        SELECT $1, post_id FROM UNNEST($2::uuid[]) AS post_id
+        ON CONFLICT (user_id, post_id) DO NOTHING
+        "#,
+        user_id,
+        post_ids
+    )
+    .execute(db)
+    .await
+    .map_err(ApiError::db("Error marking posts as seen"))?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,96 @@
+use actix_web::{web, HttpResponse};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::ats::{self, ApplicantQuery, MAX_BULK_APPLICATIONS};
+use crate::auth::Principal;
+use crate::authz::authorize_job;
+use crate::error::ApiError;
+use crate::models::job_applications::{ApplicantFilters, BulkStatusUpdate, CreateApplicationNote, RateApplication};
+use crate::pagination::PageParams;
+
+/// `GET /ats/jobs/{job_id}/applications?status=&min_match=&sort=date|match`: the job's
+/// applicants with their match score, rating and number of notes.
+pub async fn get_job_applicants(
+    db: web::Data<PgPool>,
+    job_id: web::Path<Uuid>,
+    principal: Principal,
+    web::Query(filters): web::Query<ApplicantFilters>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_job(db.get_ref(), &principal, *job_id).await?;
+    let query = ApplicantQuery::from_query(filters)?;
+    let applicants = ats::list_applicants(db.get_ref(), *job_id, &query, &page, principal.is_admin).await?;
+    Ok(HttpResponse::Ok().json(applicants))
+}
+
+pub async fn get_job_funnel(
+    db: web::Data<PgPool>,
+    job_id: web::Path<Uuid>,
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_job(db.get_ref(), &principal, *job_id).await?;
+    Ok(HttpResponse::Ok().json(ats::funnel(db.get_ref(), *job_id).await?))
+}
+
+/// Moves several applications of a job at once. Applications the pipeline does not let move
+/// are left as they are and listed under `skipped`.
+pub async fn bulk_update_status(
+    db: web::Data<PgPool>,
+    job_id: web::Path<Uuid>,
+    principal: Principal,
+    json: web::Json<BulkStatusUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_job(db.get_ref(), &principal, *job_id).await?;
+    let mut ids = json.application_ids.clone();
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() || ids.len() > MAX_BULK_APPLICATIONS {
+        return Err(ApiError::bad_request(format!(
+            "application_ids must list between 1 and {} applications",
+            MAX_BULK_APPLICATIONS
+        )));
+    }
+
+    let mut tx = db.begin().await.map_err(ApiError::db("Error updating application statuses"))?;
+    let (updated, skipped) =
+        ats::change_status_in_bulk(&mut tx, *job_id, &ids, json.status, &principal, json.note.as_deref()).await?;
+    tx.commit().await.map_err(ApiError::db("Error updating application statuses"))?;
+
+    Ok(HttpResponse::Ok().json(json!({ "updated": updated, "skipped": skipped })))
+}
+
+pub async fn rate_application(
+    db: web::Data<PgPool>,
+    application_id: web::Path<Uuid>,
+    principal: Principal,
+    json: web::Json<RateApplication>,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = ats::job_of_application(db.get_ref(), *application_id).await?;
+    authorize_job(db.get_ref(), &principal, job_id).await?;
+    ats::rate(db.get_ref(), *application_id, json.rating).await?;
+    Ok(HttpResponse::Ok().json(json!({ "id": *application_id, "recruiter_rating": json.rating })))
+}
+
+pub async fn add_application_note(
+    db: web::Data<PgPool>,
+    application_id: web::Path<Uuid>,
+    principal: Principal,
+    json: web::Json<CreateApplicationNote>,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = ats::job_of_application(db.get_ref(), *application_id).await?;
+    authorize_job(db.get_ref(), &principal, job_id).await?;
+    let note = ats::add_note(db.get_ref(), *application_id, &principal, &json.note).await?;
+    Ok(HttpResponse::Ok().json(note))
+}
+
+pub async fn get_application_notes(
+    db: web::Data<PgPool>,
+    application_id: web::Path<Uuid>,
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let job_id = ats::job_of_application(db.get_ref(), *application_id).await?;
+    authorize_job(db.get_ref(), &principal, job_id).await?;
+    Ok(HttpResponse::Ok().json(ats::notes(db.get_ref(), *application_id, &page).await?))
+}
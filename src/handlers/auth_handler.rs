@@ -0,0 +1,280 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::{encode_token, Claims, Principal, PrincipalKind, TokenResponse};
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::models::business_accounts::BusinessAccount;
+use crate::models::user::{ExchangeCodeRequest, LoginRequest, RefreshRequest, RegisterUser, User};
+use crate::password::{hash_password, verify_password, PasswordCheck, MIN_PASSWORD_LENGTH};
+use crate::session::{self, IssuedSession, RefreshOutcome};
+
+pub async fn register(
+    req: HttpRequest,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    json: web::Json<RegisterUser>,
+) -> Result<HttpResponse, ApiError> {
+    let payload = json.into_inner();
+
+    if payload.password.len() < MIN_PASSWORD_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let password_hash = hash_password(&payload.password).map_err(|e| {
+        eprintln!("Password hashing error: {:?}", e);
+        ApiError::internal("Error creating account")
+    })?;
+
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (id, first_name, last_name, username, password, email, provider)
+        VALUES ($1, $2, $3, $4, $5, $6, 'local')
+        RETURNING *
+        "#
+    )
+    .bind(Uuid::new_v4())
+    .bind(&payload.first_name)
+    .bind(&payload.last_name)
+    .bind(&payload.username)
+    .bind(&password_hash)
+    .bind(payload.email.to_lowercase())
+    .fetch_one(db.get_ref())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            ApiError::conflict("Email or username is already registered")
+        }
+        e => ApiError::db("Error creating account")(e),
+    })?;
+
+    let session = start_session(&db, &config, &req, PrincipalKind::User, user.id).await?;
+    token_response(Claims::for_user(&user, session.id, &config), session.refresh_token, &config)
+}
+
+pub async fn login(
+    req: HttpRequest,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    json: web::Json<LoginRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let user = sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE LOWER(email) = $1"
+    )
+    .bind(json.email.to_lowercase())
+    .fetch_optional(db.get_ref())
+    .await
+    .map_err(ApiError::db("Error logging in"))?
+    .ok_or_else(invalid_credentials)?;
+
+    match verify_password(&json.password, &user.password) {
+        PasswordCheck::Valid => {}
+        PasswordCheck::ValidNeedsRehash => {
+            rehash_legacy_password(&db, PrincipalKind::User, &user.id, &json.password).await
+        }
+        PasswordCheck::Invalid => return Err(invalid_credentials()),
+    }
+
+    if user.is_active == Some(false) {
+        return Err(ApiError::forbidden("Account is deactivated"));
+    }
+
+    let session = start_session(&db, &config, &req, PrincipalKind::User, user.id).await?;
+    token_response(Claims::for_user(&user, session.id, &config), session.refresh_token, &config)
+}
+
+pub async fn business_login(
+    req: HttpRequest,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    json: web::Json<LoginRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let account = sqlx::query_as::<_, BusinessAccount>(
+        "SELECT * FROM business_accounts WHERE LOWER(email) = $1"
+    )
+    .bind(json.email.to_lowercase())
+    .fetch_optional(db.get_ref())
+    .await
+    .map_err(ApiError::db("Error logging in"))?
+    .ok_or_else(invalid_credentials)?;
+
+    match verify_password(&json.password, &account.password_hash) {
+        PasswordCheck::Valid => {}
+        PasswordCheck::ValidNeedsRehash => {
+            rehash_legacy_password(&db, PrincipalKind::Business, &account.id, &json.password).await
+        }
+        PasswordCheck::Invalid => return Err(invalid_credentials()),
+    }
+
+    let session = start_session(&db, &config, &req, PrincipalKind::Business, account.id).await?;
+    token_response(Claims::for_business(&account, session.id, &config), session.refresh_token, &config)
+}
+
+/// Trades the one-time code from an OAuth redirect for a token pair.
+pub async fn exchange_code(
+    req: HttpRequest,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    json: web::Json<ExchangeCodeRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = session::redeem_login_code(&db, &json.code)
+        .await
+        .map_err(ApiError::db("Error logging in"))?
+        .ok_or_else(|| ApiError::unauthorized("Invalid or expired login code"))?;
+
+    let user = sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE id = $1 AND COALESCE(is_active, true)"
+    )
+    .bind(user_id)
+    .fetch_optional(db.get_ref())
+    .await
+    .map_err(ApiError::db("Error logging in"))?
+    .ok_or_else(|| ApiError::forbidden("Account is deactivated"))?;
+
+    let session = start_session(&db, &config, &req, PrincipalKind::User, user.id).await?;
+    token_response(Claims::for_user(&user, session.id, &config), session.refresh_token, &config)
+}
+
+pub async fn refresh(
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    json: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, ApiError> {
+    let rotated = match session::rotate_session(&db, &config, &json.refresh_token)
+        .await
+        .map_err(ApiError::db("Error refreshing session"))?
+    {
+        RefreshOutcome::Rotated(rotated) => rotated,
+        RefreshOutcome::Reused => {
+            return Err(ApiError::unauthorized(
+                "Refresh token was already used; the session has been revoked",
+            ));
+        }
+        RefreshOutcome::Invalid => {
+            return Err(ApiError::unauthorized("Invalid or expired refresh token"));
+        }
+    };
+
+    // Claims are rebuilt from the current row so profile changes reach the new token.
+    let claims = match rotated.kind {
+        PrincipalKind::User => sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE id = $1 AND COALESCE(is_active, true)"
+        )
+        .bind(rotated.principal_id)
+        .fetch_optional(db.get_ref())
+        .await
+        .map(|user| user.map(|user| Claims::for_user(&user, rotated.id, &config))),
+        PrincipalKind::Business => sqlx::query_as::<_, BusinessAccount>(
+            "SELECT * FROM business_accounts WHERE id = $1"
+        )
+        .bind(rotated.principal_id)
+        .fetch_optional(db.get_ref())
+        .await
+        .map(|account| account.map(|account| Claims::for_business(&account, rotated.id, &config))),
+    }
+    .map_err(ApiError::db("Error refreshing session"))?;
+
+    let Some(claims) = claims else {
+        if let Err(e) = session::revoke_session(&db, rotated.id).await {
+            eprintln!("Failed to revoke session {}: {:?}", rotated.id, e);
+        }
+        return Err(ApiError::unauthorized("Account is deactivated or no longer exists"));
+    };
+
+    token_response(claims, rotated.refresh_token, &config)
+}
+
+pub async fn logout(db: web::Data<PgPool>, principal: Principal) -> Result<HttpResponse, ApiError> {
+    session::revoke_session(&db, principal.session_id)
+        .await
+        .map_err(ApiError::db("Error logging out"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Logged out"
+    })))
+}
+
+pub async fn logout_all(db: web::Data<PgPool>, principal: Principal) -> Result<HttpResponse, ApiError> {
+    let count = session::revoke_all_sessions(&db, principal.kind, principal.id)
+        .await
+        .map_err(ApiError::db("Error logging out"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Logged out of all devices",
+        "sessions_revoked": count
+    })))
+}
+
+async fn start_session(
+    db: &PgPool,
+    config: &Config,
+    req: &HttpRequest,
+    kind: PrincipalKind,
+    principal_id: Uuid,
+) -> Result<IssuedSession, ApiError> {
+    let user_agent = req
+        .headers()
+        .get(actix_web::http::header::USER_AGENT)
+        .and_then(|val| val.to_str().ok());
+    session::start_session(db, config, kind, principal_id, user_agent)
+        .await
+        .map_err(ApiError::db("Failed to start session"))
+}
+
+// Plaintext rows from before hashing are upgraded the first time the owner logs in.
+async fn rehash_legacy_password(db: &PgPool, kind: PrincipalKind, id: &Uuid, password: &str) {
+    let hash = match hash_password(password) {
+        Ok(hash) => hash,
+        Err(e) => {
+            eprintln!("Password hashing error: {:?}", e);
+            return;
+        }
+    };
+
+    let result = match kind {
+        PrincipalKind::User => sqlx::query!(
+            "UPDATE users SET password = $1, updated_at = NOW() WHERE id = $2",
+            hash,
+            id
+        )
+        .execute(db)
+        .await,
+        PrincipalKind::Business => sqlx::query!(
+            "UPDATE business_accounts SET password_hash = $1, updated_at = NOW() WHERE id = $2",
+            hash,
+            id
+        )
+        .execute(db)
+        .await,
+    };
+
+    if let Err(e) = result {
+        eprintln!("Failed to rehash legacy password for {} {}: {:?}", kind.as_str(), id, e);
+    }
+}
+
+fn token_response(claims: Claims, refresh_token: String, config: &Config) -> Result<HttpResponse, ApiError> {
+    let token = encode_token(&claims, config).map_err(|e| {
+        eprintln!("JWT encoding error: {:?}", e);
+        ApiError::internal("Failed to generate token")
+    })?;
+
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        token,
+        refresh_token,
+        expires_in: config.access_token_ttl_minutes * 60,
+        principal: claims.principal,
+        id: claims.sub,
+    }))
+}
+
+fn invalid_credentials() -> ApiError {
+    ApiError::unauthorized("Invalid email or password")
+}
@@ -2,7 +2,11 @@ use actix_web::{web, HttpResponse, HttpRequest};
 use uuid::Uuid;
 use sqlx::PgPool;
 use crate::models::business_accounts::{CreateBusinessAccount, BusinessAccount, UpdateBusinessAccount};
-use serde_json::json;
+use crate::password::{hash_password, MIN_PASSWORD_LENGTH};
+use crate::auth::Principal;
+use crate::authz::authorize_business_account;
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 
 // Language extraction function (same as your user handler)
 fn get_lang(req: &HttpRequest) -> String {
@@ -25,10 +29,22 @@ pub async fn create_business_account(
     req: HttpRequest,
     db: web::Data<PgPool>,
     json: web::Json<CreateBusinessAccount>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);
     println!("Language preference: {}", lang);
 
+    if json.password.len() < MIN_PASSWORD_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let password_hash = hash_password(&json.password).map_err(|e| {
+        eprintln!("Password hashing error: {:?}", e);
+        ApiError::internal("Error creating business account")
+    })?;
+
     let account = sqlx::query_as::<_, BusinessAccount>(
         r#"
         INSERT INTO business_accounts 
@@ -41,7 +57,7 @@ pub async fn create_business_account(
     .bind(&json.company_name)
     .bind(&json.contact_person_name)
     .bind(&json.email)
-    .bind(&json.password_hash)
+    .bind(&password_hash)
     .bind(&json.address)
     .bind(&json.gst_number)
     .bind(&json.website)
@@ -50,78 +66,72 @@ pub async fn create_business_account(
     .bind(&json.description)
     .bind(&json.industry)
     .bind(&json.size)
-    .bind(json.is_verified.unwrap_or(false))
+    .bind(false) // Verification is granted by an admin, never at sign-up
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error creating business account"))?;
 
-    match account {
-        Ok(acc) => HttpResponse::Ok().json(acc),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error creating business account")
-        }
-    }
+    Ok(HttpResponse::Ok().json(account))
 }
 
 // Get All Business Accounts
-pub async fn get_business_accounts(db: web::Data<PgPool>) -> HttpResponse {
+pub async fn get_business_accounts(
+    db: web::Data<PgPool>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
     let accounts = sqlx::query_as::<_, BusinessAccount>(
-        "SELECT * FROM business_accounts ORDER BY created_at DESC"
+        r#"
+        SELECT * FROM business_accounts
+        WHERE $1::timestamp IS NULL OR (created_at, id) < ($1, $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving business accounts"))?;
 
-    match accounts {
-        Ok(accs) => HttpResponse::Ok().json(accs),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving business accounts")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(accounts, &page, |a| Cursor::new(a.created_at, a.id))))
 }
 
 // Get Business Account by ID
 pub async fn get_business_account_by_id(
     db: web::Data<PgPool>,
     account_id: web::Path<Uuid>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let account = sqlx::query_as::<_, BusinessAccount>(
         "SELECT * FROM business_accounts WHERE id = $1"
     )
     .bind(*account_id)
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving business account"))?
+    .ok_or_else(|| ApiError::not_found("Business account not found"))?;
 
-    match account {
-        Ok(Some(acc)) => HttpResponse::Ok().json(acc),
-        Ok(None) => HttpResponse::NotFound().json("Business account not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json("Error retrieving business account")
-        }
-    }
+    Ok(HttpResponse::Ok().json(account))
 }
 
 // Delete Business Account by Email
 pub async fn delete_business_account_by_email(
     db: web::Data<PgPool>,
     email: web::Path<String>,
-) -> HttpResponse {
-    let result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_business_account(&db, &principal, &email).await?;
+    sqlx::query!(
         "DELETE FROM business_accounts WHERE email = $1 RETURNING email",
         email.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error deleting business account"))?
+    .ok_or_else(|| ApiError::not_found("Business account not found"))?;
 
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json("Business account deleted successfully"),
-        Ok(None) => HttpResponse::NotFound().json("Business account not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json("Error deleting business account")
-        }
-    }
+    Ok(HttpResponse::Ok().json("Business account deleted successfully"))
 }
 
 // Update Business Account by Email
@@ -129,7 +139,12 @@ pub async fn update_business_account_by_email(
     db: web::Data<PgPool>,
     email: web::Path<String>,
     json: web::Json<UpdateBusinessAccount>,
-) -> HttpResponse {
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_business_account(&db, &principal, &email).await?;
+    if json.is_verified.is_some() && !principal.is_admin {
+        return Err(ApiError::forbidden("Only an admin can change the verification status"));
+    }
     let mut update_query = String::from("UPDATE business_accounts SET updated_at = CURRENT_TIMESTAMP");
     let mut params: Vec<(String, String)> = Vec::new();
 
@@ -142,8 +157,12 @@ pub async fn update_business_account_by_email(
     if let Some(email_new) = &json.email {
         params.push(("email".to_string(), email_new.clone()));
     }
-    if let Some(password_hash) = &json.password_hash {
-        params.push(("password_hash".to_string(), password_hash.clone()));
+    if let Some(password) = &json.password {
+        let hash = hash_password(password).map_err(|e| {
+            eprintln!("Password hashing error: {:?}", e);
+            ApiError::internal("Error updating business account")
+        })?;
+        params.push(("password_hash".to_string(), hash));
     }
     if let Some(address) = &json.address {
         params.push(("address".to_string(), address.clone()));
@@ -174,7 +193,7 @@ pub async fn update_business_account_by_email(
     }
 
     if params.is_empty() {
-        return HttpResponse::BadRequest().json("No fields to update");
+        return Err(ApiError::bad_request("No fields to update"));
     }
 
     for (i, (field, _)) in params.iter().enumerate() {
@@ -189,12 +208,10 @@ pub async fn update_business_account_by_email(
     }
     query = query.bind(email.as_ref());
 
-    match query.fetch_one(db.get_ref()).await {
-        Ok(updated_account) => HttpResponse::Ok().json(updated_account),
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().json("Business account not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json("Error updating business account")
-        }
-    }
+    let updated_account = query.fetch_optional(db.get_ref())
+        .await
+        .map_err(ApiError::db("Error updating business account"))?
+        .ok_or_else(|| ApiError::not_found("Business account not found"))?;
+
+    Ok(HttpResponse::Ok().json(updated_account))
 }
@@ -4,6 +4,10 @@ use serde::Deserialize;
 use uuid::Uuid;
 use sqlx::PgPool;
 use crate::models::comments::{Comments, Create_Comment, Update_Comment};
+use crate::auth::Principal;
+use crate::authz::{authorize_comment, Action};
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 use serde_json::json;
 use std::collections::HashMap;
 
@@ -25,8 +29,9 @@ fn get_lang(req: &HttpRequest) -> String {
 pub async fn create_comment(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<Create_Comment>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
 
@@ -39,78 +44,70 @@ pub async fn create_comment(
     )
     .bind(Uuid::new_v4())
     .bind(&json.post_id)
-    .bind(&json.author_id)
-    .bind(&json.author_type)
+    .bind(principal.id)
+    .bind(principal.kind.as_str())
     .bind(&json.content)
     .bind(&json.parent_id)  // This handles nested replies
     .bind(&json.is_active)
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error creating comment"))?;
 
-    match result {
-        Ok(comment) => HttpResponse::Ok().json(comment),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error creating comment"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(result))
 }
 
 
-pub async fn get_comments(db: web::Data<PgPool>) -> HttpResponse {
-    let users = sqlx::query_as::<_, Comments>(
-        "SELECT * FROM comments ORDER BY created_at DESC"
+pub async fn get_comments(
+    db: web::Data<PgPool>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let comments = sqlx::query_as::<_, Comments>(
+        r#"
+        SELECT * FROM comments
+        WHERE $1::timestamp IS NULL OR (created_at, id) < ($1, $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving comments"))?;
 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving comments")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(comments, &page, |c| Cursor::new(c.created_at, c.id))))
 }
 pub async fn delete_comment_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,  // Changed from email to UUID
-) -> HttpResponse {
-    let result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_comment(&db, &principal, *id, Action::Delete).await?;
+    sqlx::query!(
         "DELETE FROM comments WHERE id = $1 RETURNING id",
         id.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error deleting comment"))?
+    .ok_or_else(|| ApiError::not_found("comment not found"))?;
 
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "comment deleted successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "comment not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error deleting comment"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "comment deleted successfully"
+    })))
 }
 
 pub async fn update_comment_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<Update_Comment>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_comment(&db, &principal, *id, Action::Edit).await?;
+    sqlx::query!(
         r#"
         UPDATE comments 
         SET 
@@ -128,25 +125,14 @@ pub async fn update_comment_by_id(
         id.into_inner(),
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error updating comment"))?
+    .ok_or_else(|| ApiError::not_found("comment not found"))?;
 
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "comment updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "comment not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating comment"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "comment updated successfully"
+    })))
 }
 
 // Updated CommentWithAuthor struct
@@ -189,9 +175,9 @@ pub struct NestedComment {
 pub async fn get_comments_by_post_id(
     db: web::Data<PgPool>,
     post_id: web::Path<Uuid>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     // Fetch all comments for the post
-    let comments_result = sqlx::query_as::<_, CommentWithAuthor>(
+    let flat_comments = sqlx::query_as::<_, CommentWithAuthor>(
         r#"
         SELECT 
             c.id,
@@ -216,22 +202,11 @@ pub async fn get_comments_by_post_id(
     )
     .bind(post_id.into_inner())
     .fetch_all(db.get_ref())
-    .await;
-
-    let flat_comments = match comments_result {
-        Ok(comments) => comments,
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            return HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error fetching comments"
-            }));
-        }
-    };
+    .await
+    .map_err(ApiError::db("Error fetching comments"))?;
 
     // Create a map for quick comment lookup
     let mut comments_map: HashMap<Uuid, NestedComment> = HashMap::new();
-    let mut nested_comments: Vec<NestedComment> = Vec::new();
 
     // First pass: Create NestedComment objects and store in map
     for comment in &flat_comments {
@@ -268,52 +243,50 @@ pub async fn get_comments_by_post_id(
         comments_map.insert(comment.id, nested);
     }
 
-    // Second pass: Build nested structure by processing in reverse order
-    for comment in flat_comments.iter().rev() {
-        if let Some(parent_id) = comment.parent_id {
-            // Try to remove the child comment from the map
-            if let Some(child) = comments_map.remove(&comment.id) {
-                // Attempt to find the parent and attach the child
-                if let Some(parent) = comments_map.get_mut(&parent_id) {
-                    parent.replies.push(child);
-                } else {
-                    // If parent not found, put child back in map
-                    comments_map.insert(comment.id, child);
-                }
-            }
+    // Second pass: group replies under their parent, keeping top-level comments aside
+    let mut replies_by_parent: HashMap<Uuid, Vec<NestedComment>> = HashMap::new();
+    let mut nested_comments: Vec<NestedComment> = Vec::new();
+    for comment in comments_map.into_values() {
+        match comment.parent_id {
+            Some(parent_id) => replies_by_parent.entry(parent_id).or_default().push(comment),
+            None => nested_comments.push(comment),
         }
     }
 
-    // Collect top-level comments (those without parent)
-    for (_, comment) in comments_map {
-        if comment.parent_id.is_none() {
-            nested_comments.push(comment);
-        }
+    // Attach replies at every depth, starting from the top-level comments
+    for comment in &mut nested_comments {
+        attach_replies(comment, &mut replies_by_parent);
     }
 
     // Sort top-level comments by date (newest first)
     nested_comments.sort_by(|a, b| b.created_at.cmp(&a.created_at));
 
-    // Sort replies within each comment (oldest first)
-    for comment in &mut nested_comments {
-        comment.replies.sort_by(|a, b| a.created_at.cmp(&b.created_at));
-    }
+    Ok(HttpResponse::Ok().json(nested_comments))
+}
 
-    HttpResponse::Ok().json(nested_comments)
+/// Moves the replies of `comment` (and, recursively, their replies) out of `replies_by_parent`,
+/// oldest first.
+fn attach_replies(comment: &mut NestedComment, replies_by_parent: &mut HashMap<Uuid, Vec<NestedComment>>) {
+    let mut replies = replies_by_parent.remove(&comment.id).unwrap_or_default();
+    replies.sort_by_key(|r| r.created_at);
+    for reply in &mut replies {
+        attach_replies(reply, replies_by_parent);
+    }
+    comment.replies = replies;
 }
 
 #[derive(Deserialize)]
 pub struct LikeCommentPayload {
     pub comment_id: Uuid,
-    pub user_id: Uuid,
-    pub user_type: String, // "user" or "business"
 }
 
 pub async fn like_comment(
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<LikeCommentPayload>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let payload = json.into_inner();
+    let user_type = principal.kind.as_str();
     
     // Check if like exists
     let existing_like = sqlx::query!(
@@ -322,43 +295,40 @@ pub async fn like_comment(
         WHERE comment_id = $1 AND user_id = $2 AND user_type = $3
         "#,
         payload.comment_id,
-        payload.user_id,
-        payload.user_type
+        principal.id,
+        user_type
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error updating comment like"))?;
 
     match existing_like {
-        Ok(Some(_)) => {
+        Some(_) => {
             // Unlike
-            let _ = sqlx::query!(
+            sqlx::query!(
                 r#"DELETE FROM comment_likes 
                 WHERE comment_id = $1 AND user_id = $2 AND user_type = $3"#,
                 payload.comment_id,
-                payload.user_id,
-                payload.user_type
+                principal.id,
+                user_type
             )
             .execute(db.get_ref())
-            .await;
+            .await
+            .map_err(ApiError::db("Error updating comment like"))?;
         }
-        Ok(None) => {
+        None => {
             // Like
-            let _ = sqlx::query!(
+            sqlx::query!(
                 r#"INSERT INTO comment_likes (id, comment_id, user_id, user_type)
                 VALUES ($1, $2, $3, $4)"#,
                 Uuid::new_v4(),
                 payload.comment_id,
-                payload.user_id,
-                payload.user_type
+                principal.id,
+                user_type
             )
             .execute(db.get_ref())
-            .await;
-        }
-        Err(e) => {
-            return HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": format!("Database error: {}", e)
-            }));
+            .await
+            .map_err(ApiError::db("Error updating comment like"))?;
         }
     }
 
@@ -371,5 +341,5 @@ pub async fn like_comment(
     .await
     .unwrap_or(Some(0)); 
 
-    HttpResponse::Ok().json(json!({ "likes": count }))
+    Ok(HttpResponse::Ok().json(json!({ "likes": count })))
 }
@@ -1,7 +1,11 @@
 use actix_web::{web, HttpResponse, HttpRequest};
 use uuid::Uuid;
 use sqlx::PgPool;
-use crate::models::communities::{Community, Create_Community, Update_Community, Pagination};
+use crate::models::communities::{Community, Create_Community, Update_Community};
+use crate::pagination::{Cursor, Page, PageParams};
+use crate::auth::{Principal, PrincipalKind};
+use crate::authz::{authorize_community, CommunityRole};
+use crate::error::ApiError;
 use serde_json::json;
 
 fn get_lang(req: &HttpRequest) -> String {
@@ -22,75 +26,57 @@ fn get_lang(req: &HttpRequest) -> String {
 pub async fn get_communities_by_creator_id(
     db: web::Data<PgPool>,
     path: web::Path<(String, Uuid)>, // (creator_type, creator_id)
-) -> HttpResponse {
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
     let (creator_type, creator_id) = path.into_inner();
+    let after = page.after()?;
     let query = match creator_type.as_str() {
-        "user" => "SELECT * FROM communities WHERE creator_id = $1 ORDER BY created_at DESC",
-        "business" => "SELECT * FROM communities WHERE creator_business_id = $1 ORDER BY created_at DESC",
-        _ => return HttpResponse::BadRequest().json(json!({
-            "status": "error",
-            "message": "Invalid creator type"
-        }))
+        "user" => r#"
+            SELECT * FROM communities
+            WHERE creator_id = $1 AND ($2::timestamp IS NULL OR (created_at, id) < ($2, $3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4
+        "#,
+        "business" => r#"
+            SELECT * FROM communities
+            WHERE creator_business_id = $1 AND ($2::timestamp IS NULL OR (created_at, id) < ($2, $3))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4
+        "#,
+        _ => return Err(ApiError::bad_request("Invalid creator type")),
     };
 
     let communities = sqlx::query_as::<_, Community>(query)
         .bind(creator_id)
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(page.fetch_limit())
         .fetch_all(db.get_ref())
-        .await;
+        .await
+        .map_err(ApiError::db("Error retrieving communities"))?;
 
-    match communities {
-        Ok(communities) => {
-            if communities.is_empty() {
-                HttpResponse::NotFound().json(json!({
-                    "status": "error",
-                    "message": "No communities found for this creator"
-                }))
-            } else {
-                HttpResponse::Ok().json(communities)
-            }
-        },
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": format!("Error retrieving communities: {}", e)
-            }))
-        }
+    if communities.is_empty() && after.is_none() {
+        return Err(ApiError::not_found("No communities found for this creator"));
     }
+    Ok(HttpResponse::Ok().json(Page::new(communities, &page, |c| Cursor::new(c.created_at, c.id))))
 }
 
 pub async fn create_community(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<Create_Community>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
 
-    // Validate creator type
-    if json.creator_type != "user" && json.creator_type != "business" {
-        return HttpResponse::BadRequest().json(json!({
-            "status": "error",
-            "message": "Invalid creator type. Must be 'user' or 'business'"
-        }));
-    }
-
-    // Validate creator IDs
-    if json.creator_type == "user" && json.creator_id.is_none() {
-        return HttpResponse::BadRequest().json(json!({
-            "status": "error",
-            "message": "creator_id required for user type"
-        }));
-    }
-
-    if json.creator_type == "business" && json.creator_business_id.is_none() {
-        return HttpResponse::BadRequest().json(json!({
-            "status": "error",
-            "message": "creator_business_id required for business type"
-        }));
-    }
+    // The creator is always the caller
+    let (creator_id, creator_business_id) = match principal.kind {
+        PrincipalKind::User => (Some(principal.id), None),
+        PrincipalKind::Business => (None, Some(principal.id)),
+    };
 
-    let result = sqlx::query_as::<_, Community>(
+    let community = sqlx::query_as::<_, Community>(
         r#"
         INSERT INTO communities (
             id, name, description, 
@@ -104,99 +90,72 @@ pub async fn create_community(
     .bind(Uuid::new_v4())
     .bind(&json.name)
     .bind(&json.description)
-    .bind(&json.creator_id)
-    .bind(&json.creator_business_id)
-    .bind(&json.creator_type)
+    .bind(creator_id)
+    .bind(creator_business_id)
+    .bind(principal.kind.as_str())
     .bind(&json.banner_url)
     .bind(&json.is_public)
     .bind(&json.is_active)
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error creating community"))?;
 
-    match result {
-        Ok(c) => HttpResponse::Ok().json(c),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            
-            let error_message = if e.to_string().contains("foreign key constraint") {
-                if json.creator_type == "user" {
-                    "User not found with the provided creator_id"
-                } else {
-                    "Business account not found with the provided creator_business_id"
-                }
-            } else {
-                "Error creating community"
-            };
-            
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": error_message,
-                "details": e.to_string()
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(community))
 }
 
 pub async fn get_community(
     db: web::Data<PgPool>,
-    web::Query(pagination): web::Query<Pagination>,
-) -> HttpResponse {
-    let limit = pagination.limit.unwrap_or(5);
-    let offset = pagination.offset.unwrap_or(0);
- 
-    let users = sqlx::query_as::<_, Community>(
-        "SELECT * FROM communities ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+
+    let communities = sqlx::query_as::<_, Community>(
+        r#"
+        SELECT * FROM communities
+        WHERE $1::timestamp IS NULL OR (created_at, id) < ($1, $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+        "#
     )
-    .bind(limit)
-    .bind(offset)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
- 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving community")
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error retrieving community"))?;
+
+    Ok(HttpResponse::Ok().json(Page::new(communities, &page, |c| Cursor::new(c.created_at, c.id))))
 }
 
 pub async fn delete_community_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,  // Changed from email to UUID
-) -> HttpResponse {
-    let result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_community(&db, &principal, *id, CommunityRole::Owner).await?;
+    sqlx::query!(
         "DELETE FROM communities WHERE id = $1 RETURNING id",
         id.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "community deleted successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "community not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error deleting community"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error deleting community"))?
+    .ok_or_else(|| ApiError::not_found("community not found"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "community deleted successfully"
+    })))
 }
 
 pub async fn update_community_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<Update_Community>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_community(&db, &principal, *id, CommunityRole::Moderator).await?;
+    sqlx::query!(
         r#"
         UPDATE communities 
         SET 
@@ -216,50 +175,28 @@ pub async fn update_community_by_id(
         id.into_inner(),
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "Community updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Community not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating community"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error updating community"))?
+    .ok_or_else(|| ApiError::not_found("Community not found"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Community updated successfully"
+    })))
 }
 
 pub async fn get_community_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let community = sqlx::query_as::<_, Community>(
         "SELECT * FROM communities WHERE id = $1"
     )
     .bind(id.into_inner())
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving community"))?
+    .ok_or_else(|| ApiError::not_found("Community not found"))?;
 
-    match community {
-        Ok(Some(community)) => HttpResponse::Ok().json(community),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Community not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error retrieving community"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(community))
 }
@@ -3,6 +3,10 @@ use uuid::Uuid;
 use sqlx::PgPool;
 use crate::models::community_members::{Community_Members, Create_Community_Member, Update_Community_Member};
 use serde_json::json;
+use crate::auth::Principal;
+use crate::authz::{authorize_add_member, authorize_remove_member, authorize_update_member, CommunityRole};
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 
 fn get_lang(req: &HttpRequest) -> String {
     req.headers()
@@ -23,10 +27,16 @@ pub async fn create_community_member(
     req: HttpRequest,
     db: web::Data<PgPool>,
     json: web::Json<Create_Community_Member>,
-) -> HttpResponse {
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
-    let user = sqlx::query_as::<_, Community_Members>(
+
+    let Some(role) = CommunityRole::from_db(&json.role) else {
+        return Err(invalid_role());
+    };
+    authorize_add_member(&db, &principal, json.community_id, json.user_id, role).await?;
+    let member = sqlx::query_as::<_, Community_Members>(
         r#"
         INSERT INTO community_members (id, community_id, user_id, role)
         VALUES ($1, $2, $3, $4)
@@ -39,72 +49,67 @@ pub async fn create_community_member(
     // .bind(Uuid::new_v4()) 
     .bind(&json.role)
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error adding member in community"))?;
 
-    match user {
-        Ok(c) => HttpResponse::Ok().json(c),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error adding member in community"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(member))
 }
 
-pub async fn get_community_members(db: web::Data<PgPool>) -> HttpResponse {
-    let users = sqlx::query_as::<_, Community_Members>(
-        "SELECT * FROM community_members"
+pub async fn get_community_members(
+    db: web::Data<PgPool>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let members = sqlx::query_as::<_, Community_Members>(
+        r#"
+        SELECT * FROM community_members
+        WHERE $1::timestamp IS NULL OR (joined_at, id) < ($1, $2)
+        ORDER BY joined_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving community members"))?;
 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving community members")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(members, &page, |m| Cursor::new(m.joined_at, m.id))))
 }
 
 pub async fn delete_community_member_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,  // Changed from email to UUID
-) -> HttpResponse {
-    let result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_remove_member(&db, &principal, *id).await?;
+    sqlx::query!(
         "DELETE FROM community_members WHERE id = $1 RETURNING id",
         id.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error deleting community member"))?
+    .ok_or_else(|| ApiError::not_found("community member not found"))?;
 
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "community member deleted successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "community member not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error deleting community member"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "community member deleted successfully"
+    })))
 }
 
 pub async fn update_community_member_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<Update_Community_Member>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    if json.role.as_deref().is_some_and(|role| CommunityRole::from_db(role).is_none()) {
+        return Err(invalid_role());
+    }
+    authorize_update_member(&db, &principal, *id).await?;
+    sqlx::query!(
         r#"
         UPDATE community_members
         SET 
@@ -116,23 +121,16 @@ pub async fn update_community_member_by_id(
         id.into_inner(),
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error updating community member"))?
+    .ok_or_else(|| ApiError::not_found("community member not found"))?;
 
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "Community member updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Community not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating job"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Community member updated successfully"
+    })))
+}
+
+fn invalid_role() -> ApiError {
+    ApiError::bad_request("role must be one of: member, moderator, owner")
 }
@@ -0,0 +1,36 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::auth::Principal;
+use crate::authz::authorize_own_data;
+use crate::error::ApiError;
+use crate::feed;
+use crate::pagination::PageParams;
+
+#[derive(Deserialize)]
+pub struct SeenPosts {
+    pub post_ids: Vec<Uuid>,
+}
+
+pub async fn get_feed(
+    db: web::Data<PgPool>,
+    user_id: web::Path<Uuid>,
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_own_data(&principal, *user_id)?;
+    let posts = feed::feed_page(db.get_ref(), *user_id, &page).await?;
+    Ok(HttpResponse::Ok().json(posts))
+}
+
+pub async fn mark_posts_seen(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    json: web::Json<SeenPosts>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    let marked = feed::mark_seen(db.get_ref(), user_id, &json.post_ids).await?;
+    Ok(HttpResponse::Ok().json(json!({ "marked": marked })))
+}
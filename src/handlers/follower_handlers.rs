@@ -1,16 +1,16 @@
-use actix_web::{ web, HttpResponse, HttpRequest, Responder };
+use actix_web::{ web, HttpResponse, HttpRequest };
 use serde::Deserialize;
 use uuid::Uuid;
 use sqlx::{PgPool, postgres::PgDatabaseError};
 use crate::models::followers::{Followers, Create_Follower, Update_Follower, FollowerListUser, FollowingListUser};
+use crate::auth::Principal;
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 use serde_json::json;
-use actix_web::http::StatusCode;
 
 
 #[derive(Deserialize)]
 pub struct FollowPayload {
-    pub follower_id: Uuid,
-    
     pub followed_id: Uuid,
 }
 
@@ -32,11 +32,13 @@ fn get_lang(req: &HttpRequest) -> String {
 pub async fn create_follower(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<Create_Follower>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
-    let user = sqlx::query_as::<_, Followers>(
+    let follower_id = principal.user_id()?;
+    let follower = sqlx::query_as::<_, Followers>(
         r#"
         INSERT INTO followers (id, follower_id, followed_id, is_following)
         VALUES ($1, $2, $3, $4)
@@ -44,76 +46,64 @@ pub async fn create_follower(
         "#
     )
     .bind(Uuid::new_v4())
-    .bind(&json.follower_id)
+    .bind(follower_id)
     .bind(&json.followed_id)    
     .bind(&json.is_following)
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error in adding follower"))?;
 
-    match user {
-        Ok(c) => HttpResponse::Ok().json(c),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error in adding follower "
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(follower))
 }
 
-pub async fn view_followers(db: web::Data<PgPool>) -> HttpResponse {
-    let users = sqlx::query_as::<_, Followers>(
-        "SELECT * FROM followers"
+pub async fn view_followers(
+    db: web::Data<PgPool>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let follows = sqlx::query_as::<_, Followers>(
+        r#"
+        SELECT * FROM followers
+        WHERE $1::timestamp IS NULL OR (followed_at, id) < ($1, $2)
+        ORDER BY followed_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving followers"))?;
 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving followers")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(follows, &page, |f| Cursor::new(f.followed_at, f.id))))
 }
 
 pub async fn delete_follower_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,  // Changed from email to UUID
-) -> HttpResponse {
-    let result = sqlx::query!(
+) -> Result<HttpResponse, ApiError> {
+    sqlx::query!(
         "DELETE FROM followers WHERE id = $1 RETURNING id",
         id.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error removing follower"))?
+    .ok_or_else(|| ApiError::not_found("follower not found"))?;
 
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "follower removed successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "follower not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error removing follower"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "follower removed successfully"
+    })))
 }
 
 pub async fn update_follower_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<Update_Follower>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+) -> Result<HttpResponse, ApiError> {
+    sqlx::query!(
         r#"
         UPDATE followers
         SET 
@@ -125,49 +115,25 @@ pub async fn update_follower_by_id(
         id.into_inner(),
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error updating follower"))?
+    .ok_or_else(|| ApiError::not_found("follower not found"))?;
 
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "following status updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "follower not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating follower"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "following status updated successfully"
+    })))
 }
 
 pub async fn toggle_follow(
     payload: web::Json<FollowPayload>,
     db: web::Data<PgPool>,
-) -> impl Responder {
-    let FollowPayload { follower_id, followed_id } = payload.into_inner();
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    let FollowPayload { followed_id } = payload.into_inner();
 
-    // Check if follower is a valid user
-    let is_valid_follower = sqlx::query_scalar!(
-        "SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)",
-        follower_id
-    )
-    .fetch_one(db.get_ref())
-    .await
-    .unwrap_or(None)
-    .unwrap_or(false);
-
-    if !is_valid_follower {
-        return HttpResponse::BadRequest().json(json!({
-            "status": "error",
-            "message": "Only normal users can follow others"
-        }));
-    }
+    // Only normal users can follow others
+    let follower_id = principal.user_id()?;
 
     // Check if follow exists
     let exists = sqlx::query!(
@@ -176,25 +142,25 @@ pub async fn toggle_follow(
         followed_id
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(follow_error)?;
 
     match exists {
-        Ok(Some(row)) => {
+        Some(row) => {
             // Unfollow (delete)
-            if let Err(e) = sqlx::query!(
+            sqlx::query!(
                 "DELETE FROM followers WHERE id = $1",
                 row.id
             )
             .execute(db.get_ref())
-            .await {
-                return handle_db_error(e);
-            }
+            .await
+            .map_err(follow_error)?;
 
-            HttpResponse::Ok().json("unfollowed")
+            Ok(HttpResponse::Ok().json("unfollowed"))
         }
-        Ok(None) => {
+        None => {
             // Follow (insert)
-            let result = sqlx::query!(
+            sqlx::query!(
                 r#"INSERT INTO followers (id, follower_id, followed_id, is_following)
                 VALUES ($1, $2, $3, $4)"#,
                 Uuid::new_v4(),
@@ -203,147 +169,128 @@ pub async fn toggle_follow(
                 true
             )
             .execute(db.get_ref())
-            .await;
+            .await
+            .map_err(follow_error)?;
 
-            match result {
-                Ok(_) => HttpResponse::Ok().json("followed"),
-                Err(e) => handle_db_error(e)
-            }
+            Ok(HttpResponse::Ok().json("followed"))
         }
-        Err(e) => handle_db_error(e)
     }
 }
 
-fn handle_db_error(e: sqlx::Error) -> HttpResponse {
-    eprintln!("Database error: {:?}", e);
-    
-    if let Some(db_err) = e.as_database_error() {
-        if let Some(constraint) = db_err.constraint() {
-            if constraint == "followers_follower_id_fkey" {
-                return HttpResponse::BadRequest().json(json!({
-                    "status": "error",
-                    "message": "Invalid follower ID. Only user accounts can follow others."
-                }));
-            }
-            if constraint == "followers_followed_id_fkey" {
-                return HttpResponse::BadRequest().json(json!({
-                    "status": "error",
-                    "message": "Invalid user to follow."
-                }));
-            }
-        }
+fn follow_error(e: sqlx::Error) -> ApiError {
+    if let Some(db_err) = e.as_database_error()
+        && db_err.constraint() == Some("followers_followed_id_fkey")
+    {
+        return ApiError::unprocessable("Invalid user to follow.");
     }
-
-    HttpResponse::InternalServerError().json(json!({
-        "status": "error",
-        "message": "Database operation failed"
-    }))
+    ApiError::db("Database operation failed")(e)
 }
 
 pub async fn follow_status(
     path: web::Path<(Uuid, Uuid)>,
     db: web::Data<PgPool>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let (follower_id, followed_id) = path.into_inner();
 
-    let result = sqlx::query_scalar!(
+    let exists = sqlx::query_scalar!(
         "SELECT EXISTS(SELECT 1 FROM followers WHERE follower_id = $1 AND followed_id = $2)",
         follower_id,
         followed_id
     )
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Failed to check follow status"))?;
 
-    match result {
-        Ok(exists) => HttpResponse::Ok().json(json!({ "is_following": exists })),
-        Err(_) => HttpResponse::InternalServerError().json(json!({ "error": "Failed to check follow status" })),
-    }
+    Ok(HttpResponse::Ok().json(json!({ "is_following": exists })))
 }
 
 pub async fn get_followers_count(
     db: web::Data<PgPool>,
     user_id: web::Path<Uuid>
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let count = sqlx::query_scalar!(
         "SELECT COUNT(*) FROM followers WHERE followed_id = $1",
         user_id.into_inner()
     )
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error counting followers"))?;
 
-    match count {
-        Ok(count) => HttpResponse::Ok().json(json!({ "followers": count })),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({ "followers": count })))
 }
 
 pub async fn get_following_count(
     db: web::Data<PgPool>,
     user_id: web::Path<Uuid>
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let count = sqlx::query_scalar!(
         "SELECT COUNT(*) FROM followers WHERE follower_id = $1",
         user_id.into_inner()
     )
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error counting following"))?;
 
-    match count {
-        Ok(count) => HttpResponse::Ok().json(json!({ "following": count })),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({ "following": count })))
 }
 
 
-pub async fn get_followers_list(db: web::Data<PgPool>, user_id: web::Path<Uuid>) -> impl Responder {
-    let result = sqlx::query_as!(
+// A user follows another at most once, so (followed_at, user id) is unique within these lists.
+pub async fn get_followers_list(
+    db: web::Data<PgPool>,
+    user_id: web::Path<Uuid>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let rows = sqlx::query_as!(
         FollowerListUser,
         r#"
-        SELECT u.id, u.first_name, u.last_name, u.username, u.profile_pic
+        SELECT u.id, u.first_name, u.last_name, u.username, u.profile_pic, f.followed_at
         FROM followers f
         JOIN users u ON f.follower_id = u.id
         WHERE f.followed_id = $1
+          AND ($2::timestamp IS NULL OR (f.followed_at, f.follower_id) < ($2, $3))
+        ORDER BY f.followed_at DESC, f.follower_id DESC
+        LIMIT $4
         "#,
-        user_id.into_inner()
+        user_id.into_inner(),
+        after.map(|c| c.created_at),
+        after.map(|c| c.id),
+        page.fetch_limit()
     )
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving followers"))?;
 
-    match result {
-        Ok(rows) => HttpResponse::Ok().json(rows),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(rows, &page, |u| Cursor::new(u.followed_at, u.id))))
 }
 
 
-pub async fn get_following_list(db: web::Data<PgPool>, user_id: web::Path<Uuid>) -> impl Responder {
-    let result = sqlx::query_as!(
+pub async fn get_following_list(
+    db: web::Data<PgPool>,
+    user_id: web::Path<Uuid>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let rows = sqlx::query_as!(
         FollowingListUser,
         r#"
-        SELECT u.id, u.first_name, u.last_name, u.username, u.profile_pic
+        SELECT u.id, u.first_name, u.last_name, u.username, u.profile_pic, f.followed_at
         FROM followers f
         JOIN users u ON f.followed_id = u.id
         WHERE f.follower_id = $1
+          AND ($2::timestamp IS NULL OR (f.followed_at, f.followed_id) < ($2, $3))
+        ORDER BY f.followed_at DESC, f.followed_id DESC
+        LIMIT $4
         "#,
-        user_id.into_inner()
+        user_id.into_inner(),
+        after.map(|c| c.created_at),
+        after.map(|c| c.id),
+        page.fetch_limit()
     )
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving following"))?;
 
-    match result {
-        Ok(rows) => HttpResponse::Ok().json(rows),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(rows, &page, |u| Cursor::new(u.followed_at, u.id))))
 }
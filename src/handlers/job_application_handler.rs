@@ -1,11 +1,21 @@
 use actix_web::{web, HttpResponse, HttpRequest};
 use uuid::Uuid;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
  use serde::Serialize;
 
 use serde_json::json;
-use crate::models::user::User;
-use crate::models::job_applications::{JobApplication, CreateJobApplicationRequest};
+use crate::models::job_applications::{
+    ApplicationEvent, ApplicationStatus, CreateJobApplicationRequest, JobApplication, ResumeUpload,
+    UpdateApplicationStatus,
+};
+use crate::ats::{self, StatusChange};
+use crate::config::{Config, ReapplyPolicy};
+use crate::job_lifecycle;
+use crate::resumes;
+use crate::auth::{Principal, PrincipalKind};
+use crate::authz::{authorize_application_party, authorize_job, authorize_own_data};
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 
 fn get_lang(req: &HttpRequest) -> String {
     req.headers()
@@ -28,92 +38,305 @@ fn get_lang(req: &HttpRequest) -> String {
 pub async fn create_job_application(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    principal: Principal,
     json: web::Json<CreateJobApplicationRequest>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);
     println!("Language preference: {}", lang);
+    let applicant_id = principal.user_id()?;
     
-    // Get user's resume
-    let user_result = sqlx::query_as::<_, User>(
-        "SELECT * FROM users WHERE id = $1"
+    // Only uploaded resumes are sent; the profile's `resume_url` may point anywhere.
+    let resume_url = match json.resume_id {
+        Some(resume_id) => {
+            let resume = resumes::find(db.get_ref(), resume_id).await?;
+            if resume.user_id != applicant_id {
+                return Err(ApiError::forbidden("You can only apply with your own resumes"));
+            }
+            resume.url
+        }
+        None => String::new(),
+    };
+    let mut tx = db.begin().await.map_err(ApiError::db("Error creating job application"))?;
+
+    let now = chrono::Utc::now().naive_utc();
+    job_lifecycle::admit_applicant(&mut tx, json.job_id, now).await?;
+
+    let existing = sqlx::query!(
+        "SELECT id, status FROM job_applications WHERE job_id = $1 AND applicant_id = $2 FOR UPDATE",
+        json.job_id,
+        applicant_id
     )
-    .bind(&json.applicant_id)
-    .fetch_one(db.get_ref())
-    .await;
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::db("Error creating job application"))?;
+
+    let application = match existing {
+        None => {
+            // Create application
+            let application = sqlx::query_as::<_, JobApplication>(
+                r#"
+                INSERT INTO job_applications (
+                    id, job_id, applicant_id, cover_letter,
+                    resume_url, application_date, status,
+                    updated_at, is_active, resume_id
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING *
+                "#
+            )
+            .bind(Uuid::new_v4())
+            .bind(json.job_id)
+            .bind(applicant_id)
+            .bind(&json.cover_letter)
+            .bind(resume_url) // Empty without a resume
+            .bind(now)
+            .bind("applied") // Default status
+            .bind(now)
+            .bind(true)
+            .bind(json.resume_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ApiError::db("Error creating job application"))?;
+            ats::record_status_change(&mut tx, application.id, None, ApplicationStatus::Applied, &principal, None)
+                .await?;
+            application
+        }
+        Some(existing) if existing.status == ApplicationStatus::Withdrawn.as_str() => {
+            check_reapply(&mut tx, existing.id, config.reapply_policy).await?;
+            // Applying again reopens the withdrawn application, keeping its history.
+            let application = sqlx::query_as::<_, JobApplication>(
+                r#"
+                UPDATE job_applications
+                SET status = 'applied', is_active = TRUE, cover_letter = $2, resume_url = $3, resume_id = $4,
+                    application_date = NOW(), updated_at = NOW()
+                WHERE id = $1
+                RETURNING *
+                "#
+            )
+            .bind(existing.id)
+            .bind(&json.cover_letter)
+            .bind(resume_url)
+            .bind(json.resume_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ApiError::db("Error creating job application"))?;
+            ats::record_status_change(
+                &mut tx,
+                application.id,
+                Some(ApplicationStatus::Withdrawn),
+                ApplicationStatus::Applied,
+                &principal,
+                None,
+            )
+            .await?;
+            application
+        }
+        Some(_) => return Err(ApiError::conflict("You have already applied to this job")),
+    };
+    job_lifecycle::close_if_full(&mut tx, json.job_id, now).await?;
+    tx.commit().await.map_err(ApiError::db("Error creating job application"))?;
+
+    Ok(HttpResponse::Ok().json(application))
+}
 
-    let user = match user_result {
-        Ok(user) => user,
-        Err(e) => {
-            eprintln!("DB error fetching user: {:?}", e);
-            return HttpResponse::BadRequest().body("User not found");
+/// Whether `policy` lets an applicant who withdrew apply to the same job again now.
+async fn check_reapply(conn: &mut PgConnection, application_id: Uuid, policy: ReapplyPolicy) -> Result<(), ApiError> {
+    let hours = match policy {
+        ReapplyPolicy::Always => return Ok(()),
+        ReapplyPolicy::Never => {
+            return Err(ApiError::conflict("You withdrew your application to this job and cannot apply again"));
         }
+        ReapplyPolicy::AfterHours(hours) => hours,
     };
+    let withdrawn_at = sqlx::query_scalar!(
+        "SELECT max(created_at) FROM job_application_events WHERE application_id = $1 AND to_status = 'withdrawn'",
+        application_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(ApiError::db("Error creating job application"))?;
+    if let Some(withdrawn_at) = withdrawn_at {
+        let allowed_from = withdrawn_at + chrono::Duration::hours(hours.into());
+        if chrono::Utc::now().naive_utc() < allowed_from {
+            return Err(ApiError::conflict(format!(
+                "You can apply to this job again from {} UTC",
+                allowed_from.format("%Y-%m-%d %H:%M")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The applicant withdraws their application. It stays in the history, inactive.
+pub async fn withdraw_job_application(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    application_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let application_id = application_id.into_inner();
+    let applicant_id = principal.user_id()?;
+
+    let mut tx = db.begin().await.map_err(ApiError::db("Error withdrawing application"))?;
+    let current = sqlx::query!(
+        "SELECT applicant_id, status FROM job_applications WHERE id = $1 FOR UPDATE",
+        application_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::db("Error withdrawing application"))?
+    .ok_or_else(|| ApiError::not_found("Application not found"))?;
+    if current.applicant_id != Some(applicant_id) {
+        return Err(ApiError::forbidden("You can only withdraw your own applications"));
+    }
+    let status = ats::parse_status(&current.status)?;
+    if status.is_final() {
+        return Err(ApiError::conflict(format!("Cannot withdraw an application that is {}", status.as_str())));
+    }
 
-    let resume_url = user.resume_url.unwrap_or_else(|| "".to_string());
-    // Create application
     let application = sqlx::query_as::<_, JobApplication>(
         r#"
-        INSERT INTO job_applications (
-            id, job_id, applicant_id, cover_letter, 
-            resume_url, application_date, status, 
-            updated_at, is_active
-        )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        UPDATE job_applications SET status = 'withdrawn', is_active = FALSE, updated_at = NOW()
+        WHERE id = $1
         RETURNING *
         "#
     )
-    .bind(Uuid::new_v4())
-    .bind(&json.job_id)
-    .bind(&json.applicant_id)
-    .bind(&json.cover_letter)
-    .bind(resume_url) // Use user's resume URL (can be empty)
-    .bind(chrono::Utc::now().naive_utc())
-    .bind("applied") // Default status
-    .bind(chrono::Utc::now().naive_utc())
-    .bind(true)
-    .fetch_one(db.get_ref())
-    .await;
-
-    match application {
-        Ok(app) => HttpResponse::Ok().json(app),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "error": "Error creating job application",
-                "details": e.to_string()
-            }))
-        }
+    .bind(application_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::db("Error withdrawing application"))?;
+    let event =
+        ats::record_status_change(&mut tx, application_id, Some(status), ApplicationStatus::Withdrawn, &principal, None)
+            .await?;
+    tx.commit().await.map_err(ApiError::db("Error withdrawing application"))?;
+
+    Ok(HttpResponse::Ok().json(StatusChange { application, event }))
+}
+
+/// Attaches a resume uploaded for this application only (PDF or DOCX, sent as the request
+/// body); it replaces the one the application was sent with and is not added to the library.
+pub async fn upload_application_resume(
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    principal: Principal,
+    application_id: web::Path<Uuid>,
+    web::Query(upload): web::Query<ResumeUpload>,
+    payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let application_id = application_id.into_inner();
+    let applicant_id = principal.user_id()?;
+    let bytes = resumes::read_upload(payload).await?;
+
+    let mut tx = db.begin().await.map_err(ApiError::db("Error uploading resume"))?;
+    let current = sqlx::query!(
+        "SELECT applicant_id, status FROM job_applications WHERE id = $1 FOR UPDATE",
+        application_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::db("Error uploading resume"))?
+    .ok_or_else(|| ApiError::not_found("Application not found"))?;
+    if current.applicant_id != Some(applicant_id) {
+        return Err(ApiError::forbidden("You can only change the resume of your own applications"));
+    }
+    let status = ats::parse_status(&current.status)?;
+    if status.is_final() {
+        return Err(ApiError::conflict(format!("Cannot change the resume of an application that is {}", status.as_str())));
     }
+
+    let resume =
+        resumes::store(&mut tx, &config.resume_dir, applicant_id, upload.name.as_deref(), &bytes, false).await?;
+    let application = sqlx::query_as::<_, JobApplication>(
+        "UPDATE job_applications SET resume_id = $2, resume_url = $3, updated_at = NOW() WHERE id = $1 RETURNING *"
+    )
+    .bind(application_id)
+    .bind(resume.id)
+    .bind(&resume.url)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(ApiError::db("Error uploading resume"))?;
+    tx.commit().await.map_err(ApiError::db("Error uploading resume"))?;
+
+    Ok(HttpResponse::Ok().json(json!({ "application": application, "resume": resume })))
+}
+
+/// Moves an application through the hiring pipeline; only the business that posted the job may.
+pub async fn update_application_status(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    application_id: web::Path<Uuid>,
+    json: web::Json<UpdateApplicationStatus>,
+) -> Result<HttpResponse, ApiError> {
+    let application_id = application_id.into_inner();
+    let job_id = ats::job_of_application(db.get_ref(), application_id).await?;
+    authorize_job(db.get_ref(), &principal, job_id).await?;
+
+    let mut tx = db.begin().await.map_err(ApiError::db("Error updating application status"))?;
+    let change = ats::change_status(&mut tx, application_id, json.status, &principal, json.note.as_deref()).await?;
+    tx.commit().await.map_err(ApiError::db("Error updating application status"))?;
+
+    Ok(HttpResponse::Ok().json(change))
+}
+
+/// Every status change of an application, oldest first, with the notes left for the applicant.
+pub async fn get_application_history(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    application_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let application_id = application_id.into_inner();
+    authorize_application_party(db.get_ref(), &principal, application_id).await?;
+
+    let events = sqlx::query_as::<_, ApplicationEvent>(
+        "SELECT * FROM job_application_events WHERE application_id = $1 ORDER BY created_at, id"
+    )
+    .bind(application_id)
+    .fetch_all(db.get_ref())
+    .await
+    .map_err(ApiError::db("Error fetching application history"))?;
+
+    Ok(HttpResponse::Ok().json(events))
 }
 
-// New handler for business to view applications
+/// The applicants of a job for the business that posted it. Contact details follow the
+/// applicants' privacy settings, as in `UserResponse::for_viewer`.
 pub async fn get_applications_for_job(
     db: web::Data<PgPool>,
     job_id: web::Path<Uuid>,
-) -> HttpResponse {
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let job_id = job_id.into_inner();
+    authorize_job(db.get_ref(), &principal, job_id).await?;
+
     let applications = sqlx::query_as::<_, ApplicationWithUser>(
         r#"
         SELECT 
             ja.id, ja.job_id, ja.cover_letter, ja.resume_url, 
             ja.application_date, ja.status, ja.updated_at, ja.is_active,
-            u.id as user_id, u.first_name, u.last_name, u.email, 
-            u.profile_pic, u.organisation, u.bio, u.mobile
+            u.id as user_id, u.first_name, u.last_name,
+            CASE WHEN u.show_email OR $5 THEN u.email END AS email,
+            u.profile_pic, u.organisation, u.bio,
+            CASE WHEN u.show_mobile OR $5 THEN u.mobile END AS mobile
         FROM job_applications ja
         JOIN users u ON ja.applicant_id = u.id
         WHERE ja.job_id = $1
+          AND ($2::timestamp IS NULL OR (ja.application_date, ja.id) < ($2, $3))
+        ORDER BY ja.application_date DESC, ja.id DESC
+        LIMIT $4
         "#
     )
-    .bind(job_id.into_inner())
+    .bind(job_id)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
+    .bind(principal.is_admin)
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error fetching applications"))?;
 
-    match applications {
-        Ok(apps) => HttpResponse::Ok().json(apps),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error fetching applications")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(applications, &page, |a| Cursor::new(a.application_date, a.id))))
 }
 
 #[derive(Serialize, sqlx::FromRow)]
@@ -132,7 +355,7 @@ struct ApplicationWithUser {
     user_id: Uuid,
     first_name: String, // Changed to non-optional
     last_name: String, // Changed to non-optional
-    email: String, // Changed to non-optional
+    email: Option<String>,
     profile_pic: Option<String>,
     organisation: Option<String>,
     bio: Option<serde_json::Value>,
@@ -143,24 +366,18 @@ struct ApplicationWithUser {
 pub async fn get_applied_jobs_count_for_user(
     db: web::Data<PgPool>,
     user_id: web::Path<Uuid>,
-) -> HttpResponse {
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_own_data(&principal, *user_id)?;
     let count = sqlx::query_scalar!(
         "SELECT COUNT(*) FROM job_applications WHERE applicant_id = $1",
         user_id.into_inner()
     )
     .fetch_one(db.get_ref())
-    .await;
-
-    match count {
-        Ok(count) => HttpResponse::Ok().json(json!({ "count": count })),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "error": "Error fetching count",
-                "details": e.to_string()
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error fetching count"))?;
+
+    Ok(HttpResponse::Ok().json(json!({ "count": count })))
 }
 
 #[derive(Serialize, sqlx::FromRow)]
@@ -177,7 +394,11 @@ pub struct AppliedJob {
 pub async fn get_applied_jobs_for_user(
     db: web::Data<PgPool>,
     user_id: web::Path<Uuid>,
-) -> HttpResponse {
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_own_data(&principal, *user_id)?;
+    let after = page.after()?;
     let applications = sqlx::query_as::<_, AppliedJob>(
         r#"
         SELECT 
@@ -191,23 +412,22 @@ pub async fn get_applied_jobs_for_user(
         FROM job_applications ja
         JOIN job_listings jl ON ja.job_id = jl.id
         WHERE ja.applicant_id = $1
-        ORDER BY ja.application_date DESC
+          AND ($2::timestamp IS NULL OR (ja.application_date, ja.id) < ($2, $3))
+        ORDER BY ja.application_date DESC, ja.id DESC
+        LIMIT $4
         "#
     )
     .bind(user_id.into_inner())
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
-
-    match applications {
-        Ok(apps) => HttpResponse::Ok().json(apps),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "error": "Error fetching applied jobs",
-                "details": e.to_string()
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error fetching applied jobs"))?;
+
+    Ok(HttpResponse::Ok().json(Page::new(applications, &page, |a| {
+        Cursor::new(a.application_date, a.application_id)
+    })))
 }
 #[derive(Serialize, sqlx::FromRow)]
 pub struct UserApplication {
@@ -221,11 +441,16 @@ pub struct UserApplication {
     updated_at: chrono::NaiveDateTime,
 }
 
+/// One user's application to a job, for that user or the business that posted the job.
 pub async fn get_application_for_job_and_user(
     db: web::Data<PgPool>,
     path: web::Path<(Uuid, Uuid)>,
-) -> HttpResponse {
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
     let (job_id, user_id) = path.into_inner();
+    if !principal.is(PrincipalKind::User, user_id) {
+        authorize_job(db.get_ref(), &principal, job_id).await?;
+    }
     let application = sqlx::query_as::<_, UserApplication>(
         "SELECT * FROM job_applications 
         WHERE job_id = $1 AND applicant_id = $2"
@@ -233,19 +458,9 @@ pub async fn get_application_for_job_and_user(
     .bind(job_id)
     .bind(user_id)
     .fetch_optional(db.get_ref())
-    .await;
-
-    match application {
-        Ok(Some(app)) => HttpResponse::Ok().json(app),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "error": "Application not found"
-        })),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "error": "Error fetching application",
-                "details": e.to_string()
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error fetching application"))?
+    .ok_or_else(|| ApiError::not_found("Application not found"))?;
+
+    Ok(HttpResponse::Ok().json(application))
 }
\ No newline at end of file
@@ -1,8 +1,21 @@
 use actix_web::{web, HttpResponse, HttpRequest};
 use uuid::Uuid;
 use sqlx::PgPool;
-use crate::models::job_list::{Job_listings, CreateJob, UpdateJob,Pagination};
+use crate::models::job_list::{
+    Job_listings, CreateJob, UpdateJob, JobFilters, JobFacets, FacetCount, JobStatus, SalaryRange,
+};
+use crate::pagination::{Cursor, Page, PageParams};
+use crate::auth::{Principal, PrincipalKind};
+use crate::authz::{authorize_job, authorize_own_data};
+use crate::error::ApiError;
+use crate::job_lifecycle;
+use crate::recommendations;
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
 use serde_json::json;
+use sqlx::postgres::PgArguments;
+use sqlx::query::QueryAs;
+use sqlx::Postgres;
 
 fn get_lang(req: &HttpRequest) -> String {
     req.headers()
@@ -19,31 +32,97 @@ fn get_lang(req: &HttpRequest) -> String {
         .to_string()
 }
 
+/// The salary of a job as sent by the client: the numbers when given, otherwise parsed from the
+/// `salary_range` label. Returns the label to store with them (built from the numbers when the
+/// client sent none).
+fn resolve_salary(
+    label: Option<&str>,
+    min: Option<i32>,
+    max: Option<i32>,
+    currency: Option<&str>,
+) -> Result<(Option<String>, Option<SalaryRange>), ApiError> {
+    let currency = match currency.map(str::trim) {
+        Some(code) if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) => Some(code.to_uppercase()),
+        Some(_) => return Err(ApiError::bad_request("salary_currency must be a 3-letter currency code")),
+        None => None,
+    };
+    let band = match (min.or(max), max.or(min)) {
+        (Some(min), Some(max)) => {
+            if min < 0 || min > max {
+                return Err(ApiError::bad_request("salary_min must be between 0 and salary_max"));
+            }
+            Some(SalaryRange { min, max, currency })
+        }
+        _ => label.and_then(SalaryRange::parse).map(|band| match currency {
+            Some(currency) => SalaryRange { currency: Some(currency), ..band },
+            None => band,
+        }),
+    };
+    let label = label.map(str::to_string).or_else(|| band.as_ref().map(SalaryRange::label));
+    Ok((label, band))
+}
+
+/// `closes_at` and `max_applicants` as sent by the client, checked against `now`.
+fn check_limits(closes_at: Option<NaiveDateTime>, max_applicants: Option<i32>, now: NaiveDateTime) -> Result<(), ApiError> {
+    if closes_at.is_some_and(|closes_at| closes_at <= now) {
+        return Err(ApiError::bad_request("closes_at must be in the future"));
+    }
+    if max_applicants.is_some_and(|max| max < 1) {
+        return Err(ApiError::bad_request("max_applicants must be at least 1"));
+    }
+    Ok(())
+}
+
+/// Drafts are only shown to the business that wrote them and to admins.
+fn can_see_drafts(viewer: Option<&Principal>, posted_by: Uuid) -> bool {
+    viewer.is_some_and(|viewer| viewer.is_admin || viewer.is(PrincipalKind::Business, posted_by))
+}
 
 pub async fn create_job(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<CreateJob>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
-    let user = sqlx::query_as::<_, Job_listings>(
+    // Jobs are posted by business accounts only (posted_by references business_accounts)
+    let posted_by = principal.business_id()?;
+    let (salary_range, salary) = resolve_salary(
+        json.salary_range.as_deref(),
+        json.salary_min,
+        json.salary_max,
+        json.salary_currency.as_deref(),
+    )?;
+    let now = Utc::now().naive_utc();
+    check_limits(json.closes_at, json.max_applicants, now)?;
+    let status = match (json.status, json.is_active) {
+        (Some(status @ (JobStatus::Draft | JobStatus::Published)), _) => status,
+        (Some(_), _) => return Err(ApiError::bad_request("New jobs must be draft or published")),
+        (None, Some(false)) => JobStatus::Closed,
+        (None, _) => JobStatus::Published,
+    };
+    let job = sqlx::query_as::<_, Job_listings>(
     r#"
     INSERT INTO job_listings (
         id, posted_by, applied_by, organisation, title, description,
         location, job_type, skills, notice_period, min_experience,
-        salary_range, is_active, job_posted_by
+        salary_range, is_active, job_posted_by,
+        salary_min, salary_max, salary_currency,
+        status, closes_at, max_applicants, closed_at
     )
     VALUES (
         $1, $2, $3, $4, $5, $6,
         $7, $8, $9, $10, $11,
-        $12, $13, $14
+        $12, $13, $14,
+        $15, $16, $17,
+        $18, $19, $20, $21
     )
     RETURNING *
     "#
 )
 .bind(Uuid::new_v4())
-.bind(json.posted_by)
+.bind(posted_by)
 .bind(json.applied_by)
 .bind(&json.organisation)
 .bind(&json.title)
@@ -53,73 +132,246 @@ pub async fn create_job(
 .bind(&json.skills)
 .bind(&json.notice_period)
 .bind(&json.min_experience)
-.bind(&json.salary_range)
-.bind(json.is_active.unwrap_or(true))
+.bind(&salary_range)
+.bind(status == JobStatus::Published)
 .bind(&json.job_posted_by)
+.bind(salary.as_ref().map(|s| s.min))
+.bind(salary.as_ref().map(|s| s.max))
+.bind(salary.and_then(|s| s.currency))
+.bind(status.as_str())
+.bind(json.closes_at)
+.bind(json.max_applicants)
+.bind((status == JobStatus::Closed).then_some(now))
 .fetch_one(db.get_ref())
-.await;
+.await
+.map_err(ApiError::db("Error creating job"))?;
+
+    Ok(HttpResponse::Ok().json(job))
+}
 
+// Filters of `GET /jobs` over `$1..$10` (see `bind_filters`), one per facet so the facet query
+// can leave each one out.
+const LOCATION_FILTER: &str = "($1::text[] IS NULL OR lower(location) = ANY($1))";
+const JOB_TYPE_FILTER: &str = "($2::text[] IS NULL OR lower(job_type) = ANY($2))";
+const EXPERIENCE_FILTER: &str = "($3::int IS NULL OR COALESCE(min_experience, 0) >= $3) \
+    AND ($4::int IS NULL OR COALESCE(min_experience, 0) <= $4)";
+const SKILLS_FILTER: &str = "($5::text[] IS NULL OR CASE WHEN $6 \
+    THEN ARRAY(SELECT lower(s) FROM unnest(skills) s) @> $5 \
+    ELSE ARRAY(SELECT lower(s) FROM unnest(skills) s) && $5 END)";
+const CURRENCY_FILTER: &str = "($9::text IS NULL OR salary_currency = $9)";
+const SALARY_AND_ACTIVE_FILTER: &str = "($7::int IS NULL OR COALESCE(salary_max, salary_min) >= $7) \
+    AND ($8::int IS NULL OR COALESCE(salary_min, salary_max) <= $8) \
+    AND (NOT $10 OR COALESCE(is_active, true))";
+// Drafts and expired jobs are never listed; closed and paused ones are, unless `active_only`.
+const LISTED_FILTER: &str = "status NOT IN ('draft', 'expired')";
+// Values kept per facet, most common first.
+const FACET_VALUES_KEPT: i64 = 20;
 
-    match user {
-        Ok(u) => HttpResponse::Ok().json(u),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error creating job")
+/// `JobFilters` checked and normalized for binding.
+struct ListFilters {
+    locations: Option<Vec<String>>,
+    job_types: Option<Vec<String>>,
+    experience_min: Option<i32>,
+    experience_max: Option<i32>,
+    skills: Option<Vec<String>>,
+    all_skills: bool,
+    salary_min: Option<i32>,
+    salary_max: Option<i32>,
+    currency: Option<String>,
+    active_only: bool,
+}
+
+impl ListFilters {
+    fn from_query(filters: JobFilters) -> Result<Self, ApiError> {
+        let all_skills = match filters.skills_match.as_deref() {
+            None | Some("any") => false,
+            Some("all") => true,
+            Some(_) => return Err(ApiError::bad_request("skills_match must be `any` or `all`")),
+        };
+        if let (Some(min), Some(max)) = (filters.experience_min, filters.experience_max) && min > max {
+            return Err(ApiError::bad_request("experience_min must not be greater than experience_max"));
+        }
+        if let (Some(min), Some(max)) = (filters.salary_min, filters.salary_max) && min > max {
+            return Err(ApiError::bad_request("salary_min must not be greater than salary_max"));
         }
+        let currency = filters.currency.map(|c| c.trim().to_uppercase()).filter(|c| !c.is_empty());
+        // Amounts in different currencies are not comparable.
+        if (filters.salary_min.is_some() || filters.salary_max.is_some()) && currency.is_none() {
+            return Err(ApiError::bad_request("salary_min and salary_max need a currency"));
+        }
+        Ok(ListFilters {
+            locations: split_list(filters.location.as_deref()),
+            job_types: split_list(filters.job_type.as_deref()),
+            experience_min: filters.experience_min,
+            experience_max: filters.experience_max,
+            skills: split_list(filters.skills.as_deref()),
+            all_skills,
+            salary_min: filters.salary_min,
+            salary_max: filters.salary_max,
+            currency,
+            active_only: filters.active_only,
+        })
     }
 }
 
+/// `"Rust, go,"` -> `["rust", "go"]`; `None` when nothing is left.
+fn split_list(value: Option<&str>) -> Option<Vec<String>> {
+    let items: Vec<String> = value?
+        .split(',')
+        .map(|item| item.trim().to_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect();
+    (!items.is_empty()).then_some(items)
+}
+
+fn bind_filters<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    filters: &'q ListFilters,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    query
+        .bind(&filters.locations)
+        .bind(&filters.job_types)
+        .bind(filters.experience_min)
+        .bind(filters.experience_max)
+        .bind(&filters.skills)
+        .bind(filters.all_skills)
+        .bind(filters.salary_min)
+        .bind(filters.salary_max)
+        .bind(&filters.currency)
+        .bind(filters.active_only)
+}
+
+#[derive(Serialize)]
+pub struct JobListPage {
+    #[serde(flatten)]
+    pub page: Page<Job_listings>,
+    /// Only on the first page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<JobFacets>,
+}
+
 pub async fn get_joblist(
     db: web::Data<PgPool>,
-    web::Query(pagination): web::Query<Pagination>,
-) -> HttpResponse {
-    let limit = pagination.limit.unwrap_or(5);
-    let offset = pagination.offset.unwrap_or(0);
- 
-    let jobs = sqlx::query_as::<_, Job_listings>(
-        "SELECT * FROM job_listings ORDER BY created_at DESC LIMIT $1 OFFSET $2"
-    )
-    .bind(limit)
-    .bind(offset)
-    .fetch_all(db.get_ref())
-    .await;
- 
-    match jobs {
-        Ok(jobs) => HttpResponse::Ok().json(jobs),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving job adv")
-        }
+    web::Query(page): web::Query<PageParams>,
+    web::Query(filters): web::Query<JobFilters>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let filters = ListFilters::from_query(filters)?;
+
+    let query = format!(
+        r#"
+        SELECT * FROM job_listings
+        WHERE {LISTED_FILTER} AND {LOCATION_FILTER} AND {JOB_TYPE_FILTER} AND {EXPERIENCE_FILTER} AND {SKILLS_FILTER}
+          AND {CURRENCY_FILTER} AND {SALARY_AND_ACTIVE_FILTER}
+          AND ($11::timestamp IS NULL OR (created_at, id) < ($11, $12))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $13
+        "#
+    );
+    let jobs = bind_filters(sqlx::query_as::<_, Job_listings>(&query), &filters)
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(page.fetch_limit())
+        .fetch_all(db.get_ref())
+        .await
+        .map_err(ApiError::db("Error retrieving job adv"))?;
+
+    let facets = match after {
+        None => Some(job_facets(db.get_ref(), &filters).await?),
+        Some(_) => None,
+    };
+    let page = Page::new(jobs, &page, |job| Cursor::new(job.created_at, job.id));
+    Ok(HttpResponse::Ok().json(JobListPage { page, facets }))
+}
+
+async fn job_facets(db: &PgPool, filters: &ListFilters) -> Result<JobFacets, ApiError> {
+    let query = format!(
+        r#"
+        WITH matched AS (
+            SELECT
+                location, job_type, skills, salary_currency,
+                CASE
+                    WHEN COALESCE(min_experience, 0) <= 1 THEN '0-1'
+                    WHEN min_experience <= 4 THEN '2-4'
+                    WHEN min_experience <= 9 THEN '5-9'
+                    ELSE '10+'
+                END AS experience,
+                {LOCATION_FILTER} AS by_location,
+                {JOB_TYPE_FILTER} AS by_job_type,
+                {EXPERIENCE_FILTER} AS by_experience,
+                {SKILLS_FILTER} AS by_skills,
+                {CURRENCY_FILTER} AS by_currency,
+                {SALARY_AND_ACTIVE_FILTER} AS by_rest
+            FROM job_listings
+            WHERE {LISTED_FILTER}
+        ),
+        counts AS (
+            SELECT 'location' AS facet, min(location) AS value, COUNT(*) AS count FROM matched
+            WHERE by_job_type AND by_experience AND by_skills AND by_currency AND by_rest
+            GROUP BY lower(location)
+            UNION ALL
+            SELECT 'job_type', job_type, COUNT(*) FROM matched
+            WHERE job_type IS NOT NULL AND by_location AND by_experience AND by_skills AND by_currency AND by_rest
+            GROUP BY job_type
+            UNION ALL
+            SELECT 'skills', min(skill), COUNT(*) FROM matched, unnest(skills) AS skill
+            WHERE by_location AND by_job_type AND by_experience AND by_currency AND by_rest
+            GROUP BY lower(skill)
+            UNION ALL
+            SELECT 'experience', experience, COUNT(*) FROM matched
+            WHERE by_location AND by_job_type AND by_skills AND by_currency AND by_rest
+            GROUP BY experience
+            UNION ALL
+            SELECT 'currency', salary_currency, COUNT(*) FROM matched
+            WHERE salary_currency IS NOT NULL AND by_location AND by_job_type AND by_experience AND by_skills AND by_rest
+            GROUP BY salary_currency
+        )
+        SELECT facet, value, count FROM (
+            SELECT *, ROW_NUMBER() OVER (PARTITION BY facet ORDER BY count DESC, value) AS position
+            FROM counts
+        ) ranked
+        WHERE position <= {FACET_VALUES_KEPT}
+        ORDER BY facet, position
+        "#
+    );
+    let rows = bind_filters(sqlx::query_as::<_, (String, String, i64)>(&query), filters)
+        .fetch_all(db)
+        .await
+        .map_err(ApiError::db("Error counting job facets"))?;
+
+    let mut facets = JobFacets::default();
+    for (facet, value, count) in rows {
+        let counts = match facet.as_str() {
+            "location" => &mut facets.location,
+            "job_type" => &mut facets.job_type,
+            "skills" => &mut facets.skills,
+            "experience" => &mut facets.experience,
+            _ => &mut facets.currency,
+        };
+        counts.push(FacetCount { value, count });
     }
+    Ok(facets)
 }
+
 pub async fn delete_job_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,  // Changed from email to UUID
-) -> HttpResponse {
-    let result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_job(&db, &principal, *id).await?;
+    sqlx::query!(
         "DELETE FROM job_listings WHERE id = $1 RETURNING id",
         id.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "Job deleted successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Job not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error deleting job"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error deleting job"))?
+    .ok_or_else(|| ApiError::not_found("Job not found"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Job deleted successfully"
+    })))
 }
 
 
@@ -129,31 +381,80 @@ pub async fn update_job_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<UpdateJob>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_job(&db, &principal, *id).await?;
+    // A label replaces the whole salary, so one without numbers clears them. Numbers or a currency
+    // sent alone are merged with the stored ones.
+    let salary_changed = json.salary_range.is_some()
+        || json.salary_min.is_some()
+        || json.salary_max.is_some()
+        || json.salary_currency.is_some();
+    let (salary_range, salary) = match (salary_changed, json.salary_range.as_deref()) {
+        (false, _) => (None, None),
+        (true, Some(label)) => resolve_salary(
+            Some(label),
+            json.salary_min,
+            json.salary_max,
+            json.salary_currency.as_deref(),
+        )?,
+        (true, None) => {
+            let stored = sqlx::query!(
+                "SELECT salary_min, salary_max, salary_currency FROM job_listings WHERE id = $1",
+                *id
+            )
+            .fetch_optional(db.get_ref())
+            .await
+            .map_err(ApiError::db("Error updating job"))?
+            .ok_or_else(|| ApiError::not_found("Job not found"))?;
+            let (salary_range, salary) = resolve_salary(
+                None,
+                json.salary_min.or(stored.salary_min),
+                json.salary_max.or(stored.salary_max),
+                json.salary_currency.as_deref().or(stored.salary_currency.as_deref()),
+            )?;
+            if salary.is_none() {
+                return Err(ApiError::bad_request(
+                    "salary_currency needs a salary: send salary_min/salary_max or a salary_range",
+                ));
+            }
+            (salary_range, salary)
+        }
+    };
+    let now = Utc::now().naive_utc();
+    check_limits(json.closes_at.flatten(), json.max_applicants.flatten(), now)?;
+    let status = json.status.or(json.is_active.map(|active| match active {
+        true => JobStatus::Published,
+        false => JobStatus::Closed,
+    }));
+
+    let mut tx = db.begin().await.map_err(ApiError::db("Error updating job"))?;
+    sqlx::query!(
         r#"
         UPDATE job_listings 
 SET 
-    posted_by = COALESCE($1, posted_by),
-    applied_by = COALESCE($2, applied_by),
-    organisation = COALESCE($3, organisation),
-    title = COALESCE($4, title),
-    description = COALESCE($5, description),
-    location = COALESCE($6, location),
-    job_type = COALESCE($7, job_type),
-    skills = COALESCE($8, skills),
-    notice_period = COALESCE($9, notice_period),
-    min_experience = COALESCE($10, min_experience),
-    salary_range = COALESCE($11, salary_range),
-    job_posted_by = COALESCE($12, job_posted_by),
-    is_active = COALESCE($13, is_active),
+    applied_by = COALESCE($1, applied_by),
+    organisation = COALESCE($2, organisation),
+    title = COALESCE($3, title),
+    description = COALESCE($4, description),
+    location = COALESCE($5, location),
+    job_type = COALESCE($6, job_type),
+    skills = COALESCE($7, skills),
+    notice_period = COALESCE($8, notice_period),
+    min_experience = COALESCE($9, min_experience),
+    salary_range = COALESCE($10, salary_range),
+    job_posted_by = COALESCE($11, job_posted_by),
+    closes_at = CASE WHEN $19 THEN $12 ELSE closes_at END,
+    salary_min = CASE WHEN $14 THEN $15 ELSE salary_min END,
+    salary_max = CASE WHEN $14 THEN $16 ELSE salary_max END,
+    salary_currency = CASE WHEN $14 THEN $17 ELSE salary_currency END,
+    max_applicants = CASE WHEN $20 THEN $18 ELSE max_applicants END,
     updated_at = CURRENT_TIMESTAMP
-WHERE id = $14
+WHERE id = $13
 RETURNING id
 
         "#,
-        json.posted_by,
-json.applied_by,
+        json.applied_by,
 json.organisation.as_deref(),
 json.title,
 json.description,
@@ -162,79 +463,91 @@ json.job_type.as_deref(),
 &json.skills as _,
 json.notice_period.as_deref(),
 json.min_experience,
-json.salary_range.as_deref(),
+salary_range.as_deref(),
 json.job_posted_by.as_deref(),
-json.is_active,
-id.into_inner(),
+json.closes_at.flatten(),
+*id,
+salary_changed,
+salary.as_ref().map(|s| s.min),
+salary.as_ref().map(|s| s.max),
+salary.and_then(|s| s.currency) as Option<String>,
+json.max_applicants.flatten(),
+json.closes_at.is_some(),
+json.max_applicants.is_some(),
 
     )
-    .fetch_optional(db.get_ref())
-    .await;
-
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "Job updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Job not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating job"
-            }))
-        }
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(ApiError::db("Error updating job"))?
+    .ok_or_else(|| ApiError::not_found("Job not found"))?;
+    // After the other fields, so a job can be reopened with a new deadline in one request.
+    if let Some(status) = status {
+        job_lifecycle::change_status(&mut tx, *id, status, now).await?;
+    } else if json.closes_at.is_some() || json.max_applicants.is_some() {
+        job_lifecycle::reopen_if_unblocked(&mut tx, *id, now).await?;
     }
+    tx.commit().await.map_err(ApiError::db("Error updating job"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "Job updated successfully"
+    })))
 }
 pub async fn get_job_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
-) -> HttpResponse {
-    let result = sqlx::query_as::<_, Job_listings>(
+    viewer: Option<Principal>,
+) -> Result<HttpResponse, ApiError> {
+    let job = sqlx::query_as::<_, Job_listings>(
         "SELECT * FROM job_listings WHERE id = $1"
     )
     .bind(id.into_inner())
     .fetch_optional(db.get_ref())
-    .await;
-
-    match result {
-        Ok(Some(job)) => HttpResponse::Ok().json(job),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Job not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error retrieving job"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error retrieving job"))?
+    .filter(|job| job.status != JobStatus::Draft.as_str() || can_see_drafts(viewer.as_ref(), job.posted_by))
+    .ok_or_else(|| ApiError::not_found("Job not found"))?;
+
+    Ok(HttpResponse::Ok().json(job))
 }
 
 pub async fn get_jobs_by_posted_id(
     db: web::Data<PgPool>,
     posted_id: web::Path<Uuid>,
-) -> HttpResponse {
-    let result = sqlx::query_as::<_, Job_listings>(
-        "SELECT * FROM job_listings WHERE posted_by = $1 ORDER BY created_at DESC"
+    viewer: Option<Principal>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let with_drafts = can_see_drafts(viewer.as_ref(), *posted_id);
+
+    let jobs = sqlx::query_as::<_, Job_listings>(
+        r#"
+        SELECT * FROM job_listings
+        WHERE posted_by = $1 AND ($5 OR status <> 'draft')
+          AND ($2::timestamp IS NULL OR (created_at, id) < ($2, $3))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $4
+        "#
     )
     .bind(posted_id.into_inner())
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
+    .bind(with_drafts)
     .fetch_all(db.get_ref())
-    .await;
-
-    match result {
-        Ok(jobs) => HttpResponse::Ok().json(jobs),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error retrieving jobs"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error retrieving jobs"))?;
+
+    Ok(HttpResponse::Ok().json(Page::new(jobs, &page, |job| Cursor::new(job.created_at, job.id))))
+}
+
+pub async fn get_recommended_jobs(
+    db: web::Data<PgPool>,
+    user_id: web::Path<Uuid>,
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_own_data(&principal, *user_id)?;
+    let jobs = recommendations::recommended_jobs(db.get_ref(), *user_id, &page).await?;
+    Ok(HttpResponse::Ok().json(jobs))
 }
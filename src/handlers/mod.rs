@@ -10,4 +10,10 @@ pub mod post_likes_handles;
 pub mod follower_handlers;
 pub mod business_accounts_handler;
 pub mod share_handler;
-pub mod google_handler;
+pub mod oauth_handler;
+pub mod auth_handler;
+pub mod feed_handler;
+pub mod search_handler;
+pub mod ats_handler;
+pub mod resume_handler;
+pub mod notification_handler;
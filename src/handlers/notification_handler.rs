@@ -0,0 +1,27 @@
+use actix_web::{web, HttpResponse};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::auth::Principal;
+use crate::error::ApiError;
+use crate::notifications::{self, NotificationFilters};
+use crate::pagination::PageParams;
+
+/// `GET /notifications?unread=true`: the user's notifications, newest first.
+pub async fn get_notifications(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+    web::Query(filters): web::Query<NotificationFilters>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    Ok(HttpResponse::Ok().json(notifications::list(db.get_ref(), user_id, filters.unread, &page).await?))
+}
+
+pub async fn mark_notification_read(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    notification_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    Ok(HttpResponse::Ok().json(notifications::mark_read(db.get_ref(), user_id, *notification_id).await?))
+}
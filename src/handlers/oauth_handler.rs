@@ -0,0 +1,525 @@
+use actix_web::cookie::{time::Duration as CookieDuration, Cookie, SameSite};
+use actix_web::{get, http::header, post, web, HttpRequest, HttpResponse};
+use log::{debug, error};
+use oauth2::{AuthorizationCode, CsrfToken, PkceCodeChallenge, Scope, TokenResponse};
+use rand::Rng;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use url::Url;
+use uuid::Uuid;
+
+// Note: Ensure the `CreateUser` struct in `models/user.rs` also has `profile_pic: Option<String>`
+use crate::models::user::{CreateUser, User}; 
+use crate::oauth::{
+    state_matches, store_authorization_state, take_authorization_state, OAuthProfile, OAuthProvider,
+    OAuthProviders, OAUTH_STATE_COOKIE, OAUTH_STATE_TTL_MINUTES,
+};
+use crate::auth::Principal;
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::session;
+use crate::password::{is_oauth_placeholder, OAUTH_PASSWORD_PLACEHOLDER};
+
+fn unknown_provider(name: &str) -> ApiError {
+    ApiError::not_found(format!("Unknown or disabled login provider: {}", name))
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    /// Frontend page to return to after the login; must be on an allowed origin.
+    redirect_to: Option<String>,
+}
+
+#[get("/auth/{provider}/login")]
+pub async fn oauth_login(
+    provider_name: web::Path<String>,
+    query: web::Query<LoginQuery>,
+    providers: web::Data<OAuthProviders>,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, ApiError> {
+    let Some(provider) = providers.get(&provider_name) else {
+        return Err(unknown_provider(&provider_name));
+    };
+    let (auth_url, state_cookie) =
+        start_authorization(provider.as_ref(), &db, &config, query.redirect_to.as_deref(), None).await?;
+    Ok(HttpResponse::Found()
+        .cookie(state_cookie)
+        .append_header((header::LOCATION, auth_url.to_string()))
+        .finish())
+}
+
+/// Starts linking a provider login to the signed-in user. The frontend sends the browser to
+/// `authorization_url`; the callback then links the provider account whatever its email.
+#[post("/auth/{provider}/link")]
+pub async fn oauth_link(
+    provider_name: web::Path<String>,
+    query: web::Query<LoginQuery>,
+    providers: web::Data<OAuthProviders>,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    let Some(provider) = providers.get(&provider_name) else {
+        return Err(unknown_provider(&provider_name));
+    };
+    let (auth_url, state_cookie) =
+        start_authorization(provider.as_ref(), &db, &config, query.redirect_to.as_deref(), Some(user_id)).await?;
+    Ok(HttpResponse::Ok()
+        .cookie(state_cookie)
+        .json(json!({ "authorization_url": auth_url.to_string() })))
+}
+
+/// The provider URL to send the browser to, and the cookie binding its state to the browser.
+async fn start_authorization(
+    provider: &dyn OAuthProvider,
+    db: &PgPool,
+    config: &Config,
+    redirect_to: Option<&str>,
+    link_user_id: Option<Uuid>,
+) -> Result<(Url, Cookie<'static>), ApiError> {
+    if let Some(redirect_to) = redirect_to
+        && !config.is_allowed_redirect(redirect_to)
+    {
+        return Err(ApiError::bad_request("redirect_to is not an allowed frontend URL"));
+    }
+
+    debug!("Initiating {} OAuth login flow", provider.name());
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let mut request = provider
+        .client()
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(provider.scopes().iter().map(|scope| Scope::new(scope.to_string())));
+    if provider.supports_pkce() {
+        request = request.set_pkce_challenge(pkce_challenge);
+    }
+    let (auth_url, csrf_token) = request.url();
+
+    let stored =
+        store_authorization_state(db, provider.name(), &csrf_token, &pkce_verifier, redirect_to, link_user_id).await;
+    if let Err(e) = stored {
+        error!("Failed to store OAuth state: {}", e);
+        return Err(ApiError::internal("Failed to start login"));
+    }
+
+    // SameSite=Lax so the cookie survives the top-level redirect back from the provider.
+    let state_cookie = Cookie::build(OAUTH_STATE_COOKIE, csrf_token.secret().clone())
+        .path("/auth")
+        .http_only(true)
+        .secure(config.is_production())
+        .same_site(SameSite::Lax)
+        .max_age(CookieDuration::minutes(OAUTH_STATE_TTL_MINUTES.into()))
+        .finish();
+
+    debug!("Generated authorization URL: {}", auth_url);
+    Ok((auth_url, state_cookie))
+}
+
+#[get("/auth/{provider}/callback")]
+pub async fn oauth_callback(
+    req: HttpRequest,
+    provider_name: web::Path<String>,
+    params: web::Query<std::collections::HashMap<String, String>>,
+    providers: web::Data<OAuthProviders>,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse, ApiError> {
+    let Some(provider) = providers.get(&provider_name) else {
+        return Err(unknown_provider(&provider_name));
+    };
+
+    let code = match params.get("code") {
+        Some(c) => AuthorizationCode::new(c.clone()),
+        None => {
+            error!("Missing authorization code in callback");
+            return Err(ApiError::bad_request("Missing authorization code"));
+        }
+    };
+
+    let state = match params.get("state") {
+        Some(s) => s,
+        None => {
+            error!("Missing state in OAuth callback");
+            return Err(ApiError::bad_request("Missing state"));
+        }
+    };
+
+    let cookie_state = req.cookie(OAUTH_STATE_COOKIE);
+    if !state_matches(state, cookie_state.as_ref().map(|c| c.value())) {
+        error!("OAuth state does not match the state cookie");
+        return Err(ApiError::bad_request("Invalid OAuth state"));
+    }
+
+    let authorization = match take_authorization_state(&db, provider.name(), state).await {
+        Ok(Some(authorization)) => authorization,
+        Ok(None) => {
+            error!("Unknown, expired or reused OAuth state");
+            return Err(ApiError::bad_request("Invalid or expired OAuth state"));
+        }
+        Err(e) => {
+            error!("Database error reading OAuth state: {}", e);
+            return Err(ApiError::internal("Failed to validate login"));
+        }
+    };
+
+    let mut exchange = provider.client().exchange_code(code);
+    if provider.supports_pkce() {
+        exchange = exchange.set_pkce_verifier(authorization.pkce_verifier);
+    }
+    let token = match exchange
+        .request_async(oauth2::reqwest::async_http_client)
+        .await
+    {
+        Ok(token) => token,
+        Err(e) => {
+            error!("OAuth token exchange error: {}", e);
+            return Err(ApiError::internal("Token exchange error"));
+        }
+    };
+    let access_token = token.access_token().secret();
+
+    let profile = match provider.fetch_profile(&reqwest::Client::new(), access_token).await {
+        Ok(profile) => profile,
+        Err(e) => {
+            error!("Failed to load {} profile: {}", provider.name(), e);
+            return Err(ApiError::internal("Failed to get user info"));
+        }
+    };
+
+    // `mut` is needed to update the pic later.
+    let mut user = match authorization.link_user_id {
+        Some(user_id) => link_to_user(&db, provider.as_ref(), &profile, user_id).await?,
+        None => resolve_oauth_user(&db, provider.as_ref(), &profile, access_token).await?,
+    };
+
+    // Keep the picture in sync with the provider the account was created with; other
+    // linked providers only fill it in when it is missing.
+    let is_primary = user.provider.as_deref() == Some(provider.name());
+    let new_picture = profile.picture.as_ref().filter(|picture_url| {
+        user.profile_pic.as_deref() != Some(picture_url.as_str()) && (is_primary || user.profile_pic.is_none())
+    });
+    if let Some(picture_url) = new_picture {
+        debug!("Updating profile picture for user ID: {}", user.id);
+        if let Err(e) = update_profile_picture(&db, &user.id, picture_url).await {
+            error!("Failed to update profile picture for user {}: {}", user.id, e);
+        } else {
+            user.profile_pic = Some(picture_url.clone()); // Update local `user` object for the JWT
+        }
+    }
+
+    if let (Some(column), Some(url)) = (provider.profile_url_column(), &profile.profile_url)
+        && let Err(e) = fill_profile_link(&db, &user.id, column, url).await
+    {
+        error!("Failed to store {} profile link for user {}: {}", provider.name(), user.id, e);
+    }
+
+    // The tokens never appear in the redirect; the frontend trades this short-lived code
+    // for them at POST /auth/exchange.
+    let code = match session::create_login_code(&db, user.id).await {
+        Ok(code) => code,
+        Err(e) => {
+            error!("Failed to create login code for user {}: {}", user.id, e);
+            return Err(ApiError::internal("Failed to complete login"));
+        }
+    };
+
+    let target = authorization
+        .redirect_to
+        .unwrap_or_else(|| config.frontend_redirect_url.clone());
+    let frontend_url = match Url::parse(&target) {
+        Ok(mut url) => {
+            url.query_pairs_mut().append_pair("code", &code);
+            url
+        }
+        Err(e) => {
+            error!("Invalid frontend redirect URL {}: {}", target, e);
+            return Err(ApiError::internal("Invalid frontend redirect URL"));
+        }
+    };
+    debug!("Redirecting to frontend: {}", target);
+    let mut expired_state = Cookie::build(OAUTH_STATE_COOKIE, "").path("/auth").finish();
+    expired_state.make_removal();
+
+    Ok(HttpResponse::Found()
+        .cookie(expired_state)
+        .append_header((header::LOCATION, frontend_url.to_string()))
+        .finish())
+}
+
+/// Finds the user behind an OAuth login, linking or creating the account when needed:
+/// 1. an identity already linked for (provider, provider_user_id);
+/// 2. an existing user with the same email, if the provider verified that email and the
+///    account is known to own it (see [`owns_email`]); any other existing account answers 409
+///    and has to link the provider from a signed-in session (`POST /auth/{provider}/link`);
+/// 3. otherwise a new user.
+async fn resolve_oauth_user(
+    db: &PgPool,
+    provider: &dyn OAuthProvider,
+    profile: &OAuthProfile,
+    access_token: &str,
+) -> Result<User, ApiError> {
+    match find_user_by_identity(db, provider.name(), &profile.provider_user_id).await {
+        Ok(Some(user)) => {
+            debug!("Existing {} identity found for user {}", provider.name(), user.id);
+            return Ok(user);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!("Database error fetching identity: {}", e);
+            return Err(ApiError::internal("Failed to load user"));
+        }
+    }
+
+    let user = match get_user_by_email_internal(db, &profile.email).await {
+        Some(user) if !profile.email_verified || !owns_email(db, &user).await? => {
+            debug!("Not linking {} login to unverified account {}", provider.name(), user.id);
+            return Err(ApiError::conflict(
+                "An account with this email already exists. Sign in to it first to link this provider.",
+            ));
+        }
+        Some(user) => {
+            debug!("Linking {} login to existing user: {}", provider.name(), user.email);
+            user
+        }
+        None => {
+            debug!("User not found, creating new user for: {}", &profile.email);
+            let username = match generate_unique_username(db, &profile.email).await {
+                Ok(u) => u,
+                Err(e) => {
+                    error!("Failed to generate a unique username: {}", e);
+                    return Err(ApiError::internal("Failed to create username"));
+                }
+            };
+
+            let new_user_data = CreateUser {
+                first_name: profile.first_name.clone(),
+                last_name: profile.last_name.clone(),
+                username,
+                password: OAUTH_PASSWORD_PLACEHOLDER.to_string(), // Not used for OAuth logins
+                email: profile.email.clone(),
+                provider: Some(provider.name().to_string()),
+                profile_pic: profile.picture.clone(), // Set profile pic on creation
+                provider_user_id: Some(profile.provider_user_id.clone()),
+                access_token: Some(access_token.to_string()),
+                bio: None,
+                interests: None,
+                resume_url: None,
+            };
+
+            match create_user_internal(db, new_user_data).await {
+                Ok(user) => {
+                    debug!("Successfully created new user with ID: {}", user.id);
+                    user
+                },
+                Err(e) => {
+                    error!("Database error while creating user: {}", e);
+                    return Err(ApiError::internal("Failed to create user"));
+                }
+            }
+        }
+    };
+
+    if let Err(e) = link_identity(db, &user.id, provider.name(), profile).await {
+        error!("Failed to link {} identity to user {}: {}", provider.name(), user.id, e);
+        return Err(ApiError::conflict(format!(
+            "This account already has a different {} login linked",
+            provider.name()
+        )));
+    }
+
+    Ok(user)
+}
+
+/// Links the provider login to the user who started `POST /auth/{provider}/link`.
+async fn link_to_user(
+    db: &PgPool,
+    provider: &dyn OAuthProvider,
+    profile: &OAuthProfile,
+    user_id: Uuid,
+) -> Result<User, ApiError> {
+    match find_user_by_identity(db, provider.name(), &profile.provider_user_id).await {
+        Ok(Some(user)) if user.id == user_id => return Ok(user),
+        Ok(Some(_)) => {
+            return Err(ApiError::conflict(format!(
+                "This {} login is already linked to another account",
+                provider.name()
+            )));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            error!("Database error fetching identity: {}", e);
+            return Err(ApiError::internal("Failed to load user"));
+        }
+    }
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db("Failed to load user"))?
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+    if let Err(e) = link_identity(db, &user.id, provider.name(), profile).await {
+        error!("Failed to link {} identity to user {}: {}", provider.name(), user.id, e);
+        return Err(ApiError::conflict(format!(
+            "This account already has a different {} login linked",
+            provider.name()
+        )));
+    }
+    Ok(user)
+}
+
+/// Whether the account is known to own its email, so a provider login with the same verified
+/// email may be linked to it automatically: accounts created through an OAuth login, and
+/// accounts with a linked login whose provider reported that email. Accounts registered with
+/// a password never had their email checked.
+async fn owns_email(db: &PgPool, user: &User) -> Result<bool, ApiError> {
+    if is_oauth_placeholder(&user.password) {
+        return Ok(true);
+    }
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM user_identities WHERE user_id = $1 AND LOWER(email) = LOWER($2)
+        ) AS "exists!"
+        "#,
+        user.id,
+        user.email
+    )
+    .fetch_one(db)
+    .await
+    .map_err(ApiError::db("Failed to load user"))
+}
+
+// --- HELPER FUNCTIONS (WITH IMPROVEMENTS) ---
+
+async fn get_user_by_email_internal(db: &PgPool, email: &str) -> Option<User> {
+    sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = $1")
+        .bind(email.to_lowercase())
+        .fetch_optional(db)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Database error fetching user by email: {}", e);
+            None
+        })
+}
+
+// FIX #4: `create_user` now includes `profile_pic` for an efficient single-query creation.
+async fn create_user_internal(db: &PgPool, user_data: CreateUser) -> Result<User, sqlx::Error> {
+    sqlx::query_as::<_, User>(
+        r#"
+        INSERT INTO users (
+            id, first_name, last_name, username, password, email, provider, 
+            provider_user_id, access_token, profile_pic
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING *
+        "#
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_data.first_name)
+    .bind(user_data.last_name)
+    .bind(user_data.username)
+    .bind(user_data.password)
+    .bind(user_data.email)
+    .bind(user_data.provider)
+    .bind(user_data.provider_user_id)
+    .bind(user_data.access_token)
+    .bind(user_data.profile_pic)
+    .fetch_one(db)
+    .await
+}
+
+async fn update_profile_picture(db: &PgPool, user_id: &Uuid, picture_url: &str) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE users SET profile_pic = $1, updated_at = NOW() WHERE id = $2",
+        picture_url,
+        user_id
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+async fn generate_unique_username(db: &PgPool, email: &str) -> Result<String, sqlx::Error> {
+    let base = email.split('@').next().unwrap_or("user")
+        .to_string()
+        .replace(|c: char| !c.is_alphanumeric(), ""); // Sanitize base username
+
+    let mut username = base.clone();
+    let mut i = 0;
+    loop {
+        let exists: bool = sqlx::query_scalar!("SELECT EXISTS(SELECT 1 FROM users WHERE username = $1)", username)
+            .fetch_one(db).await?.unwrap_or(false);
+            
+        if !exists { return Ok(username); }
+
+        i += 1;
+        if i > 10 { // Prevent infinite loops
+             return Err(sqlx::Error::RowNotFound); // Or a custom error
+        }
+
+        // Append a random 4-digit number if the base username is taken
+        let suffix: u16 = rand::thread_rng().gen_range(1000..=9999);
+        username = format!("{}{}", if base.len() > 25 { &base[..25] } else { &base }, suffix);
+    }
+}
+
+async fn find_user_by_identity(
+    db: &PgPool,
+    provider: &str,
+    provider_user_id: &str,
+) -> Result<Option<User>, sqlx::Error> {
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE user_identities SET last_login_at = NOW()
+        FROM users
+        WHERE user_identities.user_id = users.id
+          AND user_identities.provider = $1
+          AND user_identities.provider_user_id = $2
+        RETURNING users.*
+        "#
+    )
+    .bind(provider)
+    .bind(provider_user_id)
+    .fetch_optional(db)
+    .await?;
+    Ok(user)
+}
+
+async fn link_identity(
+    db: &PgPool,
+    user_id: &Uuid,
+    provider: &str,
+    profile: &OAuthProfile,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_identities (id, user_id, provider, provider_user_id, email)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        provider,
+        profile.provider_user_id,
+        profile.email
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Stores the provider profile link (e.g. `users.github`) unless the user already set one.
+/// `column` comes from [`OAuthProvider::profile_url_column`], never from the request.
+async fn fill_profile_link(db: &PgPool, user_id: &Uuid, column: &str, url: &str) -> Result<(), sqlx::Error> {
+    let query = format!(
+        "UPDATE users SET {column} = $1, updated_at = NOW() WHERE id = $2 AND ({column} IS NULL OR {column} = '')"
+    );
+    sqlx::query(&query)
+        .bind(url)
+        .bind(user_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
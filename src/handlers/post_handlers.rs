@@ -1,19 +1,23 @@
-use actix_web::{web, HttpResponse, HttpRequest, Responder};
-use actix_web::{web::BytesMut, Error};
+use actix_web::{web, HttpResponse, HttpRequest};
+use actix_web::web::BytesMut;
 use futures::stream::StreamExt;
 use std::io::Write;
 use std::path::Path;
 use chrono::Utc;
 use uuid::Uuid;
 use sqlx::PgPool;
-use crate::models::posts::{Post, CreatePost, UpdatePost, PostWithRank, Pagination, MediaType};
+use crate::models::posts::{Post, CreatePost, UpdatePost, PostWithRank, PostSort, MediaType};
+use crate::auth::Principal;
+use crate::authz::{authorize_post, Action};
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
+use crate::config::{parse_window_hours, Config};
+use crate::rankings;
 use serde_json::json;
 use serde::{Serialize, Deserialize};
 use chrono::NaiveDateTime;
 use infer;
 
-use std::collections::HashMap;
-
 fn get_lang(req: &HttpRequest) -> String {
     req.headers()
         .get("Accept-Language")
@@ -32,11 +36,13 @@ fn get_lang(req: &HttpRequest) -> String {
 pub async fn create_post(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<CreatePost>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
-    let post_result = sqlx::query_as::<_, Post>(
+    let author_id = principal.user_id()?;
+    let post = sqlx::query_as::<_, Post>(
          r#"
         INSERT INTO posts (id, community_id, author_id, title, content, post_type, is_active, tags, likes)
         VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, true), $8, $9)
@@ -45,7 +51,7 @@ pub async fn create_post(
     )
     .bind(Uuid::new_v4())
     .bind(&json.community_id)
-    .bind(&json.author_id)
+    .bind(author_id)
     .bind(&json.title)
     .bind(&json.content)
     .bind(&json.post_type)
@@ -53,41 +59,31 @@ pub async fn create_post(
     .bind(&json.tags)
      .bind(json.likes.unwrap_or(0))
     .fetch_one(db.get_ref())
-    .await;
-
-    match post_result {
-        Ok(post) => {
-            // Save media if exists
-            if let Some(media) = &json.media {
-                for media_item in media {
-                    sqlx::query!(
-                        r#"
-                        INSERT INTO post_media (id, post_id, media_type, url)
-                        VALUES ($1, $2, $3, $4)
-                        "#,
-                        Uuid::new_v4(),
-                        post.id,
-                        media_item.media_type as MediaType,
-                        &media_item.url
-                    )
-                    .execute(db.get_ref())
-                    .await
-                    .map_err(|e| {
-                        eprintln!("Failed to save media: {:?}", e);
-                    }).ok();
-                }
-            }
-            
-            HttpResponse::Ok().json(post)
-        },
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error creating post"
-            }))
+    .await
+    .map_err(ApiError::db("Error creating post"))?;
+
+    // Save media if exists
+    if let Some(media) = &json.media {
+        for media_item in media {
+            sqlx::query!(
+                r#"
+                INSERT INTO post_media (id, post_id, media_type, url)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                Uuid::new_v4(),
+                post.id,
+                media_item.media_type as MediaType,
+                &media_item.url
+            )
+            .execute(db.get_ref())
+            .await
+            .map_err(|e| {
+                eprintln!("Failed to save media: {:?}", e);
+            }).ok();
         }
     }
+
+    Ok(HttpResponse::Ok().json(post))
 }
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
@@ -104,15 +100,21 @@ pub struct PostWithLikeCount {
     pub likes: i32,
     pub like_count: i64,
     pub media_urls: Vec<String>,  // Changed to i64
+    // Precomputed by the rankings job; only selected by `get_posts`.
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub hot_score: i64,
+    #[serde(skip)]
+    #[sqlx(default)]
+    pub trending_score: i64,
 }
 
 pub async fn get_posts(
     db: web::Data<PgPool>,
-    web::Query(pagination): web::Query<Pagination>,
-) -> HttpResponse {
-    let limit = pagination.limit.unwrap_or(5);
-    let offset = pagination.offset.unwrap_or(0);
-    let sort = pagination.sort.as_deref().unwrap_or("latest");
+    web::Query(page): web::Query<PageParams>,
+    web::Query(sort): web::Query<PostSort>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
 
     let base_query = r#"
         SELECT 
@@ -133,105 +135,198 @@ pub async fn get_posts(
                 FROM post_media pm 
                 WHERE pm.post_id = p.id 
                 ORDER BY pm.created_at
-            ) as media_urls
+            ) as media_urls,
+            COALESCE(ps.hot_score, 0) as hot_score,
+            COALESCE(ps.trending_score, 0) as trending_score
         FROM posts p
+        LEFT JOIN post_scores ps ON ps.post_id = p.id
     "#;
 
-    let order_clause = match sort {
-        "top" => "ORDER BY like_count DESC, p.created_at DESC",
-        "latest" => "ORDER BY p.created_at DESC",
-        _ => "ORDER BY p.created_at DESC",
+    // "top" ranks by all-time likes; "hot" and "trending" by the scores of the rankings job
+    // (posts without recent engagement follow, newest first). Ranked sorts page on
+    // (rank, created_at, id), "latest" on (created_at, id).
+    let rank_column = match sort.sort.as_deref() {
+        Some("top") => Some("like_count"),
+        Some("hot") => Some("hot_score"),
+        Some("trending") => Some("trending_score"),
+        _ => None,
+    };
+    let (keyset_clause, order_clause) = match rank_column {
+        Some(rank) => (
+            format!("(p.{rank}, p.created_at, p.id) < ($3, $1, $2)"),
+            format!("ORDER BY p.{rank} DESC, p.created_at DESC, p.id DESC"),
+        ),
+        None => (
+            "(p.created_at, p.id) < ($1, $2)".to_string(),
+            "ORDER BY p.created_at DESC, p.id DESC".to_string(),
+        ),
+    };
+    let rank = match after {
+        Some(cursor) if rank_column.is_some() => {
+            Some(cursor.rank.ok_or_else(|| ApiError::bad_request("Invalid cursor"))?)
+        }
+        _ => None,
     };
 
-    let query_str = format!("{} {}", base_query, order_clause);
-    let full_query = format!("{} LIMIT $1 OFFSET $2", query_str);
+    let full_query = format!(
+        "SELECT * FROM ({}) p WHERE ($1::timestamp IS NULL OR {}) {} LIMIT $4",
+        base_query, keyset_clause, order_clause
+    );
+
+    // Hot and trending scores are rewritten by every refresh, so their cursors carry the refresh
+    // they were ranked by (`as_of`) and stop working once another one has run. One snapshot for
+    // both reads, so a refresh cannot slip in between them.
+    let mut tx = db.begin().await.map_err(ApiError::db("Error retrieving posts"))?;
+    let scored = matches!(rank_column, Some("hot_score" | "trending_score"));
+    let ranked_at = if scored {
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await
+            .map_err(ApiError::db("Error retrieving posts"))?;
+        let ranked_at = sqlx::query_scalar!("SELECT MAX(computed_at) FROM post_scores")
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(ApiError::db("Error retrieving posts"))?;
+        if after.is_some_and(|cursor| cursor.as_of != ranked_at) {
+            return Err(ApiError::conflict(
+                "The ranking was refreshed since the first page; load it again without a cursor",
+            ));
+        }
+        ranked_at
+    } else {
+        None
+    };
 
     let posts = sqlx::query_as::<_, PostWithLikeCount>(&full_query)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(db.get_ref())
-        .await;
-
-    match posts {
-        Ok(posts) => HttpResponse::Ok().json(posts),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving posts")
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(rank)
+        .bind(page.fetch_limit())
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(ApiError::db("Error retrieving posts"))?;
+    tx.commit().await.map_err(ApiError::db("Error retrieving posts"))?;
+
+    Ok(HttpResponse::Ok().json(Page::new(posts, &page, |post| match rank_column {
+        Some("hot_score") => Cursor { as_of: ranked_at, ..Cursor::ranked(post.hot_score, post.created_at, post.id) },
+        Some("trending_score") => {
+            Cursor { as_of: ranked_at, ..Cursor::ranked(post.trending_score, post.created_at, post.id) }
         }
+        Some(_) => Cursor::ranked(post.like_count, post.created_at, post.id),
+        None => Cursor::new(post.created_at, post.id),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct TrendingTagsQuery {
+    pub window: Option<String>,
+    pub community_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct TrendingTag {
+    pub tag: String,
+    pub score: f64,
+    pub post_count: i32,
+}
+
+/// Trending tags of one window (`?window=24h`, default the first configured window), globally
+/// or in one community, as last computed by the rankings job.
+pub async fn get_trending_tags(
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    web::Query(query): web::Query<TrendingTagsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let windows = &config.trending_tag_windows;
+    let hours = match query.window.as_deref() {
+        Some(window) => parse_window_hours(window).filter(|hours| windows.contains(hours)),
+        None => windows.first().copied(),
     }
+    .ok_or_else(|| {
+        let allowed: Vec<String> = windows.iter().map(|hours| rankings::format_window(*hours)).collect();
+        ApiError::bad_request(format!("Unknown window; use one of: {}", allowed.join(", ")))
+    })?;
+    let limit = query.limit.unwrap_or(20).clamp(1, 50);
+
+    let tags = sqlx::query_as::<_, TrendingTag>(
+        r#"
+        SELECT tag, score, post_count FROM trending_tags
+        WHERE window_hours = $1 AND community_id IS NOT DISTINCT FROM $2
+        ORDER BY score DESC, tag
+        LIMIT $3
+        "#
+    )
+    .bind(hours)
+    .bind(query.community_id)
+    .bind(limit)
+    .fetch_all(db.get_ref())
+    .await
+    .map_err(ApiError::db("Error retrieving trending tags"))?;
+
+    let computed_at = sqlx::query_scalar!("SELECT MAX(computed_at) FROM trending_tags")
+        .fetch_one(db.get_ref())
+        .await
+        .map_err(ApiError::db("Error retrieving trending tags"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "window": rankings::format_window(hours),
+        "community_id": query.community_id,
+        "computed_at": computed_at,
+        "tags": tags,
+    })))
 }
 
 pub async fn delete_post_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
-) -> HttpResponse {
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_post(&db, &principal, *id, Action::Delete).await?;
     let post_id = id.into_inner();
 
     // First, delete associated media
-    let media_delete_result = sqlx::query!(
+    sqlx::query!(
         "DELETE FROM post_media WHERE post_id = $1",
         post_id
     )
     .execute(db.get_ref())
-    .await;
-
-    if let Err(e) = media_delete_result {
-        eprintln!("Database error deleting media: {:?}", e);
-        return HttpResponse::InternalServerError().json(json!({
-            "status": "error",
-            "message": "Error deleting post media"
-        }));
-    }
+    .await
+    .map_err(ApiError::db("Error deleting post media"))?;
 
     // Next, delete from shared_posts
-    let shared_delete_result = sqlx::query!(
+    sqlx::query!(
         "DELETE FROM shared_posts WHERE post_id = $1",
         post_id
     )
     .execute(db.get_ref())
-    .await;
-
-    if let Err(e) = shared_delete_result {
-        eprintln!("Database error deleting shared_posts: {:?}", e);
-        return HttpResponse::InternalServerError().json(json!({
-            "status": "error",
-            "message": "Error deleting shared posts"
-        }));
-    }
+    .await
+    .map_err(ApiError::db("Error deleting shared posts"))?;
 
     // Then, delete the post itself
-    let result = sqlx::query!(
+    sqlx::query!(
         "DELETE FROM posts WHERE id = $1 RETURNING id",
         post_id
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "post deleted successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "post not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error deleting post"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error deleting post"))?
+    .ok_or_else(|| ApiError::not_found("post not found"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "post deleted successfully"
+    })))
 }
 
 pub async fn update_post_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<UpdatePost>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_post(&db, &principal, *id, Action::Edit).await?;
+    sqlx::query!(
           r#"
         UPDATE posts 
         SET 
@@ -253,31 +348,20 @@ pub async fn update_post_by_id(
         id.into_inner(),
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "POST updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "post not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating post"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error updating post"))?
+    .ok_or_else(|| ApiError::not_found("post not found"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "POST updated successfully"
+    })))
 }
 
 pub async fn get_post_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let post = sqlx::query_as::<_, PostWithLikeCount>(
          r#"
         SELECT 
@@ -305,28 +389,19 @@ pub async fn get_post_by_id(
     )
     .bind(id.into_inner())
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error fetching post"))?
+    .ok_or_else(|| ApiError::not_found("Post not found"))?;
 
-    match post {
-        Ok(Some(post)) => HttpResponse::Ok().json(post),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "Post not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error fetching post"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(post))
 }
 
 pub async fn get_posts_by_author_id(
     db: web::Data<PgPool>,
     author_id: web::Path<Uuid>,
-) -> HttpResponse {
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
     let posts = sqlx::query_as::<_, PostWithLikeCount>(
         r#"
         SELECT 
@@ -350,73 +425,71 @@ pub async fn get_posts_by_author_id(
             ) as media_urls
         FROM posts p
         WHERE author_id = $1 
-        ORDER BY created_at DESC
+          AND ($2::timestamp IS NULL OR (p.created_at, p.id) < ($2, $3))
+        ORDER BY p.created_at DESC, p.id DESC
+        LIMIT $4
         "#
     )
     .bind(author_id.into_inner())
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving posts"))?;
 
-    match posts {
-        Ok(posts) => {
-            if posts.is_empty() {
-                HttpResponse::NotFound().json(json!({
-                    "status": "error",
-                    "message": "No posts found for this author"
-                }))
-            } else {
-                HttpResponse::Ok().json(posts)
-            }
-        },
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error retrieving posts"
-            }))
-        }
+    if posts.is_empty() && after.is_none() {
+        return Err(ApiError::not_found("No posts found for this author"));
     }
+    Ok(HttpResponse::Ok().json(Page::new(posts, &page, |post| Cursor::new(post.created_at, post.id))))
 }
 
 pub async fn get_tags_by_post_id(
     db: web::Data<PgPool>,
-) -> HttpResponse {
-    let result = sqlx::query!(
+) -> Result<HttpResponse, ApiError> {
+    let records = sqlx::query!(
         "SELECT tags FROM posts"
     )
     .fetch_all(db.get_ref())
-    .await;
-
-    match result {
-        Ok(records) => {
-            let tags: Vec<serde_json::Value> = records
-                .into_iter()
-                .filter_map(|record| record.tags)
-                .map(|tag_vec| json!(tag_vec))
-                .collect();
-            HttpResponse::Ok().json(json!({ "tags": tags }))
-        },
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error retrieving tags"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error retrieving tags"))?;
+
+    let tags: Vec<serde_json::Value> = records
+        .into_iter()
+        .filter_map(|record| record.tags)
+        .map(|tag_vec| json!(tag_vec))
+        .collect();
+    Ok(HttpResponse::Ok().json(json!({ "tags": tags })))
 }
 
 pub async fn get_posts_by_user_interest(
     pool: web::Data<PgPool>,
     user_id: web::Path<Uuid>,
-    web::Query(mut params): web::Query<HashMap<String, String>>,
-) -> impl Responder {
-    let sort_by = params.remove("sort").unwrap_or_else(|| "relevant".to_string());
-    
-    let order_clause = match sort_by.as_str() {
-        "latest" => "ORDER BY p.created_at DESC",
-        "top" => "ORDER BY like_count DESC, p.created_at DESC",
-        _ => "ORDER BY match_count DESC, p.created_at DESC",
+    web::Query(page): web::Query<PageParams>,
+    web::Query(sort): web::Query<PostSort>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let sort_by = sort.sort.as_deref().unwrap_or("relevant");
+
+    // Every order ends in (created_at, id) so the cursor has a unique position to resume from.
+    let (rank_column, keyset_clause, order_clause) = match sort_by {
+        "latest" => (None, "(p.created_at, p.id) < ($2, $3)", "ORDER BY p.created_at DESC, p.id DESC"),
+        "top" => (
+            Some("like_count"),
+            "(p.like_count, p.created_at, p.id) < ($4, $2, $3)",
+            "ORDER BY p.like_count DESC, p.created_at DESC, p.id DESC",
+        ),
+        _ => (
+            Some("match_count"),
+            "(p.match_count, p.created_at, p.id) < ($4, $2, $3)",
+            "ORDER BY p.match_count DESC, p.created_at DESC, p.id DESC",
+        ),
+    };
+    let rank = match after {
+        Some(cursor) if rank_column.is_some() => {
+            Some(cursor.rank.ok_or_else(|| ApiError::bad_request("Invalid cursor"))?)
+        }
+        _ => None,
     };
 
     let interests_result = sqlx::query_scalar!(
@@ -431,17 +504,10 @@ pub async fn get_posts_by_user_interest(
         Ok(None) | Err(_) => vec![],
     };
 
-    let limit = params.get("limit")
-        .and_then(|l| l.parse::<i64>().ok())
-        .unwrap_or(5);
-        
-    let offset = params.get("offset")
-        .and_then(|o| o.parse::<i64>().ok())
-        .unwrap_or(0);
-
     let query_str = format!(
         
       r#"
+    SELECT * FROM (
     SELECT 
         p.id,
         p.community_id,
@@ -465,26 +531,30 @@ pub async fn get_posts_by_user_interest(
             ORDER BY pm.created_at
         ) as media_urls 
     FROM posts p
+    ) p
+    WHERE ($2::timestamp IS NULL OR {})
     {}
-    LIMIT $2 OFFSET $3
+    LIMIT $5
     "#,
+    keyset_clause,
     order_clause
 );
 
-    let posts_result = sqlx::query_as::<_, PostWithRank>(&query_str)
+    let posts = sqlx::query_as::<_, PostWithRank>(&query_str)
         .bind(&interests)
-        .bind(limit)
-        .bind(offset)
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(rank)
+        .bind(page.fetch_limit())
         .fetch_all(pool.get_ref())
-        .await;
-
-    match posts_result {
-        Ok(posts) => HttpResponse::Ok().json(posts),
-        Err(e) => {
-            eprintln!("❌ Error fetching posts: {}", e);
-            HttpResponse::InternalServerError().body("Failed to fetch posts")
-        }
-    }
+        .await
+        .map_err(ApiError::db("Failed to fetch posts"))?;
+
+    Ok(HttpResponse::Ok().json(Page::new(posts, &page, |post| match rank_column {
+        Some("like_count") => Cursor::ranked(post.like_count, post.created_at, post.id),
+        Some(_) => Cursor::ranked(post.match_count.into(), post.created_at, post.id),
+        None => Cursor::new(post.created_at, post.id),
+    })))
 }
 
 #[derive(Serialize)]
@@ -494,24 +564,21 @@ pub struct UploadResponse {
 
 pub async fn upload_media(
     mut payload: actix_web::web::Payload,
-) -> Result<HttpResponse, Error> {
+) -> Result<HttpResponse, ApiError> {
     // Create uploads directory if it doesn't exist
     let upload_dir = Path::new("uploads");
     if !upload_dir.exists() {
-        std::fs::create_dir_all(upload_dir)?;
+        std::fs::create_dir_all(upload_dir).map_err(upload_error)?;
     }
 
     let mut bytes = BytesMut::new();
     while let Some(chunk) = payload.next().await {
-        let chunk = chunk?;
+        let chunk = chunk.map_err(|e| ApiError::bad_request(format!("Error reading upload: {}", e)))?;
         bytes.extend_from_slice(&chunk);
     }
 
     // Get the file type using infer
-    let kind = match infer::get(&bytes) {
-        Some(k) => k,
-        None => return Err(actix_web::error::ErrorBadRequest("Unrecognized file type")),
-    };
+    let kind = infer::get(&bytes).ok_or_else(|| ApiError::bad_request("Unrecognized file type"))?;
 
     // Define allowed MIME types
     let allowed_mime_types = vec![
@@ -522,7 +589,7 @@ pub async fn upload_media(
     ];
 
     if !allowed_mime_types.contains(&kind.mime_type()) {
-        return Err(actix_web::error::ErrorBadRequest("Unsupported file type"));
+        return Err(ApiError::bad_request("Unsupported file type"));
     }
 
     let extension = kind.extension();
@@ -533,10 +600,15 @@ pub async fn upload_media(
     let file_path = upload_dir.join(&filename);
 
     // Save file
-    let mut file = std::fs::File::create(&file_path)?;
-    file.write_all(&bytes)?;
+    let mut file = std::fs::File::create(&file_path).map_err(upload_error)?;
+    file.write_all(&bytes).map_err(upload_error)?;
 
     let media_url = format!("/uploads/{}", filename);
 
     Ok(HttpResponse::Ok().json(UploadResponse { url: media_url }))
 }
+
+fn upload_error(e: std::io::Error) -> ApiError {
+    eprintln!("Error saving upload: {:?}", e);
+    ApiError::internal("Error saving uploaded file")
+}
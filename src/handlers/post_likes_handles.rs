@@ -1,8 +1,11 @@
-use actix_web::{web, HttpResponse, HttpRequest, Responder};
+use actix_web::{web, HttpResponse, HttpRequest};
 use serde::Deserialize;
 use uuid::Uuid;
 use sqlx::PgPool;
 use crate::models::post_likes::{Post_Likes, Create_Post_Likes, Update_Post_Likes};
+use crate::auth::{Principal, PrincipalKind};
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 use serde_json::json;
 use chrono::NaiveDateTime;
 use serde::Serialize;
@@ -28,57 +31,58 @@ fn get_lang(req: &HttpRequest) -> String {
 pub async fn create_post_like(
     req: HttpRequest,
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<Create_Post_Likes>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
-    let user = sqlx::query_as::<_, Post_Likes>(
+    let user_id = principal.user_id()?;
+    let like = sqlx::query_as::<_, Post_Likes>(
         r#"
-        INSERT INTO post_likes (id, post_id, user_id, is_liked)
-        VALUES ($1, $2, $3, COALESCE($4, true))
+        INSERT INTO post_likes (id, post_id, user_id, user_type, is_liked)
+        VALUES ($1, $2, $3, 'user', COALESCE($4, true))
         RETURNING *
         "#
     )
     .bind(Uuid::new_v4())
     .bind(&json.post_id)
-    .bind(&json.user_id)
+    .bind(user_id)
     .bind(&json.is_liked)
 
     .fetch_one(db.get_ref())
-    .await;
-
-    match user {
-        Ok(c) => HttpResponse::Ok().json(c),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error in liking"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error in liking"))?;
+
+    Ok(HttpResponse::Ok().json(like))
 }
-pub async fn view_likes(db: web::Data<PgPool>) -> HttpResponse {
-    let users = sqlx::query_as::<_, Post_Likes>(
-        "SELECT * FROM post_likes"
+pub async fn view_likes(
+    db: web::Data<PgPool>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let likes = sqlx::query_as::<_, Post_Likes>(
+        r#"
+        SELECT * FROM post_likes
+        WHERE $1::timestamp IS NULL OR (liked_at, id) < ($1, $2)
+        ORDER BY liked_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving like"))?;
 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving like")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(likes, &page, |l| Cursor::new(l.liked_at, l.id))))
 }
 pub async fn update_post_likes_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,
     json: web::Json<Update_Post_Likes>,
-) -> HttpResponse {
-    let query_result = sqlx::query!(
+) -> Result<HttpResponse, ApiError> {
+    sqlx::query!(
         r#"
         UPDATE post_likes
         SET 
@@ -90,25 +94,14 @@ pub async fn update_post_likes_by_id(
         id.into_inner(),
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match query_result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "like updated successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "like was not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error updating post like"
-            }))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error updating post like"))?
+    .ok_or_else(|| ApiError::not_found("like was not found"))?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "like updated successfully"
+    })))
 }
 
 #[derive(Serialize, sqlx::FromRow)]
@@ -123,7 +116,7 @@ pub struct PostLikeInfo {
 pub async fn get_post_likes(
     pool: web::Data<PgPool>,
     post_id: web::Path<Uuid>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let post_id = post_id.into_inner();
 
     let query = r#"
@@ -148,24 +141,18 @@ pub async fn get_post_likes(
         WHERE pl.post_id = $1 AND pl.user_type = 'business'
     "#;
 
-    match sqlx::query_as::<_, PostLikeInfo>(query)
+    let likes = sqlx::query_as::<_, PostLikeInfo>(query)
         .bind(post_id)
         .fetch_all(pool.get_ref())
         .await
-    {
-        Ok(likes) => HttpResponse::Ok().json(likes),
-        Err(err) => {
-            eprintln!("DB error: {:?}", err);
-            HttpResponse::InternalServerError().body("Failed to fetch likes")
-        }
-    }
+        .map_err(ApiError::db("Failed to fetch likes"))?;
+
+    Ok(HttpResponse::Ok().json(likes))
 }
 
 #[derive(Deserialize)]
 pub struct LikePayload {
     pub post_id: Uuid,
-    pub user_id: Option<Uuid>,
-    pub business_id: Option<Uuid>,
 }
 
 #[derive(Serialize, sqlx::FromRow)]
@@ -182,9 +169,9 @@ pub struct LikeQuery {
 pub async fn get_liked_posts_by_user(
     db: web::Data<PgPool>,
     query: web::Query<LikeQuery>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     if let Some(uid) = query.user_id {
-        let result = sqlx::query_as::<_, LikedPostId>(
+        let posts = sqlx::query_as::<_, LikedPostId>(
             r#"
             SELECT post_id FROM post_likes
             WHERE user_id = $1 AND is_liked = true
@@ -192,17 +179,12 @@ pub async fn get_liked_posts_by_user(
         )
         .bind(uid)  // ✅ bind only if it's a concrete Uuid
         .fetch_all(db.get_ref())
-        .await;
+        .await
+        .map_err(ApiError::db("Error fetching liked posts (user)"))?;
 
-        return match result {
-            Ok(posts) => HttpResponse::Ok().json(posts),
-            Err(e) => {
-                eprintln!("DB error (user): {:?}", e);
-                HttpResponse::InternalServerError().body("Error fetching liked posts (user)")
-            }
-        };
+        return Ok(HttpResponse::Ok().json(posts));
     } else if let Some(bid) = query.business_id {
-        let result = sqlx::query_as::<_, LikedPostId>(
+        let posts = sqlx::query_as::<_, LikedPostId>(
             r#"
             SELECT post_id FROM post_likes
             WHERE business_id = $1 AND is_liked = true
@@ -210,18 +192,13 @@ pub async fn get_liked_posts_by_user(
         )
         .bind(bid)
         .fetch_all(db.get_ref())
-        .await;
+        .await
+        .map_err(ApiError::db("Error fetching liked posts (business)"))?;
 
-        return match result {
-            Ok(posts) => HttpResponse::Ok().json(posts),
-            Err(e) => {
-                eprintln!("DB error (business): {:?}", e);
-                HttpResponse::InternalServerError().body("Error fetching liked posts (business)")
-            }
-        };
+        return Ok(HttpResponse::Ok().json(posts));
     }
 
-    HttpResponse::BadRequest().body("Missing user_id or business_id")
+    Err(ApiError::bad_request("Missing user_id or business_id"))
 }
 
   
@@ -230,41 +207,35 @@ pub async fn get_liked_posts_by_user(
 pub async fn toggle_like_post(
     payload: web::Json<LikePayload>,
     db: web::Data<PgPool>,
-) -> impl Responder {
-    let LikePayload {
-        post_id,
-        user_id,
-        business_id,
-    } = payload.into_inner();
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    let LikePayload { post_id } = payload.into_inner();
 
-    if user_id.is_none() && business_id.is_none() {
-        return HttpResponse::BadRequest().body("Missing user or business ID");
-    }
-
-    // Build SQL and binding based on which ID is present
-    let (check_sql, id_value) = if let Some(uid) = user_id {
-        (
+    // Users like through user_id, businesses through business_id
+    let (check_sql, user_id, business_id) = match principal.kind {
+        PrincipalKind::User => (
             "SELECT id, is_liked FROM post_likes WHERE post_id = $1 AND user_id = $2",
-            uid,
-        )
-    } else if let Some(bid) = business_id {
-        (
+            Some(principal.id),
+            None,
+        ),
+        PrincipalKind::Business => (
             "SELECT id, is_liked FROM post_likes WHERE post_id = $1 AND business_id = $2",
-            bid,
-        )
-    } else {
-        unreachable!()
+            None,
+            Some(principal.id),
+        ),
     };
+    let id_value = principal.id;
 
     // Check if like exists
     let existing = sqlx::query(check_sql)
         .bind(post_id)
         .bind(id_value)
         .fetch_optional(db.get_ref())
-        .await;
+        .await
+        .map_err(ApiError::db("Error updating like"))?;
 
     match existing {
-        Ok(Some(row)) => {
+        Some(row) => {
             let is_liked: bool = row.try_get("is_liked").unwrap_or(false);
             let like_id: Uuid = row.try_get("id").unwrap();
 
@@ -277,25 +248,22 @@ pub async fn toggle_like_post(
             .await
             .ok();
         }
-        Ok(None) => {
+        None => {
             sqlx::query!(
                 r#"
-                INSERT INTO post_likes (id, post_id, user_id, business_id, is_liked)
-                VALUES ($1, $2, $3, $4, true)
+                INSERT INTO post_likes (id, post_id, user_id, business_id, user_type, is_liked)
+                VALUES ($1, $2, $3, $4, $5, true)
                 "#,
                 Uuid::new_v4(),
                 post_id,
                 user_id,
-                business_id
+                business_id,
+                principal.kind.as_str()
             )
             .execute(db.get_ref())
             .await
             .ok();
         }
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            return HttpResponse::InternalServerError().finish();
-        }
     }
 
     // Count current likes for the post
@@ -307,7 +275,7 @@ pub async fn toggle_like_post(
     .await
     .unwrap_or(Some(0));
 
-    HttpResponse::Ok().json(count.unwrap_or(0))
+    Ok(HttpResponse::Ok().json(count.unwrap_or(0)))
 }
 
 
@@ -320,24 +288,19 @@ pub struct LikeCountResponse {
 pub async fn get_like_count_for_post(
     db: web::Data<PgPool>,
     path: web::Path<Uuid>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
     let post_id = path.into_inner();
 
-    let result = sqlx::query_scalar!(
+    let count = sqlx::query_scalar!(
         "SELECT COUNT(*) FROM post_likes WHERE post_id = $1 AND is_liked = true",
         post_id
     )
     .fetch_one(db.get_ref())
-    .await;
-
-    match result {
-        Ok(count) => HttpResponse::Ok().json(LikeCountResponse {
-            post_id,
-            like_count: count.unwrap_or(0),
-        }),
-        Err(e) => {
-            eprintln!("DB Error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error fetching like count")
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error fetching like count"))?;
+
+    Ok(HttpResponse::Ok().json(LikeCountResponse {
+        post_id,
+        like_count: count.unwrap_or(0),
+    }))
 }
\ No newline at end of file
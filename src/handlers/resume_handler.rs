@@ -0,0 +1,75 @@
+use actix_files::NamedFile;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::auth::Principal;
+use crate::authz::authorize_resume;
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::models::job_applications::ResumeUpload;
+use crate::pagination::PageParams;
+use crate::resumes;
+
+/// `POST /resumes?name=`: adds a version to the user's resume library. The body is the PDF or
+/// DOCX file itself.
+pub async fn upload_resume(
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    principal: Principal,
+    web::Query(upload): web::Query<ResumeUpload>,
+    payload: web::Payload,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    let bytes = resumes::read_upload(payload).await?;
+    let mut conn = db.acquire().await.map_err(ApiError::db("Error saving resume"))?;
+    let resume = resumes::store(&mut conn, &config.resume_dir, user_id, upload.name.as_deref(), &bytes, true).await?;
+    Ok(HttpResponse::Ok().json(resume))
+}
+
+pub async fn get_resumes(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    Ok(HttpResponse::Ok().json(resumes::library(db.get_ref(), user_id, &page).await?))
+}
+
+pub async fn delete_resume(
+    db: web::Data<PgPool>,
+    principal: Principal,
+    resume_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = principal.user_id()?;
+    resumes::remove_from_library(db.get_ref(), user_id, *resume_id).await?;
+    Ok(HttpResponse::Ok().json(json!({ "message": "Resume removed from your library" })))
+}
+
+/// Downloads a resume: for its owner and the businesses they applied to with it.
+pub async fn get_resume_file(
+    req: HttpRequest,
+    db: web::Data<PgPool>,
+    config: web::Data<Config>,
+    principal: Principal,
+    resume_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    authorize_resume(db.get_ref(), &principal, *resume_id).await?;
+    let resume = resumes::find(db.get_ref(), *resume_id).await?;
+
+    let file = NamedFile::open_async(resumes::path(&config.resume_dir, &resume)).await.map_err(|e| {
+        eprintln!("Error opening resume {}: {:?}", resume.id, e);
+        ApiError::not_found("Resume file not found")
+    })?;
+    let invalid = || ApiError::internal(format!("Invalid resume content type {}", resume.content_type));
+    let content_type = resume.content_type.parse().map_err(|_| invalid())?;
+    let extension = resumes::extension(&resume.content_type).ok_or_else(invalid)?;
+    Ok(file
+        .set_content_type(content_type)
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("{}.{}", resume.name, extension))],
+        })
+        .into_response(&req))
+}
@@ -0,0 +1,43 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use crate::error::ApiError;
+use crate::pagination::PageParams;
+use crate::search::{self, SEARCH_TYPES};
+
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+}
+
+/// `GET /search?q=&type=posts|users|communities|jobs`: one page of the best matches of that
+/// type. Without `type`, the first page of every type; follow a `next_cursor` with its `type`.
+pub async fn search(
+    db: web::Data<PgPool>,
+    web::Query(query): web::Query<SearchQuery>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let q = query.q.as_deref().map(str::trim).unwrap_or_default();
+    if q.is_empty() {
+        return Err(ApiError::bad_request("Search query `q` must not be empty"));
+    }
+    let db = db.get_ref();
+
+    match query.kind.as_deref() {
+        Some("posts") => Ok(HttpResponse::Ok().json(search::search_posts(db, q, &page).await?)),
+        Some("users") => Ok(HttpResponse::Ok().json(search::search_users(db, q, &page).await?)),
+        Some("communities") => Ok(HttpResponse::Ok().json(search::search_communities(db, q, &page).await?)),
+        Some("jobs") => Ok(HttpResponse::Ok().json(search::search_jobs(db, q, &page).await?)),
+        Some(_) => Err(ApiError::bad_request(format!("Unknown type; use one of: {}", SEARCH_TYPES.join(", ")))),
+        None if page.cursor.is_some() => Err(ApiError::bad_request("A cursor needs the `type` it came from")),
+        None => Ok(HttpResponse::Ok().json(json!({
+            "posts": search::search_posts(db, q, &page).await?,
+            "users": search::search_users(db, q, &page).await?,
+            "communities": search::search_communities(db, q, &page).await?,
+            "jobs": search::search_jobs(db, q, &page).await?,
+        }))),
+    }
+}
@@ -1,37 +1,36 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpResponse};
 use sqlx::PgPool;
 use uuid::Uuid;
 use crate::models::share_post::{SharePostPayload, SharePostResponse};
+use crate::auth::Principal;
+use crate::error::ApiError;
 
 
 pub async fn share_post(
     db: web::Data<PgPool>,
+    principal: Principal,
     json: web::Json<SharePostPayload>,
-) -> impl Responder {
+) -> Result<HttpResponse, ApiError> {
+    let shared_by = principal.user_id()?;
     for recipient_id in &json.shared_with {
-        if let Err(e) = sqlx::query!(
+        sqlx::query!(
             r#"
             INSERT INTO shared_posts (id, post_id, shared_by, shared_with, message)
             VALUES ($1, $2, $3, $4, $5)
             "#,
             Uuid::new_v4(),
             json.post_id,
-            json.shared_by,
+            shared_by,
             recipient_id,
             json.message
         )
         .execute(db.get_ref())
-        .await {
-            eprintln!("Failed to insert share record: {:?}", e);
-            return HttpResponse::InternalServerError().json(SharePostResponse {
-                status: "error".to_string(),
-                message: "Failed to share post".to_string(),
-            });
-        }
+        .await
+        .map_err(ApiError::db("Failed to share post"))?;
     }
 
-    HttpResponse::Ok().json(SharePostResponse {
+    Ok(HttpResponse::Ok().json(SharePostResponse {
         status: "success".to_string(),
         message: "Post shared successfully".to_string(),
-    })
+    }))
 }
\ No newline at end of file
@@ -2,6 +2,8 @@ use actix_web::{web, HttpResponse, HttpRequest};
 use uuid::Uuid;
 use sqlx::PgPool;
 use crate::models::user_badges::{User_Badges, Create_User_Badge};
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 use serde_json::json;
 
 fn get_lang(req: &HttpRequest) -> String {
@@ -23,10 +25,10 @@ pub async fn create_user_badge(
     req: HttpRequest,
     db: web::Data<PgPool>,
     json: web::Json<Create_User_Badge>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
-    let user = sqlx::query_as::<_, User_Badges>(
+    let badge = sqlx::query_as::<_, User_Badges>(
         r#"
         INSERT INTO user_badges (id, user_id, badge_name, badge_icon)
         VALUES ($1, $2, $3, $4)
@@ -40,62 +42,50 @@ pub async fn create_user_badge(
 
 
     .fetch_one(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error creating user badge"))?;
 
-    match user {
-        Ok(c) => HttpResponse::Ok().json(c),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error creating user badge"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(badge))
 }
 
-pub async fn get_user_badges(db: web::Data<PgPool>) -> HttpResponse {
-    let users = sqlx::query_as::<_, User_Badges>(
-        "SELECT * FROM user_badges ORDER BY awarded_at DESC"
+pub async fn get_user_badges(
+    db: web::Data<PgPool>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
+    let badges = sqlx::query_as::<_, User_Badges>(
+        r#"
+        SELECT * FROM user_badges
+        WHERE $1::timestamp IS NULL OR (awarded_at, id) < ($1, $2)
+        ORDER BY awarded_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving badges"))?;
 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving badges")
-        }
-    }
+    Ok(HttpResponse::Ok().json(Page::new(badges, &page, |b| Cursor::new(b.awarded_at, b.id))))
 }
 
 pub async fn delete_user_badge_by_id(
     db: web::Data<PgPool>,
     id: web::Path<Uuid>,  // Changed from email to UUID
-) -> HttpResponse {
-    let result = sqlx::query!(
+) -> Result<HttpResponse, ApiError> {
+    sqlx::query!(
         "DELETE FROM user_badges WHERE id = $1 RETURNING id",
         id.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error deleting badge"))?
+    .ok_or_else(|| ApiError::not_found("badge not found"))?;
 
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json(json!({
-            "status": "success",
-            "message": "badge deleted successfully"
-        })),
-        Ok(None) => HttpResponse::NotFound().json(json!({
-            "status": "error",
-            "message": "badge not found"
-        })),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(json!({
-                "status": "error",
-                "message": "Error deleting badge"
-            }))
-        }
-    }
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "success",
+        "message": "badge deleted successfully"
+    })))
 }
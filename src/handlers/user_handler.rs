@@ -1,12 +1,15 @@
 // src/handlers/user_handler.rs
-use actix_web::{web, HttpResponse, HttpRequest, get, Responder};
+use actix_web::{web, HttpResponse, HttpRequest, get};
 use uuid::Uuid;
 use sqlx::PgPool;
 use sqlx::{Pool, Postgres};
 use sqlx::QueryBuilder;
-use crate::models::user::{CreateUser, User, UpdateUser};
-use serde_json::json;
-use std::collections::HashMap;
+use crate::models::user::{CreateUser, User, UpdateUser, SelfUserView, UserResponse};
+use crate::password::hash_password;
+use crate::auth::Principal;
+use crate::authz::authorize_user_account;
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
 
 
 fn get_lang(req: &HttpRequest) -> String {
@@ -24,27 +27,19 @@ fn get_lang(req: &HttpRequest) -> String {
         .to_string()
 }
 
-#[derive(serde::Serialize, sqlx::FromRow)]
-struct PublicUser {
-    id: Uuid,
-    username: String,
-    first_name: String,
-    last_name: String,
-    profile_pic: Option<String>,
-    bio: Option<serde_json::Value>,
-    email: String,
-    organisation: Option<String>,
-    created_at: Option<chrono::NaiveDateTime>,
-}
-
 pub async fn create_user(
     req: HttpRequest,
     db: web::Data<PgPool>,
     json: web::Json<CreateUser>,
-) -> HttpResponse {
+) -> Result<HttpResponse, ApiError> {
     let lang = get_lang(&req);            
     println!("Language preference: {}", lang);
 
+    let password_hash = hash_password(&json.password).map_err(|e| {
+        eprintln!("Password hashing error: {:?}", e);
+        ApiError::internal("Error creating user")
+    })?;
+
     // Ensure all fields from CreateUser and default/generated fields are handled.
     // The INSERT statement should list all columns you are providing values for,
     // and the number of placeholders ($1, $2, ...) must match the number of .bind() calls.
@@ -73,7 +68,7 @@ pub async fn create_user(
     .bind(&json.first_name)
     .bind(&json.last_name)
     .bind(&json.username)
-    .bind(&json.password)
+    .bind(&password_hash)
     .bind(&json.email)
     .bind(&json.resume_url)
     .bind(&json.bio) // Bind bio from CreateUser
@@ -82,65 +77,77 @@ pub async fn create_user(
     .bind(&json.provider_user_id)
     .bind(&json.access_token)
     .fetch_one(db.get_ref())
-    .await;
-
-    match user {
-        Ok(u) => HttpResponse::Ok().json(u),
-        Err(e) => {
-            eprintln!("DB error: {:?}", e);
-            // Provide more specific error message if possible (e.g., unique constraint violation)
-            HttpResponse::InternalServerError().body(format!("Error creating user: {}", e))
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error creating user"))?;
+
+    Ok(HttpResponse::Ok().json(SelfUserView::from(user)))
 }
 
-pub async fn get_users(db: web::Data<PgPool>) -> HttpResponse {
+pub async fn get_users(
+    db: web::Data<PgPool>,
+    viewer: Option<Principal>,
+    web::Query(page): web::Query<PageParams>,
+) -> Result<HttpResponse, ApiError> {
+    let after = page.after()?;
     // With SELECT *, it's crucial that the User struct exactly matches the database table schema.
     // We already fixed `User` to include `refresh_token` as Option<String>,
     // and other fields as Options where appropriate.
     let users = sqlx::query_as::<_, User>(
-        "SELECT * FROM users ORDER BY created_at DESC"
+        r#"
+        SELECT * FROM users
+        WHERE $1::timestamp IS NULL OR (created_at, id) < ($1, $2)
+        ORDER BY created_at DESC, id DESC
+        LIMIT $3
+        "#
     )
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
     .fetch_all(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving users"))?;
 
-    match users {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().body("Error retrieving users")
-        }
-    }
+    // created_at is NOT NULL; the model keeps it optional.
+    let users = Page::new(users, &page, |user| Cursor::new(user.created_at.unwrap_or_default(), user.id))
+        .map(|user| UserResponse::for_viewer(user, viewer.as_ref()));
+    Ok(HttpResponse::Ok().json(users))
 }
 
 pub async fn delete_user_by_email(
     db: web::Data<PgPool>,
     email: web::Path<String>,
-) -> HttpResponse {
-    let result = sqlx::query!(
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_user_account(&db, &principal, &email).await?;
+    sqlx::query!(
         "DELETE FROM users WHERE email = $1 RETURNING email",
         email.into_inner()
     )
     .fetch_optional(db.get_ref())
-    .await;
-
-    match result {
-        Ok(Some(_)) => HttpResponse::Ok().json("User deleted successfully"),
-        Ok(None) => HttpResponse::NotFound().json("User not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json("Error deleting user")
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error deleting user"))?
+    .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    Ok(HttpResponse::Ok().json("User deleted successfully"))
 }
 
 pub async fn update_user_by_email(
     db: web::Data<PgPool>,
     email_path: web::Path<String>,
     json: web::Json<UpdateUser>,
-) -> HttpResponse {
+    principal: Principal,
+) -> Result<HttpResponse, ApiError> {
+    authorize_user_account(&db, &principal, &email_path).await?;
     let email = email_path.into_inner();
-    let payload = json.into_inner();
+    let mut payload = json.into_inner();
+
+    if let Some(password) = payload.password.take() {
+        let hash = hash_password(&password).map_err(|e| {
+            eprintln!("Password hashing error: {:?}", e);
+            ApiError::internal("Error updating user")
+        })?;
+        payload.password = Some(hash);
+    }
 
     let mut builder = QueryBuilder::new("UPDATE users SET ");
     let mut first = true;
@@ -205,6 +212,8 @@ pub async fn update_user_by_email(
     push_field!(&payload.hacking_on, "hackingon");
     push_field!(&payload.learning, "learning");
     push_field!(&payload.available_for, "availablefor");
+    push_field!(&payload.show_email, "show_email");
+    push_field!(&payload.show_mobile, "show_mobile");
 
     if !first {
         builder.push(", ");
@@ -217,149 +226,91 @@ pub async fn update_user_by_email(
 
     let query = builder.build_query_as::<User>();
 
-    match query.fetch_one(db.get_ref()).await {
-        Ok(updated_user) => HttpResponse::Ok().json(updated_user),
-        Err(sqlx::Error::RowNotFound) => HttpResponse::NotFound().json("User not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json(format!("Error updating user: {}", e))
-        }
-    }
+    let updated_user = query.fetch_optional(db.get_ref())
+        .await
+        .map_err(ApiError::db("Error updating user"))?
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::for_viewer(updated_user, Some(&principal))))
 }
 
 
 pub async fn get_user_by_id(
     db: web::Data<PgPool>,
     user_id: web::Path<Uuid>,
-) -> HttpResponse {
+    viewer: Option<Principal>,
+) -> Result<HttpResponse, ApiError> {
     // `SELECT *` here is fine if User struct matches DB.
     let user = sqlx::query_as::<_, User>(
         "SELECT * FROM users WHERE id = $1"
     )
     .bind(*user_id)
     .fetch_optional(db.get_ref())
-    .await;
-
-    match user {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
-        Ok(None) => HttpResponse::NotFound().json("User not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json("Error retrieving user")
-        }
-    }
-}
+    .await
+    .map_err(ApiError::db("Error retrieving user"))?
+    .ok_or_else(|| ApiError::not_found("User not found"))?;
 
-#[derive(serde::Serialize, sqlx::FromRow)]
-struct PublicProfileUser {
-    id: Uuid,
-    username: String,
-    first_name: String,
-    last_name: String,
-    profile_pic: Option<String>,
-    bio: Option<serde_json::Value>,
-    email: String,
-    organisation: Option<String>,
-    created_at: Option<chrono::NaiveDateTime>,
-    college: Option<String>,
-    stream: Option<String>,
-    linkedin: Option<String>,
-    github: Option<String>,
-    skills: Option<Vec<String>>,
-    interests: Option<Vec<String>>,
-    work_experiences: Option<serde_json::Value>,
-    projects: Option<serde_json::Value>,
-    batch: Option<String>,
-    pronouns: Option<String>,
-    location: Option<String>,
-    hackingon: Option<String>,
-    learning: Option<String>,
-    availablefor: Option<String>,
+    Ok(HttpResponse::Ok().json(UserResponse::for_viewer(user, viewer.as_ref())))
 }
 
 pub async fn get_public_profile(
     db: web::Data<PgPool>,
     identifier: web::Path<String>,
-) -> impl Responder {
+    viewer: Option<Principal>,
+) -> Result<HttpResponse, ApiError> {
     // First try to find by username
-    let by_username = sqlx::query_as::<_, PublicProfileUser>(
-        r#"
-        SELECT 
-            id, username, first_name, last_name, profile_pic, bio, email, 
-            organisation, created_at, college, stream, linkedin, github, 
-            skills, interests, work_experiences, projects, batch,
-            pronouns, location, hackingon, learning, availablefor
-        FROM users
-        WHERE username = $1
-        "#
+    let by_username = sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE username = $1"
     )
     .bind(identifier.as_str())
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving profile"))?;
 
-    if let Ok(Some(user)) = by_username {
-        return HttpResponse::Ok().json(user);
+    if let Some(user) = by_username {
+        return Ok(HttpResponse::Ok().json(UserResponse::for_viewer(user, viewer.as_ref())));
     }
 
     // Try by email
-    let by_email = sqlx::query_as::<_, PublicProfileUser>(
-        r#"
-        SELECT 
-            id, username, first_name, last_name, profile_pic, bio, email, 
-            organisation, created_at, college, stream, linkedin, github, 
-            skills, interests, work_experiences, projects, batch,
-            pronouns, location, hackingon, learning, availablefor
-        FROM users
-        WHERE email = $1
-        "#
+    let user = sqlx::query_as::<_, User>(
+        "SELECT * FROM users WHERE email = $1"
     )
     .bind(identifier.as_str())
     .fetch_optional(db.get_ref())
-    .await;
-
-    match by_email {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
-        Ok(None) => HttpResponse::NotFound().body("User not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().finish()
-        }
-    }
+    .await
+    .map_err(ApiError::db("Error retrieving profile"))?
+    .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::for_viewer(user, viewer.as_ref())))
 }
 
 pub async fn get_user_interest_by_id(
     db: web::Data<PgPool>,
     user_id: web::Path<Uuid>,
-) -> HttpResponse {
-    let result = sqlx::query!(
+) -> Result<HttpResponse, ApiError> {
+    let record = sqlx::query!(
         "SELECT interests FROM users WHERE id = $1",
         *user_id
     )
     .fetch_optional(db.get_ref())
-    .await;
+    .await
+    .map_err(ApiError::db("Error retrieving user interests"))?
+    .ok_or_else(|| ApiError::not_found("User not found"))?;
 
-    match result {
-        Ok(Some(record)) => {
-            // interests column could be Option<String> or Option<serde_json::Value> depending on your schema
-            // If interests is stored as JSONB array of strings in DB, you'd deserialize it here
-            HttpResponse::Ok().json(record.interests)
-        }
-        Ok(None) => HttpResponse::NotFound().json("User not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e);
-            HttpResponse::InternalServerError().json("Error retrieving user interests")
-        }
-    }
+    // interests column could be Option<String> or Option<serde_json::Value> depending on your schema
+    // If interests is stored as JSONB array of strings in DB, you'd deserialize it here
+    Ok(HttpResponse::Ok().json(record.interests))
 }
 
 // Or /api/users/{id} if you pass ID in JWT
 pub async fn get_user_by_email(
     pool: web::Data<Pool<Postgres>>,
     path_email: web::Path<String>,
-) -> impl Responder {
+    viewer: Option<Principal>,
+) -> Result<HttpResponse, ApiError> {
     let email = path_email.into_inner();
      let email_lower = email.to_lowercase();
-    match sqlx::query_as!(
+    let user = sqlx::query_as!(
         User,
         r#"
         SELECT
@@ -379,9 +330,6 @@ pub async fn get_user_by_email(
             resume_url,
             interests,
             provider,
-            provider_user_id,
-            access_token,
-            refresh_token,
             oauth_email,
             college,
             batch,
@@ -395,7 +343,10 @@ pub async fn get_user_by_email(
             location,
             hackingon,
             learning,
-            availablefor
+            availablefor,
+            role,
+            show_email,
+            show_mobile
         FROM users
         WHERE email = $1
         "#,
@@ -403,12 +354,8 @@ pub async fn get_user_by_email(
     )
     .fetch_optional(pool.get_ref())
     .await
-    {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
-        Ok(None) => HttpResponse::NotFound().body("User not found"),
-        Err(e) => {
-            eprintln!("Database error: {:?}", e); // This is where the error will be very clear
-            HttpResponse::InternalServerError().body("Database error")
-        }
-    }
+    .map_err(ApiError::db("Error retrieving user"))?
+    .ok_or_else(|| ApiError::not_found("User not found"))?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::for_viewer(user, viewer.as_ref())))
 }
\ No newline at end of file
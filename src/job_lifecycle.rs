@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use log::{error, info};
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::ApiError;
+use crate::models::job_applications::ApplicationStatus;
+use crate::models::job_list::JobStatus;
+
+#[derive(Debug, Serialize)]
+pub struct CloseSummary {
+    pub expired_jobs: u64,
+    pub notified_applicants: u64,
+    pub checked_at: NaiveDateTime,
+}
+
+/// Expires the published and paused jobs whose `closes_at` is at or before `now` and notifies
+/// their pending applicants, in one transaction.
+pub async fn close_expired(db: &PgPool, now: NaiveDateTime) -> Result<CloseSummary, sqlx::Error> {
+    let mut tx = db.begin().await?;
+    let expired = sqlx::query_scalar!(
+        r#"
+        UPDATE job_listings
+        SET status = 'expired', is_active = FALSE, closed_at = $1, updated_at = $1
+        WHERE status IN ('published', 'paused') AND closes_at <= $1
+        RETURNING id
+        "#,
+        now
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let notified_applicants = notify_pending_applicants(&mut tx, &expired, JobStatus::Expired).await?;
+    tx.commit().await?;
+    Ok(CloseSummary { expired_jobs: expired.len() as u64, notified_applicants, checked_at: now })
+}
+
+/// Starts the background job that expires jobs past their deadline every
+/// `job_close_check_seconds` (first run right away). Does nothing when the interval is 0.
+pub fn spawn_closer(db: PgPool, config: &Config) {
+    if config.job_close_check_seconds == 0 {
+        return;
+    }
+    let period = Duration::from_secs(config.job_close_check_seconds);
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(period);
+        loop {
+            interval.tick().await;
+            match close_expired(&db, Utc::now().naive_utc()).await {
+                Ok(summary) if summary.expired_jobs > 0 => info!(
+                    "Expired {} jobs, notified {} applicants",
+                    summary.expired_jobs, summary.notified_applicants
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Failed to close expired jobs: {}", e),
+            }
+        }
+    });
+}
+
+/// Moves a job to `next` on behalf of the business that posted it (see
+/// [`JobStatus::can_move_to`]). Publishing needs the deadline ahead and room for more
+/// applicants; closing notifies the pending applicants.
+pub async fn change_status(
+    conn: &mut PgConnection,
+    job_id: Uuid,
+    next: JobStatus,
+    now: NaiveDateTime,
+) -> Result<(), ApiError> {
+    let job = sqlx::query!(
+        "SELECT status, closes_at, max_applicants FROM job_listings WHERE id = $1 FOR UPDATE",
+        job_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error updating job"))?
+    .ok_or_else(|| ApiError::not_found("Job not found"))?;
+    let current = JobStatus::parse(&job.status)
+        .ok_or_else(|| ApiError::internal(format!("Unknown job status {}", job.status)))?;
+    if current == next {
+        return Ok(());
+    }
+    if !current.can_move_to(next) {
+        return Err(ApiError::conflict(format!(
+            "A {} job cannot be moved to {}",
+            current.as_str(),
+            next.as_str()
+        )));
+    }
+    if next == JobStatus::Published {
+        if job.closes_at.is_some_and(|closes_at| closes_at <= now) {
+            return Err(ApiError::conflict("Set a closes_at in the future to publish this job again"));
+        }
+        if let Some(max) = job.max_applicants
+            && applicant_count(conn, job_id).await? >= i64::from(max)
+        {
+            return Err(ApiError::conflict("This job already has its maximum number of applicants"));
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE job_listings
+        SET status = $2, is_active = ($2 = 'published'), closed_when_full = FALSE,
+            closed_at = CASE WHEN $2 = 'closed' THEN $3::timestamp END, updated_at = $3
+        WHERE id = $1
+        "#,
+        job_id,
+        next.as_str(),
+        now
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error updating job"))?;
+    if next == JobStatus::Closed {
+        notify_pending_applicants(conn, &[job_id], next).await.map_err(ApiError::db("Error updating job"))?;
+    }
+    Ok(())
+}
+
+/// Checks that a job takes applications at `now`, locking it until the end of the transaction
+/// so two applicants cannot both take its last place.
+pub async fn admit_applicant(conn: &mut PgConnection, job_id: Uuid, now: NaiveDateTime) -> Result<(), ApiError> {
+    let job = sqlx::query!(
+        "SELECT status, closes_at, max_applicants FROM job_listings WHERE id = $1 FOR UPDATE",
+        job_id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error creating job application"))?
+    .ok_or_else(|| ApiError::not_found("Job not found"))?;
+    if job.status != JobStatus::Published.as_str() {
+        return Err(ApiError::conflict("This job is closed and no longer accepts applications"));
+    }
+    if job.closes_at.is_some_and(|closes_at| closes_at <= now) {
+        return Err(ApiError::conflict("The application deadline for this job has passed"));
+    }
+    if let Some(max) = job.max_applicants
+        && applicant_count(conn, job_id).await? >= i64::from(max)
+    {
+        return Err(ApiError::conflict("This job has reached its maximum number of applicants"));
+    }
+    Ok(())
+}
+
+/// Closes a published job once it has `max_applicants` applications. Run after adding one, in
+/// the transaction of [`admit_applicant`]. A later withdrawal does not reopen the job; raising
+/// or removing the cap does (see [`reopen_if_unblocked`]).
+pub async fn close_if_full(conn: &mut PgConnection, job_id: Uuid, now: NaiveDateTime) -> Result<(), ApiError> {
+    sqlx::query!(
+        r#"
+        UPDATE job_listings
+        SET status = 'closed', is_active = FALSE, closed_when_full = TRUE, closed_at = $2, updated_at = $2
+        WHERE id = $1 AND status = 'published' AND max_applicants <= (
+            SELECT count(*) FROM job_applications WHERE job_id = $1 AND status <> 'withdrawn'
+        )
+        "#,
+        job_id,
+        now
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error creating job application"))?;
+    Ok(())
+}
+
+/// Publishes an expired job again once its deadline is moved out or removed, and a job closed
+/// by [`close_if_full`] once its cap leaves room again. Run after changing either limit; jobs
+/// the employer closed stay closed.
+pub async fn reopen_if_unblocked(conn: &mut PgConnection, job_id: Uuid, now: NaiveDateTime) -> Result<(), ApiError> {
+    sqlx::query!(
+        r#"
+        UPDATE job_listings
+        SET status = 'published', is_active = TRUE, closed_when_full = FALSE, closed_at = NULL, updated_at = $2
+        WHERE id = $1 AND (status = 'expired' OR (status = 'closed' AND closed_when_full))
+          AND (closes_at IS NULL OR closes_at > $2)
+          AND (max_applicants IS NULL OR max_applicants > (
+              SELECT count(*) FROM job_applications WHERE job_id = $1 AND status <> 'withdrawn'
+          ))
+        "#,
+        job_id,
+        now
+    )
+    .execute(conn)
+    .await
+    .map_err(ApiError::db("Error updating job"))?;
+    Ok(())
+}
+
+/// Applications that count against `max_applicants`: all but the withdrawn ones.
+async fn applicant_count(conn: &mut PgConnection, job_id: Uuid) -> Result<i64, ApiError> {
+    sqlx::query_scalar!(
+        r#"SELECT count(*) AS "count!" FROM job_applications WHERE job_id = $1 AND status <> 'withdrawn'"#,
+        job_id
+    )
+    .fetch_one(conn)
+    .await
+    .map_err(ApiError::db("Error counting applicants"))
+}
+
+/// Tells the applicants whose applications to `job_ids` are still open that the jobs closed
+/// (`status` closed or expired). Returns the number of notifications sent.
+async fn notify_pending_applicants(
+    conn: &mut PgConnection,
+    job_ids: &[Uuid],
+    status: JobStatus,
+) -> Result<u64, sqlx::Error> {
+    if job_ids.is_empty() {
+        return Ok(0);
+    }
+    // `format()` arguments: the job title, then the organisation.
+    let (kind, message) = match status {
+        JobStatus::Expired => (
+            "job_expired",
+            "The application deadline of \"%s\" at %s has passed. Your application is still with the employer.",
+        ),
+        _ => ("job_closed", "\"%s\" at %s was closed by the employer. Your application is still with the employer."),
+    };
+    let pending: Vec<&str> =
+        ApplicationStatus::ALL.into_iter().filter(|s| !s.is_final()).map(ApplicationStatus::as_str).collect();
+    let sent = sqlx::query!(
+        r#"
+        INSERT INTO notifications (user_id, kind, message, job_id, application_id)
+        SELECT a.applicant_id, $2, format($3, COALESCE(j.title->>'en', 'a job'), j.organisation), j.id, a.id
+        FROM job_applications a
+        JOIN job_listings j ON j.id = a.job_id
+        WHERE a.job_id = ANY($1) AND a.status = ANY($4)
+        "#,
+        job_ids,
+        kind,
+        message,
+        &pending as &[&str]
+    )
+    .execute(conn)
+    .await?
+    .rows_affected();
+    Ok(sent)
+}
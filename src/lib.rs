@@ -0,0 +1,22 @@
+pub mod app;
+pub mod db;
+pub mod models;
+pub mod handlers;
+pub mod routes;
+pub mod oauth;
+pub mod config;
+pub mod auth;
+pub mod authz;
+pub mod error;
+pub mod notifications;
+pub mod pagination;
+pub mod ats;
+pub mod feed;
+pub mod job_lifecycle;
+pub mod rankings;
+pub mod recommendations;
+pub mod resumes;
+pub mod search;
+pub mod password;
+pub mod session;
+pub mod faker;
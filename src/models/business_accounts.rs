@@ -10,6 +10,7 @@ pub struct BusinessAccount {
     pub company_name: String,
     pub contact_person_name: String,
     pub email: String,
+    #[serde(skip_serializing)]
     pub password_hash: String,
     pub address: String,
     pub gst_number: Option<String>,
@@ -29,7 +30,9 @@ pub struct CreateBusinessAccount {
     pub company_name: String,
     pub contact_person_name: String,
     pub email: String,
-    pub password_hash: String,
+    // Plaintext from the client; hashed before it is stored. Older clients still send `password_hash`.
+    #[serde(alias = "password_hash")]
+    pub password: String,
     pub address: String,
     pub gst_number: Option<String>,
     pub website: Option<String>,
@@ -38,7 +41,7 @@ pub struct CreateBusinessAccount {
     pub description: Option<String>,
     pub industry: Option<String>,
     pub size: Option<String>,
-    pub is_verified: Option<bool>
+    // `is_verified` is not accepted here: new accounts start unverified until an admin verifies them.
 }
 
 #[derive(Deserialize)]
@@ -46,7 +49,8 @@ pub struct UpdateBusinessAccount {
     pub company_name: Option<String>,
     pub contact_person_name: Option<String>,
     pub email: Option<String>,
-    pub password_hash: Option<String>,
+    #[serde(alias = "password_hash")]
+    pub password: Option<String>,
     pub address: Option<String>,
     pub gst_number: Option<String>,
     pub website: Option<String>,
@@ -4,12 +4,6 @@ use uuid::Uuid;
 use chrono::NaiveDateTime;
 use serde_json::Value;
 
-#[derive(Deserialize)]
-pub struct Pagination {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
-}
-
 #[derive(Serialize, Deserialize, FromRow)]
 pub struct Comments {
     pub id: Uuid,
@@ -24,8 +18,6 @@ pub struct Comments {
 #[derive(Deserialize)]
 pub struct Create_Comment {
     pub post_id: Uuid,
-    pub author_id: Uuid,
-    pub author_type: String, // 👈 Make sure this and all fields are `pub`
     pub content: serde_json::Value,
     pub parent_id: Option<Uuid>,
     pub is_active: Option<bool>,
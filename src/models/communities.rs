@@ -6,12 +6,6 @@ use serde_json::Value;
 
 
 
-#[derive(Deserialize)]
-pub struct Pagination {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
-}
-
 #[derive(Serialize, Deserialize, FromRow)]
 pub struct Community {
     pub id: Uuid,
@@ -32,9 +26,6 @@ pub struct Community {
 pub struct Create_Community {
     pub name: String,
     pub description: Option<serde_json::Value>,
-    pub creator_id: Option<Uuid>,              // Changed to Option
-    pub creator_business_id: Option<Uuid>,     // New field
-    pub creator_type: String,                  // New field
     pub banner_url: Option<String>,
     pub is_public: Option<bool>,
     pub is_active: Option<bool>,
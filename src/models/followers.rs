@@ -16,7 +16,6 @@ pub struct Followers {
 
 #[derive(Serialize, Deserialize, FromRow)]
 pub struct Create_Follower {
-    pub follower_id: Uuid,
     pub followed_id: Uuid,
     pub is_following: bool,
 }
@@ -33,6 +32,7 @@ pub struct FollowerListUser {
     pub last_name: String,
     pub username: String,
     pub profile_pic: Option<String>,
+    pub followed_at: NaiveDateTime,
 }
 
 #[derive(Serialize, FromRow)]
@@ -42,4 +42,5 @@ pub struct FollowingListUser {
     pub last_name: String,
     pub username: String,
     pub profile_pic: Option<String>,
+    pub followed_at: NaiveDateTime,
 }
\ No newline at end of file
@@ -14,12 +14,192 @@ pub struct JobApplication {
     pub status: String,
     pub updated_at: NaiveDateTime,
     pub is_active: bool,
+    /// The uploaded resume sent with this application, if any (see `resume_url`).
+    pub resume_id: Option<Uuid>,
 }
 
 // Updated to only require job_id and cover letter
 #[derive(Deserialize)]
 pub struct CreateJobApplicationRequest {
     pub job_id: Uuid,
-    pub applicant_id: Uuid,
     pub cover_letter: Option<String>,
-}
\ No newline at end of file
+    /// A resume from the applicant's library; without it the application has no resume.
+    pub resume_id: Option<Uuid>,
+}
+
+/// Where an application is in the hiring pipeline. Stored as its snake_case name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplicationStatus {
+    Applied,
+    UnderReview,
+    Shortlisted,
+    Interview,
+    Offered,
+    Hired,
+    Rejected,
+    Withdrawn,
+}
+
+impl ApplicationStatus {
+    pub const ALL: [ApplicationStatus; 8] = [
+        ApplicationStatus::Applied,
+        ApplicationStatus::UnderReview,
+        ApplicationStatus::Shortlisted,
+        ApplicationStatus::Interview,
+        ApplicationStatus::Offered,
+        ApplicationStatus::Hired,
+        ApplicationStatus::Rejected,
+        ApplicationStatus::Withdrawn,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApplicationStatus::Applied => "applied",
+            ApplicationStatus::UnderReview => "under_review",
+            ApplicationStatus::Shortlisted => "shortlisted",
+            ApplicationStatus::Interview => "interview",
+            ApplicationStatus::Offered => "offered",
+            ApplicationStatus::Hired => "hired",
+            ApplicationStatus::Rejected => "rejected",
+            ApplicationStatus::Withdrawn => "withdrawn",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<ApplicationStatus> {
+        Self::ALL.into_iter().find(|status| status.as_str() == value)
+    }
+
+    /// Hired, rejected and withdrawn applications do not move any more.
+    pub fn is_final(self) -> bool {
+        matches!(self, ApplicationStatus::Hired | ApplicationStatus::Rejected | ApplicationStatus::Withdrawn)
+    }
+
+    /// The pipeline applied -> ... -> hired, without the exits (rejected, withdrawn).
+    pub const PIPELINE: [ApplicationStatus; 6] = [
+        ApplicationStatus::Applied,
+        ApplicationStatus::UnderReview,
+        ApplicationStatus::Shortlisted,
+        ApplicationStatus::Interview,
+        ApplicationStatus::Offered,
+        ApplicationStatus::Hired,
+    ];
+
+    /// Position in the pipeline; `None` for rejected and withdrawn.
+    pub fn stage(self) -> Option<usize> {
+        Self::PIPELINE.iter().position(|status| *status == self)
+    }
+
+    /// Whether the business that posted the job may move an application from `self` to `next`:
+    /// forward through the pipeline (stages may be skipped) or to rejected, never back and never
+    /// out of a final status. Withdrawing is left to the applicant.
+    pub fn can_move_to(self, next: ApplicationStatus) -> bool {
+        if self.is_final() {
+            return false;
+        }
+        match (self.stage(), next.stage()) {
+            (Some(from), Some(to)) => to > from,
+            _ => next == ApplicationStatus::Rejected,
+        }
+    }
+}
+
+/// Body of `PATCH /jobApplications/{id}/status`.
+#[derive(Deserialize)]
+pub struct UpdateApplicationStatus {
+    pub status: ApplicationStatus,
+    /// Optional message to the applicant, kept in the history.
+    pub note: Option<String>,
+}
+
+/// One status change of an application.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ApplicationEvent {
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub changed_by_type: Option<String>,
+    pub changed_by_user: Option<Uuid>,
+    pub changed_by_business: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Query of the resume uploads: `POST /resumes?name=` and `PUT /jobApplications/{id}/resume?name=`.
+#[derive(Deserialize)]
+pub struct ResumeUpload {
+    pub name: Option<String>,
+}
+
+/// Query of `GET /ats/jobs/{job_id}/applications`.
+#[derive(Deserialize)]
+pub struct ApplicantFilters {
+    /// Comma-separated statuses, e.g. `shortlisted,interview`.
+    pub status: Option<String>,
+    /// Lowest match score (0-100) to include.
+    pub min_match: Option<i64>,
+    /// `date` (default, newest first) or `match` (best match first).
+    pub sort: Option<String>,
+}
+
+/// Body of `PATCH /ats/jobs/{job_id}/applications/status`.
+#[derive(Deserialize)]
+pub struct BulkStatusUpdate {
+    pub application_ids: Vec<Uuid>,
+    pub status: ApplicationStatus,
+    pub note: Option<String>,
+}
+
+/// Body of `POST /ats/applications/{id}/notes`.
+#[derive(Deserialize)]
+pub struct CreateApplicationNote {
+    pub note: String,
+}
+
+/// Body of `PUT /ats/applications/{id}/rating`; `null` clears the rating.
+#[derive(Deserialize)]
+pub struct RateApplication {
+    pub rating: Option<i16>,
+}
+
+/// A recruiter's private note on an application; never shown to the applicant.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ApplicationNote {
+    pub id: Uuid,
+    pub application_id: Uuid,
+    pub written_by_type: String,
+    pub written_by_user: Option<Uuid>,
+    pub written_by_business: Option<Uuid>,
+    pub note: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ApplicationStatus::*;
+    use super::*;
+
+    #[test]
+    fn test_statuses_only_move_forward_or_to_rejected() {
+        assert!(Applied.can_move_to(UnderReview));
+        assert!(Applied.can_move_to(Interview));
+        assert!(Offered.can_move_to(Hired));
+        assert!(Interview.can_move_to(Rejected));
+        assert!(!Interview.can_move_to(Shortlisted));
+        assert!(!Applied.can_move_to(Applied));
+        assert!(!Applied.can_move_to(Withdrawn));
+        assert!(!Hired.can_move_to(Rejected));
+        assert!(!Rejected.can_move_to(UnderReview));
+        assert!(!Withdrawn.can_move_to(Applied));
+    }
+
+    #[test]
+    fn test_statuses_round_trip_through_their_names() {
+        for status in ApplicationStatus::ALL {
+            assert_eq!(ApplicationStatus::parse(status.as_str()), Some(status));
+            assert_eq!(serde_json::to_value(status).unwrap(), status.as_str());
+        }
+        assert_eq!(ApplicationStatus::parse("under review"), None);
+    }
+}
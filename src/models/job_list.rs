@@ -1,4 +1,4 @@
-use serde::{Serialize, Deserialize};
+use serde::{Serialize, Deserialize, Deserializer};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::NaiveDateTime;
@@ -22,12 +22,6 @@ impl fmt::Display for JobType {
     }
 }
 
-#[derive(Deserialize)]
-pub struct Pagination {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
-}
-
 #[derive(Serialize, Deserialize, FromRow)]
 pub struct Job_listings {
     pub id: Uuid,
@@ -42,15 +36,23 @@ pub struct Job_listings {
     pub min_experience: Option<i32>,
     pub skills: Option<Vec<String>>,
     pub salary_range: Option<String>,
+    pub salary_min: Option<i32>,
+    pub salary_max: Option<i32>,
+    pub salary_currency: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
     pub is_active: Option<bool>,
     pub job_posted_by: Option<String>,
+    pub status: String,
+    /// Applications close at this time; the job then expires.
+    pub closes_at: Option<NaiveDateTime>,
+    /// The job closes once this many applications (withdrawn ones aside) came in.
+    pub max_applicants: Option<i32>,
+    pub closed_at: Option<NaiveDateTime>,
 }
 
 #[derive(Deserialize)]
 pub struct CreateJob {
-    pub posted_by: Uuid,
     pub applied_by: Option<Uuid>,
     pub organisation: String,
     pub title: Value,
@@ -60,14 +62,22 @@ pub struct CreateJob {
     pub skills: Vec<String>,
     pub notice_period: String,
     pub min_experience: Option<i32>,
-    pub salary_range: String,
+    /// Label shown to users; when the numbers below are missing they are parsed from it.
+    pub salary_range: Option<String>,
+    pub salary_min: Option<i32>,
+    pub salary_max: Option<i32>,
+    pub salary_currency: Option<String>,
     pub job_posted_by: String,
+    /// Superseded by `status`: `false` creates a closed job.
     pub is_active: Option<bool>,
+    /// `draft` or `published` (the default).
+    pub status: Option<JobStatus>,
+    pub closes_at: Option<NaiveDateTime>,
+    pub max_applicants: Option<i32>,
 }
 
 #[derive(Deserialize)]
 pub struct UpdateJob {
-    pub posted_by: Option<Uuid>,
     pub applied_by: Option<Uuid>,
     pub organisation: Option<String>,
     pub title: Option<Value>,
@@ -78,6 +88,202 @@ pub struct UpdateJob {
     pub notice_period: Option<String>,
     pub min_experience: Option<i32>,
     pub salary_range: Option<String>,
+    pub salary_min: Option<i32>,
+    pub salary_max: Option<i32>,
+    pub salary_currency: Option<String>,
     pub job_posted_by: Option<String>,
+    /// Superseded by `status`: `true` publishes the job, `false` closes it.
     pub is_active: Option<bool>,
+    pub status: Option<JobStatus>,
+    /// `null` removes the deadline.
+    #[serde(default, deserialize_with = "present")]
+    pub closes_at: Option<Option<NaiveDateTime>>,
+    /// `null` removes the cap.
+    #[serde(default, deserialize_with = "present")]
+    pub max_applicants: Option<Option<i32>>,
+}
+
+/// For `Option<Option<T>>` fields with `#[serde(default)]`: a missing field stays `None`, so it is
+/// left unchanged, while `null` becomes `Some(None)` and clears it.
+fn present<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// Where a job posting is in its lifecycle. Only published jobs are open to applications;
+/// drafts and expired jobs are left out of `GET /jobs`. Stored as its lowercase name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Draft,
+    Published,
+    Paused,
+    Closed,
+    /// Closed by the server when `closes_at` passed.
+    Expired,
+}
+
+impl JobStatus {
+    pub const ALL: [JobStatus; 5] =
+        [JobStatus::Draft, JobStatus::Published, JobStatus::Paused, JobStatus::Closed, JobStatus::Expired];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Draft => "draft",
+            JobStatus::Published => "published",
+            JobStatus::Paused => "paused",
+            JobStatus::Closed => "closed",
+            JobStatus::Expired => "expired",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<JobStatus> {
+        Self::ALL.into_iter().find(|status| status.as_str() == value)
+    }
+
+    /// Whether the business that posted the job may move it from `self` to `next`: any job can
+    /// be (re)published, a published job paused, and a published or paused one closed. Nothing
+    /// goes back to draft, and only the server expires jobs.
+    pub fn can_move_to(self, next: JobStatus) -> bool {
+        match next {
+            JobStatus::Published => self != JobStatus::Published,
+            JobStatus::Paused => self == JobStatus::Published,
+            JobStatus::Closed => matches!(self, JobStatus::Published | JobStatus::Paused),
+            JobStatus::Draft | JobStatus::Expired => false,
+        }
+    }
+}
+
+/// A salary band in whole units of `currency` (ISO 4217 code).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SalaryRange {
+    pub min: i32,
+    pub max: i32,
+    pub currency: Option<String>,
+}
+
+impl SalaryRange {
+    /// Reads the amounts out of a free-text label: "50000-70000 USD", "$50,000 - $70,000",
+    /// "50k to 70k EUR" or a single amount. `None` when there is no amount or the band is
+    /// reversed. `powerbrilliant-admin backfill-salaries` runs it over the labels of older jobs.
+    pub fn parse(label: &str) -> Option<SalaryRange> {
+        let text = label.replace(',', "");
+        let chars: Vec<char> = text.chars().collect();
+        let mut amounts = Vec::new();
+        let mut i = 0;
+        while i < chars.len() && amounts.len() < 2 {
+            if !chars[i].is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let mut amount: f64 = chars[start..i].iter().collect::<String>().parse().ok()?;
+            let mut j = i;
+            while j < chars.len() && chars[j] == ' ' {
+                j += 1;
+            }
+            let thousands = j < chars.len()
+                && chars[j].eq_ignore_ascii_case(&'k')
+                && !chars.get(j + 1).is_some_and(|c| c.is_alphabetic());
+            if thousands {
+                amount *= 1000.0;
+                i = j + 1;
+            }
+            if amount > i32::MAX as f64 {
+                return None;
+            }
+            amounts.push(amount.round() as i32);
+        }
+
+        let min = *amounts.first()?;
+        let max = amounts.get(1).copied().unwrap_or(min);
+        if min > max {
+            return None;
+        }
+        Some(SalaryRange { min, max, currency: Self::currency_in(label) })
+    }
+
+    fn currency_in(label: &str) -> Option<String> {
+        let code = label
+            .split(|c: char| !c.is_alphabetic())
+            .find(|word| word.len() == 3 && word.chars().all(|c| c.is_ascii_uppercase()));
+        if let Some(code) = code {
+            return Some(code.to_string());
+        }
+        [('$', "USD"), ('€', "EUR"), ('£', "GBP"), ('₹', "INR")]
+            .iter()
+            .find(|(symbol, _)| label.contains(*symbol))
+            .map(|(_, code)| code.to_string())
+    }
+
+    /// `50000-70000 USD`, the label of a band given as numbers.
+    pub fn label(&self) -> String {
+        match &self.currency {
+            Some(currency) => format!("{}-{} {}", self.min, self.max, currency),
+            None => format!("{}-{}", self.min, self.max),
+        }
+    }
+}
+
+/// Filters of `GET /jobs`. List values are comma-separated (`?job_type=remote,hybrid`) and
+/// compared case-insensitively.
+#[derive(Debug, Default, Deserialize)]
+pub struct JobFilters {
+    pub location: Option<String>,
+    pub job_type: Option<String>,
+    /// Years of experience the job asks for, inclusive bounds.
+    pub experience_min: Option<i32>,
+    pub experience_max: Option<i32>,
+    pub skills: Option<String>,
+    /// `any` (default): the job asks for at least one of `skills`; `all`: for every one of them.
+    pub skills_match: Option<String>,
+    /// Salary band; jobs whose band overlaps it match, jobs without a salary do not.
+    pub salary_min: Option<i32>,
+    pub salary_max: Option<i32>,
+    pub currency: Option<String>,
+    #[serde(default)]
+    pub active_only: bool,
+}
+
+/// Number of jobs with one value of a facet.
+#[derive(Debug, Serialize, FromRow)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: i64,
+}
+
+/// Counts behind the filter chips of `GET /jobs`. Each facet is counted with every filter
+/// applied except its own, so the other values of a chip keep their counts once one is picked.
+#[derive(Debug, Default, Serialize)]
+pub struct JobFacets {
+    pub location: Vec<FacetCount>,
+    pub job_type: Vec<FacetCount>,
+    pub skills: Vec<FacetCount>,
+    pub experience: Vec<FacetCount>,
+    pub currency: Vec<FacetCount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn band(min: i32, max: i32, currency: Option<&str>) -> Option<SalaryRange> {
+        Some(SalaryRange { min, max, currency: currency.map(str::to_string) })
+    }
+
+    #[test]
+    fn test_salary_labels_are_parsed() {
+        assert_eq!(SalaryRange::parse("50000-70000 USD"), band(50000, 70000, Some("USD")));
+        assert_eq!(SalaryRange::parse("$50,000 - $70,000"), band(50000, 70000, Some("USD")));
+        assert_eq!(SalaryRange::parse("50k to 70K EUR"), band(50000, 70000, Some("EUR")));
+        assert_eq!(SalaryRange::parse("45000"), band(45000, 45000, None));
+        assert_eq!(SalaryRange::parse("Competitive"), None);
+        assert_eq!(SalaryRange::parse("70000-50000"), None);
+    }
 }
\ No newline at end of file
@@ -17,7 +17,6 @@ pub struct Post_Likes {
 #[derive(Serialize, Deserialize, FromRow)]
 pub struct Create_Post_Likes {
     pub post_id: Uuid,
-    pub user_id: Uuid,
     pub is_liked: bool,
 }
 
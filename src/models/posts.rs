@@ -25,7 +25,6 @@ pub struct Post {
 #[derive(Deserialize)]
 pub struct CreatePost {
     pub community_id: Uuid,
-    pub author_id: Uuid,
     pub title: serde_json::Value,
     pub content: serde_json::Value,
     pub post_type: String,
@@ -53,7 +52,7 @@ pub struct PostWithRank {
     pub title: Option<serde_json::Value>,
     pub content: Option<serde_json::Value>,
     pub post_type: Option<String>,
-    pub created_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
     pub is_active: Option<bool>,
     pub tags: Option<Vec<String>>,
     pub likes: Option<i32>,
@@ -62,11 +61,10 @@ pub struct PostWithRank {
     pub media_urls: Option<Vec<String>>, // Added
 }
 
+/// `?sort=` of the post lists; paging itself comes from `pagination::PageParams`.
 #[derive(Deserialize)]
-pub struct Pagination {
-    pub limit: Option<i64>,
-    pub offset: Option<i64>,
-    pub sort: Option<String>, 
+pub struct PostSort {
+    pub sort: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, sqlx::Type, Debug, Clone, Copy)]
@@ -4,7 +4,6 @@ use uuid::Uuid;
 #[derive(Deserialize)]
 pub struct SharePostPayload {
     pub post_id: Uuid,
-    pub shared_by: Uuid,
     pub shared_with: Vec<Uuid>,
     pub message: Option<String>,
 }
@@ -5,7 +5,9 @@ use uuid::Uuid;
 use chrono::NaiveDateTime;
 use serde_json::Value;
 
-#[derive(Serialize, Deserialize, FromRow, Debug)]
+use crate::auth::{Principal, PrincipalKind};
+
+#[derive(Deserialize, FromRow, Debug)]
 pub struct User {
     pub id: Uuid,
     pub first_name: String,
@@ -23,9 +25,8 @@ pub struct User {
     pub resume_url: Option<String>,
     pub interests: Option<Vec<String>>,
     pub provider: Option<String>,
-    pub provider_user_id: Option<String>,
-    pub access_token: Option<String>,
-    pub refresh_token: Option<String>,
+    // provider_user_id, access_token and refresh_token are never loaded into a `User`;
+    // see `user_identities` for provider ids.
     pub oauth_email: Option<String>,
     pub college: Option<String>,
     pub batch: Option<String>,
@@ -40,6 +41,9 @@ pub struct User {
     pub hackingon: Option<String>,
     pub learning: Option<String>,
     pub availablefor: Option<String>,
+    pub role: String,
+    pub show_email: bool,
+    pub show_mobile: bool,
 }
 
 #[derive(Deserialize, Debug)] // Added Debug
@@ -91,4 +95,255 @@ pub struct UpdateUser {
     pub hacking_on: Option<String>,
     pub learning: Option<String>,
     pub available_for: Option<String>,        // For JSONB
-}
\ No newline at end of file
+
+    // Privacy settings
+    pub show_email: Option<bool>,
+    pub show_mobile: Option<bool>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RegisterUser {
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct ExchangeCodeRequest {
+    pub code: String,
+}
+
+// ---------------------------------------------------------------------------
+// Response views. A `User` row is never serialized directly: it holds the password
+// hash and OAuth tokens. Pick the view with `UserResponse::for_viewer`.
+// ---------------------------------------------------------------------------
+
+/// What anyone may see. `email` and `mobile` are only present when the user opted in.
+#[derive(Serialize, Debug)]
+pub struct PublicUserView {
+    pub id: Uuid,
+    pub username: String,
+    pub first_name: String,
+    pub last_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mobile: Option<String>,
+    pub organisation: Option<String>,
+    pub bio: Option<Value>,
+    pub profile_pic: Option<String>,
+    pub created_at: Option<NaiveDateTime>,
+    pub interests: Option<Vec<String>>,
+    pub college: Option<String>,
+    pub batch: Option<String>,
+    pub stream: Option<String>,
+    pub linkedin: Option<String>,
+    pub github: Option<String>,
+    pub skills: Option<Vec<String>>,
+    pub work_experiences: Option<Value>,
+    pub projects: Option<Value>,
+    pub pronouns: Option<String>,
+    pub location: Option<String>,
+    pub hackingon: Option<String>,
+    pub learning: Option<String>,
+    pub availablefor: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PrivacySettings {
+    pub show_email: bool,
+    pub show_mobile: bool,
+}
+
+/// What the user sees about themselves.
+#[derive(Serialize, Debug)]
+pub struct SelfUserView {
+    #[serde(flatten)]
+    pub profile: PublicUserView,
+    pub resume_url: Option<String>,
+    pub is_active: Option<bool>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub role: String,
+    pub privacy: PrivacySettings,
+}
+
+/// What platform admins see.
+#[derive(Serialize, Debug)]
+pub struct AdminUserView {
+    #[serde(flatten)]
+    pub account: SelfUserView,
+    pub provider: Option<String>,
+    pub oauth_email: Option<String>,
+}
+
+impl PublicUserView {
+    fn from_user(user: User, show_contact: bool) -> Self {
+        PublicUserView {
+            id: user.id,
+            username: user.username,
+            first_name: user.first_name,
+            last_name: user.last_name,
+            email: (show_contact || user.show_email).then_some(user.email),
+            mobile: user.mobile.filter(|_| show_contact || user.show_mobile),
+            organisation: user.organisation,
+            bio: user.bio,
+            profile_pic: user.profile_pic,
+            created_at: user.created_at,
+            interests: user.interests,
+            college: user.college,
+            batch: user.batch,
+            stream: user.stream,
+            linkedin: user.linkedin,
+            github: user.github,
+            skills: user.skills,
+            work_experiences: user.work_experiences,
+            projects: user.projects,
+            pronouns: user.pronouns,
+            location: user.location,
+            hackingon: user.hackingon,
+            learning: user.learning,
+            availablefor: user.availablefor,
+        }
+    }
+}
+
+impl From<User> for PublicUserView {
+    fn from(user: User) -> Self {
+        PublicUserView::from_user(user, false)
+    }
+}
+
+impl From<User> for SelfUserView {
+    fn from(mut user: User) -> Self {
+        let resume_url = user.resume_url.take();
+        let is_active = user.is_active;
+        let updated_at = user.updated_at;
+        let role = std::mem::take(&mut user.role);
+        let privacy = PrivacySettings {
+            show_email: user.show_email,
+            show_mobile: user.show_mobile,
+        };
+        SelfUserView {
+            profile: PublicUserView::from_user(user, true),
+            resume_url,
+            is_active,
+            updated_at,
+            role,
+            privacy,
+        }
+    }
+}
+
+impl From<User> for AdminUserView {
+    fn from(mut user: User) -> Self {
+        let provider = user.provider.take();
+        let oauth_email = user.oauth_email.take();
+        AdminUserView {
+            account: user.into(),
+            provider,
+            oauth_email,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum UserResponse {
+    Admin(AdminUserView),
+    Owner(SelfUserView),
+    Public(PublicUserView),
+}
+
+impl UserResponse {
+    /// Admins get the admin view, the user gets the self view, everyone else the public view.
+    pub fn for_viewer(user: User, viewer: Option<&Principal>) -> Self {
+        match viewer {
+            Some(principal) if principal.is_admin => UserResponse::Admin(user.into()),
+            Some(principal) if principal.is(PrincipalKind::User, user.id) => UserResponse::Owner(user.into()),
+            _ => UserResponse::Public(user.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            first_name: "Asha".to_string(),
+            last_name: "Rao".to_string(),
+            username: "asha".to_string(),
+            password: "$argon2id$secret".to_string(),
+            mobile: Some("9999999999".to_string()),
+            email: "asha@example.com".to_string(),
+            organisation: None,
+            bio: None,
+            profile_pic: None,
+            created_at: None,
+            updated_at: None,
+            is_active: Some(true),
+            resume_url: Some("/uploads/resume.pdf".to_string()),
+            interests: None,
+            provider: Some("google".to_string()),
+            oauth_email: None,
+            college: None,
+            batch: None,
+            stream: None,
+            linkedin: None,
+            github: None,
+            skills: None,
+            work_experiences: None,
+            projects: None,
+            pronouns: None,
+            location: None,
+            hackingon: None,
+            learning: None,
+            availablefor: None,
+            role: "user".to_string(),
+            show_email: false,
+            show_mobile: true,
+        }
+    }
+
+    #[test]
+    fn test_user_views_hide_secrets_and_private_fields() {
+        let user = sample_user();
+        let owner = Principal {
+            id: user.id,
+            kind: PrincipalKind::User,
+            session_id: Uuid::new_v4(),
+            is_admin: false,
+        };
+
+        let public = serde_json::to_value(UserResponse::for_viewer(sample_user(), None)).unwrap();
+        assert!(public.get("email").is_none());
+        assert_eq!(public["mobile"], "9999999999");
+        assert!(public.get("resume_url").is_none());
+
+        let own = serde_json::to_value(UserResponse::for_viewer(user, Some(&owner))).unwrap();
+        assert_eq!(own["email"], "asha@example.com");
+        assert_eq!(own["privacy"]["show_email"], false);
+        assert!(own.get("provider").is_none());
+
+        for view in [public, own] {
+            for secret in ["password", "access_token", "refresh_token", "provider_user_id"] {
+                assert!(view.get(secret).is_none(), "{secret} leaked");
+            }
+        }
+    }
+}
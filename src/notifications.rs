@@ -0,0 +1,73 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
+
+/// A message to a user, such as `job_closed` or `job_expired` for a job they applied to.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub message: String,
+    pub job_id: Option<Uuid>,
+    pub application_id: Option<Uuid>,
+    pub read_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Query of `GET /notifications`.
+#[derive(Deserialize)]
+pub struct NotificationFilters {
+    #[serde(default)]
+    pub unread: bool,
+}
+
+/// The user's notifications, newest first; only the unread ones when `unread_only`.
+pub async fn list(
+    db: &PgPool,
+    user_id: Uuid,
+    unread_only: bool,
+    page: &PageParams,
+) -> Result<Page<Notification>, ApiError> {
+    let after = page.after()?;
+    let notifications = sqlx::query_as::<_, Notification>(
+        r#"
+        SELECT * FROM notifications
+        WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+          AND ($3::timestamp IS NULL OR (created_at, id) < ($3, $4))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $5
+        "#,
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
+    .fetch_all(db)
+    .await
+    .map_err(ApiError::db("Error fetching notifications"))?;
+
+    Ok(Page::new(notifications, page, |n| Cursor::new(n.created_at, n.id)))
+}
+
+/// Marks one of the user's notifications as read; reading it again keeps the first time.
+pub async fn mark_read(db: &PgPool, user_id: Uuid, id: Uuid) -> Result<Notification, ApiError> {
+    sqlx::query_as::<_, Notification>(
+        r#"
+        UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db("Error updating notification"))?
+    .ok_or_else(|| ApiError::not_found("Notification not found"))
+}
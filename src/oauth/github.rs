@@ -0,0 +1,107 @@
+use futures::future::BoxFuture;
+use oauth2::{basic::BasicClient, AuthType};
+use serde::Deserialize;
+
+use super::{build_client, split_name, OAuthProfile, OAuthProvider};
+use crate::config::OAuthProviderConfig;
+
+const AUTH_URL: &str = "https://github.com/login/oauth/authorize";
+const TOKEN_URL: &str = "https://github.com/login/oauth/access_token";
+const USER_URL: &str = "https://api.github.com/user";
+const EMAILS_URL: &str = "https://api.github.com/user/emails";
+// The GitHub API rejects requests without a User-Agent.
+const USER_AGENT: &str = "PowerBrilliant";
+
+#[derive(Deserialize, Debug)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+    name: Option<String>,
+    avatar_url: Option<String>,
+    html_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+pub struct GitHub {
+    client: BasicClient,
+}
+
+impl GitHub {
+    pub fn new(config: &OAuthProviderConfig) -> Self {
+        GitHub {
+            client: build_client(config, AUTH_URL, TOKEN_URL, AuthType::RequestBody),
+        }
+    }
+}
+
+impl OAuthProvider for GitHub {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    fn client(&self) -> &BasicClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &["read:user", "user:email"]
+    }
+
+    fn profile_url_column(&self) -> Option<&'static str> {
+        Some("github")
+    }
+
+    fn fetch_profile<'a>(
+        &'a self,
+        http: &'a reqwest::Client,
+        access_token: &'a str,
+    ) -> BoxFuture<'a, Result<OAuthProfile, String>> {
+        Box::pin(async move {
+            let user: GitHubUser = get_json(http, USER_URL, access_token).await?;
+            // `/user` only exposes the public email, so take the primary one from `/user/emails`.
+            let emails: Vec<GitHubEmail> = get_json(http, EMAILS_URL, access_token).await?;
+            let email = emails
+                .into_iter()
+                .find(|e| e.primary)
+                .ok_or_else(|| "GitHub account has no primary email".to_string())?;
+
+            let (first_name, last_name) = split_name(user.name.as_deref(), &user.login);
+            Ok(OAuthProfile {
+                provider_user_id: user.id.to_string(),
+                email: email.email,
+                email_verified: email.verified,
+                first_name,
+                last_name,
+                picture: user.avatar_url,
+                profile_url: user.html_url,
+            })
+        })
+    }
+}
+
+async fn get_json<T: serde::de::DeserializeOwned>(
+    http: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+) -> Result<T, String> {
+    let resp = http
+        .get(url)
+        .bearer_auth(access_token)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+    if !resp.status().is_success() {
+        return Err(format!("{} returned status {}", url, resp.status()));
+    }
+    resp.json()
+        .await
+        .map_err(|e| format!("Failed to parse response from {}: {}", url, e))
+}
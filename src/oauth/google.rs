@@ -0,0 +1,82 @@
+use futures::future::BoxFuture;
+use oauth2::{basic::BasicClient, AuthType};
+use serde::Deserialize;
+
+use super::{build_client, OAuthProfile, OAuthProvider};
+use crate::config::OAuthProviderConfig;
+
+const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const USERINFO_URL: &str = "https://www.googleapis.com/oauth2/v2/userinfo";
+
+#[derive(Deserialize, Debug)]
+struct GoogleUser {
+    id: String,
+    email: String,
+    #[serde(default)]
+    verified_email: bool,
+    given_name: Option<String>,
+    family_name: Option<String>,
+    picture: Option<String>,
+}
+
+pub struct Google {
+    client: BasicClient,
+}
+
+impl Google {
+    pub fn new(config: &OAuthProviderConfig) -> Self {
+        Google {
+            client: build_client(config, AUTH_URL, TOKEN_URL, AuthType::BasicAuth),
+        }
+    }
+}
+
+impl OAuthProvider for Google {
+    fn name(&self) -> &'static str {
+        "google"
+    }
+
+    fn client(&self) -> &BasicClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &[
+            "https://www.googleapis.com/auth/userinfo.profile",
+            "https://www.googleapis.com/auth/userinfo.email",
+        ]
+    }
+
+    fn fetch_profile<'a>(
+        &'a self,
+        http: &'a reqwest::Client,
+        access_token: &'a str,
+    ) -> BoxFuture<'a, Result<OAuthProfile, String>> {
+        Box::pin(async move {
+            let resp = http
+                .get(USERINFO_URL)
+                .bearer_auth(access_token)
+                .send()
+                .await
+                .map_err(|e| format!("Request to Google user info endpoint failed: {}", e))?;
+            if !resp.status().is_success() {
+                return Err(format!("Google userinfo API returned status {}", resp.status()));
+            }
+            let user: GoogleUser = resp
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Google user info: {}", e))?;
+
+            Ok(OAuthProfile {
+                provider_user_id: user.id,
+                email: user.email,
+                email_verified: user.verified_email,
+                first_name: user.given_name.unwrap_or_default(),
+                last_name: user.family_name.unwrap_or_default(),
+                picture: user.picture,
+                profile_url: None,
+            })
+        })
+    }
+}
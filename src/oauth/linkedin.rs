@@ -0,0 +1,86 @@
+use futures::future::BoxFuture;
+use oauth2::{basic::BasicClient, AuthType};
+use serde::Deserialize;
+
+use super::{build_client, OAuthProfile, OAuthProvider};
+use crate::config::OAuthProviderConfig;
+
+const AUTH_URL: &str = "https://www.linkedin.com/oauth/v2/authorization";
+const TOKEN_URL: &str = "https://www.linkedin.com/oauth/v2/accessToken";
+const USERINFO_URL: &str = "https://api.linkedin.com/v2/userinfo";
+
+// "Sign In with LinkedIn using OpenID Connect" userinfo response.
+#[derive(Deserialize, Debug)]
+struct LinkedInUser {
+    sub: String,
+    email: String,
+    #[serde(default)]
+    email_verified: bool,
+    given_name: Option<String>,
+    family_name: Option<String>,
+    picture: Option<String>,
+}
+
+pub struct LinkedIn {
+    client: BasicClient,
+}
+
+impl LinkedIn {
+    pub fn new(config: &OAuthProviderConfig) -> Self {
+        LinkedIn {
+            client: build_client(config, AUTH_URL, TOKEN_URL, AuthType::RequestBody),
+        }
+    }
+}
+
+impl OAuthProvider for LinkedIn {
+    fn name(&self) -> &'static str {
+        "linkedin"
+    }
+
+    fn client(&self) -> &BasicClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &["openid", "profile", "email"]
+    }
+
+    // LinkedIn only accepts PKCE for native apps; web apps authenticate with the client secret.
+    fn supports_pkce(&self) -> bool {
+        false
+    }
+
+    fn fetch_profile<'a>(
+        &'a self,
+        http: &'a reqwest::Client,
+        access_token: &'a str,
+    ) -> BoxFuture<'a, Result<OAuthProfile, String>> {
+        Box::pin(async move {
+            let resp = http
+                .get(USERINFO_URL)
+                .bearer_auth(access_token)
+                .send()
+                .await
+                .map_err(|e| format!("Request to LinkedIn user info endpoint failed: {}", e))?;
+            if !resp.status().is_success() {
+                return Err(format!("LinkedIn userinfo API returned status {}", resp.status()));
+            }
+            let user: LinkedInUser = resp
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse LinkedIn user info: {}", e))?;
+
+            // The OIDC userinfo has no public profile URL, so `users.linkedin` is left alone.
+            Ok(OAuthProfile {
+                provider_user_id: user.sub,
+                email: user.email,
+                email_verified: user.email_verified,
+                first_name: user.given_name.unwrap_or_default(),
+                last_name: user.family_name.unwrap_or_default(),
+                picture: user.picture,
+                profile_url: None,
+            })
+        })
+    }
+}
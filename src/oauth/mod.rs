@@ -0,0 +1,222 @@
+mod github;
+mod google;
+mod linkedin;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use oauth2::{
+    basic::BasicClient, AuthType, AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeVerifier,
+    RedirectUrl, TokenUrl,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::config::{Config, OAuthProviderConfig};
+
+/// The account as reported by an OAuth provider, normalised across providers.
+#[derive(Debug)]
+pub struct OAuthProfile {
+    pub provider_user_id: String,
+    pub email: String,
+    /// Only verified emails may be used to link the login to an existing account.
+    pub email_verified: bool,
+    pub first_name: String,
+    pub last_name: String,
+    pub picture: Option<String>,
+    /// Public profile page, stored in the column named by [`OAuthProvider::profile_url_column`].
+    pub profile_url: Option<String>,
+}
+
+pub trait OAuthProvider: Send + Sync {
+    /// Path segment in `/auth/{provider}/...`, also stored in `user_identities.provider`.
+    fn name(&self) -> &'static str;
+
+    fn client(&self) -> &BasicClient;
+
+    fn scopes(&self) -> &'static [&'static str];
+
+    fn supports_pkce(&self) -> bool {
+        true
+    }
+
+    /// `users` column that keeps a link to the public profile on this provider, if any.
+    fn profile_url_column(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn fetch_profile<'a>(
+        &'a self,
+        http: &'a reqwest::Client,
+        access_token: &'a str,
+    ) -> BoxFuture<'a, Result<OAuthProfile, String>>;
+}
+
+/// Every provider enabled in [`Config`], keyed by [`OAuthProvider::name`].
+pub struct OAuthProviders {
+    providers: HashMap<&'static str, Arc<dyn OAuthProvider>>,
+}
+
+impl OAuthProviders {
+    pub fn from_config(config: &Config) -> Self {
+        let mut providers: Vec<Arc<dyn OAuthProvider>> =
+            vec![Arc::new(google::Google::new(&config.google_oauth()))];
+        if let Some(github) = &config.github_oauth {
+            providers.push(Arc::new(github::GitHub::new(github)));
+        }
+        if let Some(linkedin) = &config.linkedin_oauth {
+            providers.push(Arc::new(linkedin::LinkedIn::new(linkedin)));
+        }
+        OAuthProviders::new(providers)
+    }
+
+    /// A fixed set of providers; the tests use it to log in through a local stub provider.
+    pub fn new(providers: Vec<Arc<dyn OAuthProvider>>) -> Self {
+        OAuthProviders {
+            providers: providers.into_iter().map(|p| (p.name(), p)).collect(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn OAuthProvider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.providers.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+fn build_client(
+    config: &OAuthProviderConfig,
+    auth_url: &str,
+    token_url: &str,
+    auth_type: AuthType,
+) -> BasicClient {
+    let auth_url = AuthUrl::new(auth_url.to_string()).expect("Invalid auth URL");
+    let token_url = TokenUrl::new(token_url.to_string()).expect("Invalid token URL");
+    let redirect_url = RedirectUrl::new(config.redirect_url.clone())
+        .expect("Invalid redirect URL");
+
+    BasicClient::new(
+        ClientId::new(config.client_id.clone()),
+        Some(ClientSecret::new(config.client_secret.clone())),
+        auth_url,
+        Some(token_url)
+    )
+    .set_auth_type(auth_type)
+    .set_redirect_uri(redirect_url)
+}
+
+/// Splits a display name into first and last name, falling back to `fallback` for the first name.
+fn split_name(name: Option<&str>, fallback: &str) -> (String, String) {
+    let name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(fallback);
+    match name.split_once(' ') {
+        Some((first, last)) => (first.to_string(), last.trim().to_string()),
+        None => (name.to_string(), String::new()),
+    }
+}
+
+/// How long a started login may take before its state and PKCE verifier expire.
+pub const OAUTH_STATE_TTL_MINUTES: i32 = 10;
+
+/// Cookie that binds the `state` parameter to the browser that started the login.
+pub const OAUTH_STATE_COOKIE: &str = "oauth_state";
+
+/// What was remembered about a login between the redirect to the provider and the callback.
+pub struct AuthorizationState {
+    pub pkce_verifier: PkceCodeVerifier,
+    /// Allowlisted frontend URL requested at login, if any.
+    pub redirect_to: Option<String>,
+    /// The signed-in user who asked to link this provider (`POST /auth/{provider}/link`).
+    pub link_user_id: Option<Uuid>,
+}
+
+/// Remembers the CSRF state, PKCE verifier, requested frontend redirect and, when linking, the
+/// signed-in user of a login that is about to redirect to the provider.
+pub async fn store_authorization_state(
+    db: &PgPool,
+    provider: &str,
+    state: &CsrfToken,
+    pkce_verifier: &PkceCodeVerifier,
+    redirect_to: Option<&str>,
+    link_user_id: Option<Uuid>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!("DELETE FROM oauth_states WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO oauth_states (state, provider, pkce_verifier, redirect_to, expires_at, link_user_id)
+        VALUES ($1, $2, $3, $4, NOW() + make_interval(mins => $5), $6)
+        "#,
+        state.secret(),
+        provider,
+        pkce_verifier.secret(),
+        redirect_to,
+        OAUTH_STATE_TTL_MINUTES,
+        link_user_id
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Consumes the stored state. `None` means the state is unknown, expired, already used
+/// or belongs to another provider.
+pub async fn take_authorization_state(
+    db: &PgPool,
+    provider: &str,
+    state: &str,
+) -> Result<Option<AuthorizationState>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM oauth_states
+        WHERE state = $1 AND provider = $2 AND expires_at > NOW()
+        RETURNING pkce_verifier, redirect_to, link_user_id
+        "#,
+        state,
+        provider
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(row.map(|row| AuthorizationState {
+        pkce_verifier: PkceCodeVerifier::new(row.pkce_verifier),
+        redirect_to: row.redirect_to,
+        link_user_id: row.link_user_id,
+    }))
+}
+
+/// The `state` query parameter must match the cookie set when the login started.
+pub fn state_matches(query_state: &str, cookie_state: Option<&str>) -> bool {
+    match cookie_state {
+        Some(cookie_state) if cookie_state.len() == query_state.len() => cookie_state
+            .bytes()
+            .zip(query_state.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(split_name(Some("Ada King Lovelace"), "ada"), ("Ada".to_string(), "King Lovelace".to_string()));
+        assert_eq!(split_name(Some("  "), "octocat"), ("octocat".to_string(), String::new()));
+        assert_eq!(split_name(None, "octocat"), ("octocat".to_string(), String::new()));
+    }
+
+    #[test]
+    fn test_state_must_match_cookie() {
+        assert!(state_matches("abc123", Some("abc123")));
+        assert!(!state_matches("abc123", Some("abc124")));
+        assert!(!state_matches("abc123", Some("abc")));
+        assert!(!state_matches("abc123", None));
+    }
+}
@@ -0,0 +1,154 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Fractional scores and ranks are multiplied by this and rounded before they go into
+/// `Cursor::ranked`, so a cursor compares as an integer and resumes at an exact position.
+pub const RANK_SCALE: f64 = 1_000_000.0;
+
+/// Position after the last row of a page. Lists are ordered newest first on
+/// `(created_at, id)`; lists with a ranking (e.g. `sort=top`) put the rank in front of that key.
+/// Rankings that change over time also carry `as_of`, the moment the first page was ranked at,
+/// so later pages are ranked the same way.
+///
+/// Clients only ever see the opaque `encode()`d form and send it back as `?cursor=`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(rename = "r", default, skip_serializing_if = "Option::is_none")]
+    pub rank: Option<i64>,
+    #[serde(rename = "a", default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<NaiveDateTime>,
+    #[serde(rename = "t")]
+    pub created_at: NaiveDateTime,
+    #[serde(rename = "i")]
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: NaiveDateTime, id: Uuid) -> Self {
+        Cursor { rank: None, as_of: None, created_at, id }
+    }
+
+    pub fn ranked(rank: i64, created_at: NaiveDateTime, id: Uuid) -> Self {
+        Cursor { rank: Some(rank), as_of: None, created_at, id }
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).expect("cursor serializes");
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    pub fn decode(value: &str) -> Result<Self, ApiError> {
+        URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| ApiError::bad_request("Invalid cursor"))
+    }
+}
+
+/// `?cursor=&limit=` of every paginated list.
+#[derive(Debug, Default, Deserialize)]
+pub struct PageParams {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl PageParams {
+    /// Requested page size, clamped to `1..=MAX_PAGE_SIZE`.
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Rows to fetch: one more than the page size, to know whether another page follows.
+    pub fn fetch_limit(&self) -> i64 {
+        self.limit() + 1
+    }
+
+    /// The decoded cursor, `None` for the first page.
+    pub fn after(&self) -> Result<Option<Cursor>, ApiError> {
+        self.cursor.as_deref().filter(|c| !c.is_empty()).map(Cursor::decode).transpose()
+    }
+}
+
+/// Response envelope of every paginated list.
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> Page<T> {
+    /// Builds the page from up to `params.fetch_limit()` rows; `key` gives the cursor of a row.
+    pub fn new(mut rows: Vec<T>, params: &PageParams, key: impl Fn(&T) -> Cursor) -> Self {
+        let limit = params.limit() as usize;
+        let has_more = rows.len() > limit;
+        rows.truncate(limit);
+        let next_cursor = if has_more { rows.last().map(|row| key(row).encode()) } else { None };
+        Page { items: rows, next_cursor, has_more }
+    }
+
+    /// Converts the items after the cursor has been taken from the raw rows.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            has_more: self.has_more,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_micro_opt(12, 0, second, 123_456).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_round_trips_through_its_opaque_form() {
+        let plain = Cursor::new(at(1), Uuid::new_v4());
+        assert_eq!(Cursor::decode(&plain.encode()).unwrap(), plain);
+
+        let ranked = Cursor::ranked(42, at(2), Uuid::new_v4());
+        assert_eq!(Cursor::decode(&ranked.encode()).unwrap(), ranked);
+
+        assert!(Cursor::decode("not a cursor").is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("{}")).is_err());
+    }
+
+    #[test]
+    fn test_page_reports_whether_more_rows_follow() {
+        let params = PageParams { cursor: None, limit: Some(2) };
+        assert_eq!(params.fetch_limit(), 3);
+
+        let rows: Vec<(NaiveDateTime, Uuid)> = (0..3).map(|s| (at(s), Uuid::new_v4())).collect();
+        let last_kept = rows[1];
+        let page = Page::new(rows, &params, |(t, id)| Cursor::new(*t, *id));
+        assert_eq!(page.items.len(), 2);
+        assert!(page.has_more);
+        assert_eq!(Cursor::decode(page.next_cursor.as_deref().unwrap()).unwrap(), Cursor::new(last_kept.0, last_kept.1));
+
+        let rows: Vec<(NaiveDateTime, Uuid)> = (0..2).map(|s| (at(s), Uuid::new_v4())).collect();
+        let page = Page::new(rows, &params, |(t, id)| Cursor::new(*t, *id));
+        assert!(!page.has_more);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_limit_is_clamped() {
+        assert_eq!(PageParams::default().limit(), DEFAULT_PAGE_SIZE);
+        assert_eq!(PageParams { cursor: None, limit: Some(0) }.limit(), 1);
+        assert_eq!(PageParams { cursor: None, limit: Some(10_000) }.limit(), MAX_PAGE_SIZE);
+    }
+}
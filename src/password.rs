@@ -0,0 +1,87 @@
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use rand::rngs::OsRng;
+
+/// Stored in `users.password` for accounts that sign in through an OAuth provider.
+/// It is not a valid hash, so password login is always refused for these rows.
+pub const OAUTH_PASSWORD_PLACEHOLDER: &str = "!oauth";
+
+// Older Google sign-ups stored this literal instead of the placeholder above.
+const LEGACY_GOOGLE_PLACEHOLDER: &str = "provided_by_google";
+
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PasswordCheck {
+    Valid,
+    /// The row still holds a plaintext password from before hashing was introduced.
+    /// The caller should store a fresh hash once the login succeeds.
+    ValidNeedsRehash,
+    Invalid,
+}
+
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+}
+
+/// Whether the stored password marks an account created through an OAuth login.
+pub fn is_oauth_placeholder(stored: &str) -> bool {
+    stored == OAUTH_PASSWORD_PLACEHOLDER || stored == LEGACY_GOOGLE_PLACEHOLDER
+}
+
+pub fn verify_password(candidate: &str, stored: &str) -> PasswordCheck {
+    if is_oauth_placeholder(stored) {
+        return PasswordCheck::Invalid;
+    }
+
+    if stored.starts_with("$argon2") {
+        return match PasswordHash::new(stored) {
+            Ok(parsed) if Argon2::default().verify_password(candidate.as_bytes(), &parsed).is_ok() => {
+                PasswordCheck::Valid
+            }
+            _ => PasswordCheck::Invalid,
+        };
+    }
+
+    // Legacy plaintext row
+    if constant_time_eq(candidate.as_bytes(), stored.as_bytes()) {
+        PasswordCheck::ValidNeedsRehash
+    } else {
+        PasswordCheck::Invalid
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify() {
+        let hash = hash_password("correct horse").unwrap();
+        assert_ne!(hash, "correct horse");
+        assert_eq!(verify_password("correct horse", &hash), PasswordCheck::Valid);
+        assert_eq!(verify_password("wrong horse", &hash), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn test_legacy_plaintext_needs_rehash() {
+        assert_eq!(verify_password("secure123", "secure123"), PasswordCheck::ValidNeedsRehash);
+        assert_eq!(verify_password("secure12", "secure123"), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn test_oauth_placeholders_never_match() {
+        assert_eq!(verify_password("provided_by_google", "provided_by_google"), PasswordCheck::Invalid);
+        assert_eq!(verify_password("!oauth", OAUTH_PASSWORD_PLACEHOLDER), PasswordCheck::Invalid);
+    }
+}
@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use log::{error, info};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::config::Config;
+use crate::pagination::RANK_SCALE;
+
+// Engagement weights: a comment counts twice a like, sharing a post three times.
+const LIKE_WEIGHT: f64 = 1.0;
+const COMMENT_WEIGHT: f64 = 2.0;
+const SHARE_WEIGHT: f64 = 3.0;
+// hot = engagement / (age in hours + 2) ^ HOT_GRAVITY, for posts younger than HOT_HORIZON_DAYS.
+const HOT_GRAVITY: f64 = 1.5;
+const HOT_HORIZON_DAYS: i32 = 7;
+// trending = engagement where every like, comment and share counts less the older it is,
+// halving every TRENDING_HALF_LIFE_HOURS: how fast a post is gaining engagement right now.
+const TRENDING_HALF_LIFE_HOURS: f64 = 6.0;
+// Trending tags kept per window and scope (global or one community).
+const TRENDING_TAGS_KEPT: i64 = 50;
+
+#[derive(Debug, Serialize)]
+pub struct RefreshSummary {
+    pub scored_posts: u64,
+    pub trending_tags: u64,
+    pub computed_at: NaiveDateTime,
+}
+
+/// Rebuilds `post_scores` and `trending_tags` as of `now`, in one transaction so readers never
+/// see a half-built ranking.
+pub async fn refresh(db: &PgPool, tag_windows: &[i32], now: NaiveDateTime) -> Result<RefreshSummary, sqlx::Error> {
+    // Activity older than this cannot affect any score.
+    let horizon_hours = tag_windows.iter().copied().max().unwrap_or(0).max(HOT_HORIZON_DAYS * 24);
+    let mut tx = db.begin().await?;
+
+    sqlx::query!("DELETE FROM post_scores").execute(&mut *tx).await?;
+    let scored_posts = sqlx::query(&format!(
+        r#"
+        WITH events AS (
+            SELECT post_id, liked_at AS happened_at, {like} AS weight
+            FROM post_likes
+            WHERE is_liked = true AND liked_at > $1 - make_interval(hours => $2)
+            UNION ALL
+            SELECT post_id, created_at, {comment}
+            FROM comments
+            WHERE created_at > $1 - make_interval(hours => $2)
+            UNION ALL
+            -- One share per sharer, however many people they sent the post to.
+            SELECT post_id, MIN(shared_at), {share}
+            FROM shared_posts
+            WHERE shared_at > $1 - make_interval(hours => $2)
+            GROUP BY post_id, shared_by
+        )
+        INSERT INTO post_scores (post_id, hot_score, trending_score, computed_at)
+        SELECT
+            p.id,
+            CASE WHEN p.created_at > $1 - make_interval(days => {hot_days})
+                THEN round({scale} * SUM(e.weight)
+                    / power(GREATEST(EXTRACT(EPOCH FROM ($1 - p.created_at)) / 3600, 0) + 2, {gravity}))
+                ELSE 0
+            END::bigint,
+            round({scale} * SUM(e.weight
+                * power(0.5, GREATEST(EXTRACT(EPOCH FROM ($1 - e.happened_at)) / 3600, 0) / {half_life})))::bigint,
+            $1
+        FROM posts p
+        JOIN events e ON e.post_id = p.id
+        WHERE p.is_active AND e.happened_at <= $1
+        GROUP BY p.id, p.created_at
+        "#,
+        like = LIKE_WEIGHT,
+        comment = COMMENT_WEIGHT,
+        share = SHARE_WEIGHT,
+        hot_days = HOT_HORIZON_DAYS,
+        scale = RANK_SCALE,
+        gravity = HOT_GRAVITY,
+        half_life = TRENDING_HALF_LIFE_HOURS,
+    ))
+    .bind(now)
+    .bind(horizon_hours)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query!("DELETE FROM trending_tags").execute(&mut *tx).await?;
+    let trending_tags = sqlx::query(&format!(
+        r#"
+        WITH events AS (
+            -- Publishing a post counts like one like for its tags.
+            SELECT id AS post_id, created_at AS happened_at, {like} AS weight
+            FROM posts
+            WHERE created_at > $1 - make_interval(hours => $2)
+            UNION ALL
+            SELECT post_id, liked_at, {like}
+            FROM post_likes
+            WHERE is_liked = true AND liked_at > $1 - make_interval(hours => $2)
+            UNION ALL
+            SELECT post_id, created_at, {comment}
+            FROM comments
+            WHERE created_at > $1 - make_interval(hours => $2)
+            UNION ALL
+            SELECT post_id, MIN(shared_at), {share}
+            FROM shared_posts
+            WHERE shared_at > $1 - make_interval(hours => $2)
+            GROUP BY post_id, shared_by
+        ),
+        tagged AS (
+            SELECT w.hours, p.community_id, p.id AS post_id, lower(trim(tag)) AS tag, e.weight
+            FROM unnest($3::int[]) AS w(hours)
+            JOIN events e ON e.happened_at > $1 - make_interval(hours => w.hours) AND e.happened_at <= $1
+            JOIN posts p ON p.id = e.post_id AND p.is_active
+            CROSS JOIN LATERAL unnest(p.tags) AS tag
+            WHERE trim(tag) <> ''
+        ),
+        ranked AS (
+            SELECT
+                hours,
+                CASE WHEN GROUPING(community_id) = 0 THEN community_id END AS community_id,
+                tag,
+                SUM(weight) AS score,
+                COUNT(DISTINCT post_id)::int AS post_count,
+                ROW_NUMBER() OVER (
+                    PARTITION BY hours, GROUPING(community_id), community_id
+                    ORDER BY SUM(weight) DESC, tag
+                ) AS position
+            FROM tagged
+            GROUP BY GROUPING SETS ((hours, tag), (hours, community_id, tag))
+        )
+        INSERT INTO trending_tags (window_hours, community_id, tag, score, post_count, computed_at)
+        SELECT hours, community_id, tag, score, post_count, $1
+        FROM ranked
+        WHERE position <= {kept}
+        "#,
+        like = LIKE_WEIGHT,
+        comment = COMMENT_WEIGHT,
+        share = SHARE_WEIGHT,
+        kept = TRENDING_TAGS_KEPT,
+    ))
+    .bind(now)
+    .bind(horizon_hours)
+    .bind(tag_windows)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok(RefreshSummary { scored_posts, trending_tags, computed_at: now })
+}
+
+/// Starts the background job that refreshes the rankings every `rankings_refresh_seconds`
+/// (first run right away). Does nothing when the interval is 0.
+pub fn spawn_refresher(db: PgPool, config: &Config) {
+    if config.rankings_refresh_seconds == 0 {
+        return;
+    }
+    let windows = config.trending_tag_windows.clone();
+    let period = Duration::from_secs(config.rankings_refresh_seconds);
+    actix_rt::spawn(async move {
+        let mut interval = actix_rt::time::interval(period);
+        loop {
+            interval.tick().await;
+            match refresh(&db, &windows, Utc::now().naive_utc()).await {
+                Ok(summary) => info!(
+                    "Rankings refreshed: {} posts, {} trending tags",
+                    summary.scored_posts, summary.trending_tags
+                ),
+                Err(e) => error!("Failed to refresh rankings: {}", e),
+            }
+        }
+    });
+}
+
+/// `24` -> `24h`, `168` -> `7d`.
+pub fn format_window(hours: i32) -> String {
+    if hours > 24 && hours % 24 == 0 {
+        format!("{}d", hours / 24)
+    } else {
+        format!("{}h", hours)
+    }
+}
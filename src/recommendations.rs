@@ -0,0 +1,260 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::models::job_list::Job_listings;
+use crate::pagination::{Cursor, Page, PageParams};
+
+// Points of the match score (0-100): the share of the job's skills the user has, then
+// location, experience and job type.
+const SKILL_POINTS: i64 = 50;
+const LOCATION_POINTS: i64 = 20;
+const REMOTE_POINTS: i64 = 15;
+const EXPERIENCE_POINTS: i64 = 20;
+const JOB_TYPE_POINTS: i64 = 10;
+
+/// A job recommended to a user, with its match score and why it was picked.
+#[derive(Serialize, sqlx::FromRow)]
+pub struct RecommendedJob {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub job: Job_listings,
+    pub score: i64,
+    /// The job's skills the user has, as the job spells them.
+    pub matched_skills: Vec<String>,
+    #[sqlx(skip)]
+    pub reasons: Vec<String>,
+    #[serde(skip)]
+    pub location_match: bool,
+}
+
+struct Profile {
+    skills: Vec<String>,
+    location: Option<String>,
+    years: f64,
+    job_types: Vec<String>,
+}
+
+/// One page of active jobs for `user_id`, best match first. Jobs they already applied to are
+/// left out, as are jobs that share neither a skill nor the location with their profile.
+pub async fn recommended_jobs(db: &PgPool, user_id: Uuid, page: &PageParams) -> Result<Page<RecommendedJob>, ApiError> {
+    let after = page.after()?;
+    let rank = match after {
+        Some(cursor) => Some(cursor.rank.ok_or_else(|| ApiError::bad_request("Invalid cursor"))?),
+        None => None,
+    };
+
+    let user = sqlx::query!(
+        "SELECT skills, location, work_experiences, availablefor FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(ApiError::db("Error retrieving user"))?
+    .ok_or_else(|| ApiError::not_found("User not found"))?;
+    let profile = Profile {
+        skills: user.skills.unwrap_or_default().iter().map(|s| s.trim().to_lowercase()).collect(),
+        location: user.location.map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty()),
+        years: experience_years(user.work_experiences.as_ref(), Utc::now().date_naive()),
+        job_types: preferred_job_types(user.availablefor.as_deref().unwrap_or_default()),
+    };
+
+    let query = format!(
+        r#"
+        WITH candidates AS (
+            SELECT j.*,
+                ARRAY(SELECT s FROM unnest(COALESCE(j.skills, '{{}}')) s WHERE lower(s) = ANY($2)) AS matched_skills,
+                $3::text IS NOT NULL AND j.location <> '' AND (
+                    strpos(lower(j.location), $3) > 0 OR strpos($3, lower(j.location)) > 0
+                ) AS location_match
+            FROM job_listings j
+            WHERE COALESCE(j.is_active, true)
+              AND NOT EXISTS(SELECT 1 FROM job_applications a WHERE a.job_id = j.id AND a.applicant_id = $1)
+        ),
+        scored AS (
+            SELECT *,
+                (CASE WHEN cardinality(skills) > 0
+                    THEN {skill} * cardinality(matched_skills) / cardinality(skills) ELSE 0 END
+                 + CASE WHEN location_match THEN {location} WHEN job_type = 'remote' THEN {remote} ELSE 0 END
+                 + CASE WHEN COALESCE(min_experience, 0) <= $4 THEN {experience}
+                        WHEN min_experience <= $4 + 1 THEN {experience} / 2 ELSE 0 END
+                 + CASE WHEN job_type = ANY($5) THEN {job_type} ELSE 0 END
+                )::bigint AS score
+            FROM candidates
+            WHERE cardinality(matched_skills) > 0 OR location_match
+        )
+        SELECT * FROM scored
+        WHERE $6::bigint IS NULL OR (score, created_at, id) < ($6, $7, $8)
+        ORDER BY score DESC, created_at DESC, id DESC
+        LIMIT $9
+        "#,
+        skill = SKILL_POINTS,
+        location = LOCATION_POINTS,
+        remote = REMOTE_POINTS,
+        experience = EXPERIENCE_POINTS,
+        job_type = JOB_TYPE_POINTS,
+    );
+
+    let jobs = sqlx::query_as::<_, RecommendedJob>(&query)
+        .bind(user_id)
+        .bind(&profile.skills)
+        .bind(&profile.location)
+        .bind(profile.years)
+        .bind(&profile.job_types)
+        .bind(rank)
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(page.fetch_limit())
+        .fetch_all(db)
+        .await
+        .map_err(ApiError::db("Error recommending jobs"))?;
+
+    Ok(Page::new(jobs, page, |rec| Cursor::ranked(rec.score, rec.job.created_at, rec.job.id))
+        .map(|mut rec| {
+            rec.reasons = reasons(&rec, &profile);
+            rec
+        }))
+}
+
+fn reasons(rec: &RecommendedJob, profile: &Profile) -> Vec<String> {
+    let job = &rec.job;
+    let mut reasons = Vec::new();
+    let skill_count = job.skills.as_ref().map_or(0, Vec::len);
+    if skill_count > 0 {
+        reasons.push(format!("matches {} of {} skills", rec.matched_skills.len(), skill_count));
+    }
+    if rec.location_match {
+        reasons.push(format!("located in {}", job.location));
+    } else if job.job_type.as_deref() == Some("remote") {
+        reasons.push("remote, open to any location".to_string());
+    }
+    let years = profile.years.floor() as i32;
+    match job.min_experience.unwrap_or(0) {
+        0 => reasons.push("no experience required".to_string()),
+        required if years >= required => {
+            reasons.push(format!("asks for {} years of experience, you have {}", required, years))
+        }
+        required => reasons.push(format!("asks for {} years of experience, you have {} so far", required, years)),
+    }
+    if let Some(job_type) = &job.job_type
+        && profile.job_types.contains(job_type)
+    {
+        reasons.push(format!("{}, as you prefer", job_type));
+    }
+    reasons
+}
+
+/// Job types the user is open to, read from the free-text `availablefor` of their profile
+/// ("Remote or hybrid roles" -> `["remote", "hybrid"]`).
+fn preferred_job_types(available_for: &str) -> Vec<String> {
+    let text = available_for.to_lowercase();
+    let mut types = Vec::new();
+    if text.contains("remote") {
+        types.push("remote".to_string());
+    }
+    if text.contains("hybrid") {
+        types.push("hybrid".to_string());
+    }
+    if ["onsite", "on-site", "on site", "office", "in person", "in-person"].iter().any(|w| text.contains(w)) {
+        types.push("onsite".to_string());
+    }
+    types
+}
+
+/// Years of work in the profile's `work_experiences`: a list of entries with either a number of
+/// `years`, or a `start_date` and `end_date` (also `startDate`/`endDate` or `from`/`to`; a missing
+/// or empty end means the job is ongoing). Dates are `YYYY`, `YYYY-MM` or `YYYY-MM-DD`.
+/// Overlapping periods count once.
+pub fn experience_years(work_experiences: Option<&Value>, today: NaiveDate) -> f64 {
+    let Some(entries) = work_experiences.and_then(Value::as_array) else {
+        return 0.0;
+    };
+    let this_month = today.year() * 12 + today.month0() as i32;
+    let mut stated_years = 0.0;
+    let mut periods = Vec::new();
+    for entry in entries {
+        if let Some(years) = entry.get("years").and_then(Value::as_f64) {
+            stated_years += years.max(0.0);
+            continue;
+        }
+        let field = |names: &[&str]| {
+            names.iter().find_map(|name| entry.get(*name).and_then(Value::as_str)).filter(|s| !s.trim().is_empty())
+        };
+        let Some(start) = field(&["start_date", "startDate", "from"]).and_then(month_index) else {
+            continue;
+        };
+        let end = field(&["end_date", "endDate", "to"]).and_then(month_index).unwrap_or(this_month);
+        if end >= start {
+            periods.push((start, end.min(this_month)));
+        }
+    }
+
+    periods.sort();
+    let mut months = 0;
+    let mut covered_until = i32::MIN;
+    for (start, end) in periods {
+        let start = start.max(covered_until);
+        if end > start {
+            months += end - start;
+            covered_until = end;
+        }
+    }
+    stated_years + months as f64 / 12.0
+}
+
+/// `"2021-03"` -> months since year 0; a bare year counts from January. Years outside
+/// 1900..=2100 are typos and read as no date, which also keeps the month arithmetic in range.
+fn month_index(date: &str) -> Option<i32> {
+    let mut parts = date.trim().split('-');
+    let year: i32 = parts.next()?.parse().ok().filter(|y| (1900..=2100).contains(y))?;
+    let month: i32 = match parts.next() {
+        Some(month) => month.parse().ok().filter(|m| (1..=12).contains(m))?,
+        None => 1,
+    };
+    Some(year * 12 + month - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_experience_is_summed_without_overlaps() {
+        let today = NaiveDate::from_ymd_opt(2024, 7, 15).unwrap();
+        let work = json!([
+            { "company": "A", "start_date": "2018-01", "end_date": "2020-01" },
+            { "company": "B", "startDate": "2019-01-10", "endDate": "2021-01-31" },
+            { "company": "C", "from": "2022", "to": "" },
+            { "company": "D", "years": 1.5 },
+            { "company": "no dates" }
+        ]);
+        // 2018-01..2021-01 is 3 years, 2022-01..2024-07 another 2.5, plus 1.5 stated.
+        assert_eq!(experience_years(Some(&work), today), 7.0);
+        assert_eq!(experience_years(None, today), 0.0);
+        assert_eq!(experience_years(Some(&json!({ "years": 3 })), today), 0.0);
+    }
+
+    #[test]
+    fn test_implausible_years_are_ignored() {
+        let today = NaiveDate::from_ymd_opt(2024, 7, 15).unwrap();
+        let work = json!([
+            { "company": "A", "start_date": "999999999-01", "end_date": "2020-01" },
+            { "company": "B", "start_date": "2147483647" },
+            { "company": "C", "start_date": "1066-10", "end_date": "2023-07" },
+            { "company": "D", "start_date": "2020-07", "end_date": "999999999-12" }
+        ]);
+        // Only D counts, and a nonsense end date means it is still ongoing.
+        assert_eq!(experience_years(Some(&work), today), 4.0);
+    }
+
+    #[test]
+    fn test_job_types_are_read_from_free_text() {
+        assert_eq!(preferred_job_types("Remote or hybrid roles"), vec!["remote", "hybrid"]);
+        assert_eq!(preferred_job_types("Office work, full time"), vec!["onsite"]);
+        assert!(preferred_job_types("Freelance").is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+use std::path::{Path, PathBuf};
+
+use actix_web::web::{self, BytesMut};
+use chrono::NaiveDateTime;
+use futures::stream::StreamExt;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams};
+
+/// Largest resume accepted.
+pub const MAX_RESUME_BYTES: usize = 10 * 1024 * 1024;
+
+// Accepted formats, recognized from the file content rather than its name.
+const PDF: &str = "application/pdf";
+const DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// An uploaded resume. The file itself is only served through `GET /resumes/{id}/file`.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Resume {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub content_type: String,
+    pub size_bytes: i32,
+    #[serde(skip)]
+    pub storage_key: String,
+    pub in_library: bool,
+    pub created_at: NaiveDateTime,
+    #[sqlx(skip)]
+    pub url: String,
+}
+
+impl Resume {
+    fn with_url(mut self) -> Self {
+        self.url = url(self.id);
+        self
+    }
+}
+
+/// Where the file of a resume can be downloaded; what applications store as `resume_url`.
+pub fn url(id: Uuid) -> String {
+    format!("/resumes/{}/file", id)
+}
+
+pub fn path(dir: &str, resume: &Resume) -> PathBuf {
+    Path::new(dir).join(&resume.storage_key)
+}
+
+/// Reads an upload body, refusing anything over [`MAX_RESUME_BYTES`].
+pub async fn read_upload(mut payload: web::Payload) -> Result<BytesMut, ApiError> {
+    let mut bytes = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| ApiError::bad_request(format!("Error reading upload: {}", e)))?;
+        if bytes.len() + chunk.len() > MAX_RESUME_BYTES {
+            return Err(ApiError::bad_request(format!(
+                "Resumes can be at most {} MB",
+                MAX_RESUME_BYTES / (1024 * 1024)
+            )));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// The file extension of a resume content type; only PDF and DOCX have one.
+pub fn extension(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        PDF => Some("pdf"),
+        DOCX => Some("docx"),
+        _ => None,
+    }
+}
+
+/// The content type and file extension of a resume, sniffed with `infer`; only PDF and DOCX.
+pub fn sniff(bytes: &[u8]) -> Result<(&'static str, &'static str), ApiError> {
+    let kind = infer::get(bytes).ok_or_else(|| ApiError::bad_request("Unrecognized file type"))?;
+    let extension = extension(kind.mime_type())
+        .ok_or_else(|| ApiError::bad_request("Resumes must be PDF or DOCX files"))?;
+    Ok((kind.mime_type(), extension))
+}
+
+/// Saves the file in `dir` and records it for `user_id`. `name` defaults to "Resume N", N
+/// counting the user's uploads.
+pub async fn store(
+    conn: &mut PgConnection,
+    dir: &str,
+    user_id: Uuid,
+    name: Option<&str>,
+    bytes: &[u8],
+    in_library: bool,
+) -> Result<Resume, ApiError> {
+    let (content_type, extension) = sniff(bytes)?;
+    let id = Uuid::new_v4();
+    let storage_key = format!("{}.{}", id, extension);
+    let name = match name.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(name) => name.to_string(),
+        None => {
+            let count = sqlx::query_scalar!(r#"SELECT count(*) AS "count!" FROM resumes WHERE user_id = $1"#, user_id)
+                .fetch_one(&mut *conn)
+                .await
+                .map_err(ApiError::db("Error saving resume"))?;
+            format!("Resume {}", count + 1)
+        }
+    };
+
+    std::fs::create_dir_all(dir).map_err(storage_error)?;
+    let file_path = Path::new(dir).join(&storage_key);
+    std::fs::write(&file_path, bytes).map_err(storage_error)?;
+
+    let resume = sqlx::query_as::<_, Resume>(
+        r#"
+        INSERT INTO resumes (id, user_id, name, content_type, size_bytes, storage_key, in_library)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(name)
+    .bind(content_type)
+    .bind(bytes.len() as i32)
+    .bind(&storage_key)
+    .bind(in_library)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(ApiError::db("Error saving resume"));
+    if resume.is_err() {
+        std::fs::remove_file(&file_path).ok();
+    }
+    resume.map(Resume::with_url)
+}
+
+pub async fn find(db: &PgPool, id: Uuid) -> Result<Resume, ApiError> {
+    sqlx::query_as::<_, Resume>("SELECT * FROM resumes WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .map_err(ApiError::db("Error retrieving resume"))?
+        .map(Resume::with_url)
+        .ok_or_else(|| ApiError::not_found("Resume not found"))
+}
+
+/// The user's resume library, newest first.
+pub async fn library(db: &PgPool, user_id: Uuid, page: &PageParams) -> Result<Page<Resume>, ApiError> {
+    let after = page.after()?;
+    let resumes = sqlx::query_as::<_, Resume>(
+        r#"
+        SELECT * FROM resumes
+        WHERE user_id = $1 AND in_library
+          AND ($2::timestamp IS NULL OR (created_at, id) < ($2, $3))
+        ORDER BY created_at DESC, id DESC
+        LIMIT $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(after.map(|c| c.created_at))
+    .bind(after.map(|c| c.id))
+    .bind(page.fetch_limit())
+    .fetch_all(db)
+    .await
+    .map_err(ApiError::db("Error fetching resumes"))?;
+
+    Ok(Page::new(resumes, page, |r| Cursor::new(r.created_at, r.id)).map(Resume::with_url))
+}
+
+/// Takes a resume out of the user's library. The file stays for the applications that use it.
+pub async fn remove_from_library(db: &PgPool, user_id: Uuid, id: Uuid) -> Result<(), ApiError> {
+    let removed = sqlx::query!(
+        "UPDATE resumes SET in_library = FALSE WHERE id = $1 AND user_id = $2 AND in_library",
+        id,
+        user_id
+    )
+    .execute(db)
+    .await
+    .map_err(ApiError::db("Error removing resume"))?;
+    if removed.rows_affected() == 0 {
+        return Err(ApiError::not_found("Resume not found"));
+    }
+    Ok(())
+}
+
+fn storage_error(e: std::io::Error) -> ApiError {
+    eprintln!("Error saving resume: {:?}", e);
+    ApiError::internal("Error saving resume")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_pdf_and_docx_are_accepted() {
+        assert_eq!(sniff(b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n").unwrap(), (PDF, "pdf"));
+        assert!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_err());
+        assert!(sniff(b"just some text").is_err());
+    }
+}
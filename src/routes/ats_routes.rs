@@ -0,0 +1,17 @@
+use actix_web::web;
+use crate::handlers::ats_handler::{
+    add_application_note, bulk_update_status, get_application_notes, get_job_applicants, get_job_funnel,
+    rate_application,
+};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/ats")
+            .route("/jobs/{job_id}/applications", web::get().to(get_job_applicants))
+            .route("/jobs/{job_id}/applications/status", web::patch().to(bulk_update_status))
+            .route("/jobs/{job_id}/funnel", web::get().to(get_job_funnel))
+            .route("/applications/{id}/notes", web::get().to(get_application_notes))
+            .route("/applications/{id}/notes", web::post().to(add_application_note))
+            .route("/applications/{id}/rating", web::put().to(rate_application)),
+    );
+}
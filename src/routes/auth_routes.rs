@@ -0,0 +1,12 @@
+use actix_web::web;
+use crate::handlers::auth_handler::{register, login, business_login, exchange_code, refresh, logout, logout_all};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/auth/register", web::post().to(register))
+       .route("/auth/login", web::post().to(login))
+       .route("/auth/business/login", web::post().to(business_login))
+       .route("/auth/exchange", web::post().to(exchange_code))
+       .route("/auth/refresh", web::post().to(refresh))
+       .route("/auth/logout", web::post().to(logout))
+       .route("/auth/logout-all", web::post().to(logout_all));
+}
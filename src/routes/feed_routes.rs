@@ -0,0 +1,10 @@
+use actix_web::web;
+use crate::handlers::feed_handler::{get_feed, mark_posts_seen};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/feed")
+            .route("/seen", web::post().to(mark_posts_seen))
+            .route("/{user_id}", web::get().to(get_feed)),
+    );
+}
@@ -4,7 +4,11 @@ use crate::handlers::job_application_handler::{
     get_applications_for_job,
     get_applied_jobs_for_user,
     get_applied_jobs_count_for_user,
-    get_application_for_job_and_user
+    get_application_for_job_and_user,
+    get_application_history,
+    update_application_status,
+    upload_application_resume,
+    withdraw_job_application,
 
 };
 // use crate::handlers::joblist_handlers::get_joblist;
@@ -22,6 +26,10 @@ pub fn config(cfg: &mut web::ServiceConfig) {
              .route("/{user_id}", web::get().to(get_applied_jobs_for_user))
             .route("/count/{user_id}", web::get().to(get_applied_jobs_count_for_user))
             .route("job/{job_id}/user/{user_id}", web::get().to(get_application_for_job_and_user))
+            .route("/{id}/status", web::patch().to(update_application_status))
+            .route("/{id}/history", web::get().to(get_application_history))
+            .route("/{id}/withdraw", web::post().to(withdraw_job_application))
+            .route("/{id}/resume", web::put().to(upload_application_resume))
     );
     ;
 }
@@ -5,6 +5,7 @@ use crate::handlers::joblist_handlers::delete_job_by_id;
 use crate::handlers::joblist_handlers::update_job_by_id;
 use crate::handlers::joblist_handlers::get_job_by_id;
 use crate::handlers::joblist_handlers::get_jobs_by_posted_id;
+use crate::handlers::joblist_handlers::get_recommended_jobs;
 
 
 
@@ -17,6 +18,7 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .route("/update/{id}", web::put().to(update_job_by_id))
             .route("/{id}", web::get().to(get_job_by_id))
             .route("/by-poster/{posted_id}", web::get().to(get_jobs_by_posted_id))
+            .route("/recommended/{user_id}", web::get().to(get_recommended_jobs))
 
             
     );
@@ -10,4 +10,10 @@ pub mod post_likes_routes;
 pub mod follower_routes;
 pub mod business_accounts_routes;
 pub mod share_routes;
-pub mod google_routes;
+pub mod oauth_routes;
+pub mod auth_routes;
+pub mod feed_routes;
+pub mod search_routes;
+pub mod ats_routes;
+pub mod resume_routes;
+pub mod notification_routes;
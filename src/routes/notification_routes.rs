@@ -0,0 +1,10 @@
+use actix_web::web;
+use crate::handlers::notification_handler::{get_notifications, mark_notification_read};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/notifications")
+            .route("", web::get().to(get_notifications))
+            .route("/{id}/read", web::post().to(mark_notification_read)),
+    );
+}
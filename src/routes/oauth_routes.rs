@@ -0,0 +1,8 @@
+use actix_web::web;
+use crate::handlers::oauth_handler::{oauth_login, oauth_link, oauth_callback};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(oauth_login)
+       .service(oauth_link)
+       .service(oauth_callback);
+}
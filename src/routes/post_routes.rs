@@ -1,11 +1,12 @@
 use actix_web::web;
-use crate::handlers::post_handlers::{get_posts, create_post, get_posts_by_user_interest,  get_tags_by_post_id,delete_post_by_id, update_post_by_id, get_post_by_id, get_posts_by_author_id, upload_media};
+use crate::handlers::post_handlers::{get_posts, create_post, get_posts_by_user_interest,  get_tags_by_post_id,delete_post_by_id, update_post_by_id, get_post_by_id, get_posts_by_author_id, upload_media, get_trending_tags};
 
 pub fn config(cfg: &mut web::ServiceConfig) {
     cfg.service(
         web::scope("/post")
             .route("/create", web::post().to(create_post))
             .route("/tags", web::get().to(get_tags_by_post_id))
+            .route("/trending_tags", web::get().to(get_trending_tags))
             .route("/upload", web::post().to(upload_media)) 
             .route("", web::get().to(get_posts))
             .route("/{id}", web::get().to(get_post_by_id))  
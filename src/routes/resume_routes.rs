@@ -0,0 +1,12 @@
+use actix_web::web;
+use crate::handlers::resume_handler::{delete_resume, get_resume_file, get_resumes, upload_resume};
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/resumes")
+            .route("", web::post().to(upload_resume))
+            .route("", web::get().to(get_resumes))
+            .route("/{id}", web::delete().to(delete_resume))
+            .route("/{id}/file", web::get().to(get_resume_file)),
+    );
+}
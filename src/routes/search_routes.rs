@@ -0,0 +1,6 @@
+use actix_web::web;
+use crate::handlers::search_handler::search;
+
+pub fn config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/search", web::get().to(search));
+}
@@ -0,0 +1,208 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::postgres::PgRow;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::pagination::{Cursor, Page, PageParams, RANK_SCALE};
+
+/// Values of `/search?type=`.
+pub const SEARCH_TYPES: [&str; 4] = ["posts", "users", "communities", "jobs"];
+
+// ts_headline options: short fields come back whole, long ones as the best fragments.
+const WHOLE_FIELD: &str = "StartSel=<mark>, StopSel=</mark>, HighlightAll=true";
+const FRAGMENTS: &str = "StartSel=<mark>, StopSel=</mark>, MaxFragments=2, MaxWords=20, MinWords=5";
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PostHit {
+    pub id: Uuid,
+    pub community_id: Uuid,
+    pub author_id: Uuid,
+    pub title: serde_json::Value,
+    pub post_type: String,
+    pub tags: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub rank: f64,
+    /// `title` and `content` with every language key kept and the matches wrapped in `<mark>`.
+    pub highlights: serde_json::Value,
+    #[serde(skip)]
+    pub rank_key: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserHit {
+    pub id: Uuid,
+    pub username: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub profile_pic: Option<String>,
+    pub organisation: Option<String>,
+    pub skills: Option<Vec<String>>,
+    pub created_at: NaiveDateTime,
+    pub rank: f64,
+    pub highlights: serde_json::Value,
+    #[serde(skip)]
+    pub rank_key: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CommunityHit {
+    pub id: Uuid,
+    pub name: String,
+    pub banner_url: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub rank: f64,
+    pub highlights: serde_json::Value,
+    #[serde(skip)]
+    pub rank_key: i64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct JobHit {
+    pub id: Uuid,
+    pub posted_by: Uuid,
+    pub organisation: String,
+    pub title: serde_json::Value,
+    pub location: String,
+    pub job_type: Option<String>,
+    pub skills: Option<Vec<String>>,
+    pub created_at: NaiveDateTime,
+    pub rank: f64,
+    pub highlights: serde_json::Value,
+    #[serde(skip)]
+    pub rank_key: i64,
+}
+
+/// Best matching active posts for `q` (web search syntax: `"exact phrase"`, `or`, `-word`).
+pub async fn search_posts(db: &PgPool, q: &str, page: &PageParams) -> Result<Page<PostHit>, ApiError> {
+    let columns = format!(
+        r#"t.id, t.community_id, t.author_id, t.title, t.post_type, COALESCE(t.tags, '{{}}') AS tags,
+        jsonb_build_object(
+            'title', ts_headline('simple', COALESCE(t.title, '{{}}'), q.query, '{whole}'),
+            'content', ts_headline('simple', COALESCE(t.content, '{{}}'), q.query, '{fragments}')
+        ) AS highlights"#,
+        whole = WHOLE_FIELD,
+        fragments = FRAGMENTS,
+    );
+    search_in(db, "posts", "t.is_active", &columns, q, page, |hit: &PostHit| {
+        Cursor::ranked(hit.rank_key, hit.created_at, hit.id)
+    })
+    .await
+}
+
+/// Best matching active users by name, username, skills and bio.
+pub async fn search_users(db: &PgPool, q: &str, page: &PageParams) -> Result<Page<UserHit>, ApiError> {
+    let columns = format!(
+        r#"t.id, t.username, t.first_name, t.last_name, t.profile_pic, t.organisation, t.skills,
+        jsonb_build_object(
+            'name', ts_headline('simple', t.first_name || ' ' || t.last_name, q.query, '{whole}'),
+            'username', ts_headline('simple', t.username, q.query, '{whole}'),
+            'skills', ts_headline('simple', COALESCE(array_to_string(t.skills, ', '), ''), q.query, '{whole}'),
+            'bio', ts_headline('simple', COALESCE(t.bio, '{{}}'), q.query, '{fragments}')
+        ) AS highlights"#,
+        whole = WHOLE_FIELD,
+        fragments = FRAGMENTS,
+    );
+    search_in(db, "users", "COALESCE(t.is_active, true)", &columns, q, page, |hit: &UserHit| {
+        Cursor::ranked(hit.rank_key, hit.created_at, hit.id)
+    })
+    .await
+}
+
+/// Best matching active public communities by name and description.
+pub async fn search_communities(db: &PgPool, q: &str, page: &PageParams) -> Result<Page<CommunityHit>, ApiError> {
+    let columns = format!(
+        r#"t.id, t.name, t.banner_url,
+        jsonb_build_object(
+            'name', ts_headline('simple', t.name, q.query, '{whole}'),
+            'description', ts_headline('simple', COALESCE(t.description, '{{}}'), q.query, '{fragments}')
+        ) AS highlights"#,
+        whole = WHOLE_FIELD,
+        fragments = FRAGMENTS,
+    );
+    let visible = "COALESCE(t.is_active, true) AND COALESCE(t.is_public, true)";
+    search_in(db, "communities", visible, &columns, q, page, |hit: &CommunityHit| {
+        Cursor::ranked(hit.rank_key, hit.created_at, hit.id)
+    })
+    .await
+}
+
+/// Best matching active jobs by title, skills, organisation and description.
+pub async fn search_jobs(db: &PgPool, q: &str, page: &PageParams) -> Result<Page<JobHit>, ApiError> {
+    let columns = format!(
+        r#"t.id, t.posted_by, t.organisation, t.title, t.location, t.job_type, t.skills,
+        jsonb_build_object(
+            'title', ts_headline('simple', t.title, q.query, '{whole}'),
+            'skills', ts_headline('simple', COALESCE(array_to_string(t.skills, ', '), ''), q.query, '{whole}'),
+            'description', ts_headline('simple', t.description, q.query, '{fragments}')
+        ) AS highlights"#,
+        whole = WHOLE_FIELD,
+        fragments = FRAGMENTS,
+    );
+    search_in(db, "job_listings", "COALESCE(t.is_active, true)", &columns, q, page, |hit: &JobHit| {
+        Cursor::ranked(hit.rank_key, hit.created_at, hit.id)
+    })
+    .await
+}
+
+/// Runs one page of a search over `table`: matches are found through the GIN index on
+/// `search_vector` and ranked (title-like fields weigh most); highlights are only built for the
+/// rows of the page.
+async fn search_in<T>(
+    db: &PgPool,
+    table: &str,
+    visible: &str,
+    columns: &str,
+    q: &str,
+    page: &PageParams,
+    key: impl Fn(&T) -> Cursor,
+) -> Result<Page<T>, ApiError>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    let after = page.after()?;
+    let rank = match after {
+        Some(cursor) => Some(cursor.rank.ok_or_else(|| ApiError::bad_request("Invalid cursor"))?),
+        None => None,
+    };
+
+    let query = format!(
+        r#"
+        WITH q AS (
+            SELECT websearch_to_tsquery('simple', $1) AS query
+        ),
+        hits AS (
+            -- Normalization 1 divides by the log of the document length, so long posts do not
+            -- win just by repeating a word.
+            SELECT t.id, t.created_at, round({scale} * ts_rank_cd(t.search_vector, q.query, 1)::float8)::bigint AS rank_key
+            FROM {table} t, q
+            WHERE t.search_vector @@ q.query AND {visible}
+        ),
+        page AS (
+            SELECT * FROM hits
+            WHERE $2::bigint IS NULL OR (rank_key, created_at, id) < ($2, $3, $4)
+            ORDER BY rank_key DESC, created_at DESC, id DESC
+            LIMIT $5
+        )
+        SELECT {columns}, page.created_at, page.rank_key, page.rank_key::float8 / {scale} AS rank
+        FROM page
+        JOIN {table} t ON t.id = page.id
+        CROSS JOIN q
+        ORDER BY page.rank_key DESC, page.created_at DESC, page.id DESC
+        "#,
+        scale = RANK_SCALE,
+    );
+
+    let hits = sqlx::query_as::<_, T>(&query)
+        .bind(q)
+        .bind(rank)
+        .bind(after.map(|c| c.created_at))
+        .bind(after.map(|c| c.id))
+        .bind(page.fetch_limit())
+        .fetch_all(db)
+        .await
+        .map_err(ApiError::db("Error searching"))?;
+
+    Ok(Page::new(hits, page, key))
+}
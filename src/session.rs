@@ -0,0 +1,226 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::PrincipalKind;
+use crate::config::Config;
+
+/// A freshly created or rotated session. `refresh_token` is the only copy of the
+/// raw token; the database keeps just its hash.
+pub struct IssuedSession {
+    pub id: Uuid,
+    pub kind: PrincipalKind,
+    pub principal_id: Uuid,
+    pub refresh_token: String,
+}
+
+pub enum RefreshOutcome {
+    Rotated(IssuedSession),
+    /// An already rotated token was presented again. The session has been revoked,
+    /// since either the client or an attacker is holding a stolen copy.
+    Reused,
+    Invalid,
+}
+
+fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn hash_refresh_token(token: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(token.as_bytes()))
+}
+
+pub async fn start_session(
+    db: &PgPool,
+    config: &Config,
+    kind: PrincipalKind,
+    principal_id: Uuid,
+    user_agent: Option<&str>,
+) -> Result<IssuedSession, sqlx::Error> {
+    let refresh_token = generate_refresh_token();
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO sessions (id, principal_id, principal_type, refresh_token_hash, user_agent, expires_at)
+        VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(days => $6))
+        RETURNING id
+        "#,
+        Uuid::new_v4(),
+        principal_id,
+        kind.as_str(),
+        hash_refresh_token(&refresh_token),
+        user_agent,
+        config.refresh_token_ttl_days as i32
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(IssuedSession {
+        id,
+        kind,
+        principal_id,
+        refresh_token,
+    })
+}
+
+/// Swaps the presented refresh token for a new one and slides the session expiry.
+pub async fn rotate_session(
+    db: &PgPool,
+    config: &Config,
+    refresh_token: &str,
+) -> Result<RefreshOutcome, sqlx::Error> {
+    let presented_hash = hash_refresh_token(refresh_token);
+    let new_token = generate_refresh_token();
+
+    let rotated = sqlx::query!(
+        r#"
+        UPDATE sessions
+        SET previous_token_hash = refresh_token_hash,
+            refresh_token_hash = $1,
+            last_used_at = NOW(),
+            expires_at = NOW() + make_interval(days => $2)
+        WHERE refresh_token_hash = $3 AND revoked_at IS NULL AND expires_at > NOW()
+        RETURNING id, principal_id, principal_type
+        "#,
+        hash_refresh_token(&new_token),
+        config.refresh_token_ttl_days as i32,
+        presented_hash
+    )
+    .fetch_optional(db)
+    .await?;
+
+    if let Some(row) = rotated {
+        let Some(kind) = PrincipalKind::from_db(&row.principal_type) else {
+            return Ok(RefreshOutcome::Invalid);
+        };
+        return Ok(RefreshOutcome::Rotated(IssuedSession {
+            id: row.id,
+            kind,
+            principal_id: row.principal_id,
+            refresh_token: new_token,
+        }));
+    }
+
+    let reused = sqlx::query_scalar!(
+        r#"
+        UPDATE sessions SET revoked_at = NOW()
+        WHERE previous_token_hash = $1 AND revoked_at IS NULL
+        RETURNING id
+        "#,
+        presented_hash
+    )
+    .fetch_optional(db)
+    .await?;
+
+    Ok(match reused {
+        Some(_) => RefreshOutcome::Reused,
+        None => RefreshOutcome::Invalid,
+    })
+}
+
+pub async fn revoke_session(db: &PgPool, session_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE sessions SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL",
+        session_id
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Revokes every open session of the principal ("log out of all devices").
+pub async fn revoke_all_sessions(
+    db: &PgPool,
+    kind: PrincipalKind,
+    principal_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sessions SET revoked_at = NOW()
+        WHERE principal_id = $1 AND principal_type = $2 AND revoked_at IS NULL
+        "#,
+        principal_id,
+        kind.as_str()
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn is_session_active(
+    db: &PgPool,
+    session_id: Uuid,
+    kind: PrincipalKind,
+    principal_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let active = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM sessions
+            WHERE id = $1 AND principal_id = $2 AND principal_type = $3
+              AND revoked_at IS NULL AND expires_at > NOW()
+        )
+        "#,
+        session_id,
+        principal_id,
+        kind.as_str()
+    )
+    .fetch_one(db)
+    .await?;
+    Ok(active.unwrap_or(false))
+}
+
+/// Seconds a one-time login code stays redeemable.
+const LOGIN_CODE_TTL_SECONDS: f64 = 60.0;
+
+/// Issues a one-time code the frontend trades for a token pair at `/auth/exchange`,
+/// so OAuth redirects never carry the tokens themselves.
+pub async fn create_login_code(db: &PgPool, user_id: Uuid) -> Result<String, sqlx::Error> {
+    sqlx::query!("DELETE FROM login_codes WHERE expires_at < NOW()")
+        .execute(db)
+        .await?;
+
+    let code = generate_refresh_token();
+    sqlx::query!(
+        r#"
+        INSERT INTO login_codes (code_hash, user_id, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(secs => $3))
+        "#,
+        hash_refresh_token(&code),
+        user_id,
+        LOGIN_CODE_TTL_SECONDS
+    )
+    .execute(db)
+    .await?;
+    Ok(code)
+}
+
+/// Consumes a login code, returning the user it was issued for.
+pub async fn redeem_login_code(db: &PgPool, code: &str) -> Result<Option<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "DELETE FROM login_codes WHERE code_hash = $1 AND expires_at > NOW() RETURNING user_id",
+        hash_refresh_token(code)
+    )
+    .fetch_optional(db)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_tokens_are_unique_and_hashed() {
+        let first = generate_refresh_token();
+        let second = generate_refresh_token();
+        assert_ne!(first, second);
+        assert_ne!(hash_refresh_token(&first), first);
+        assert_eq!(hash_refresh_token(&first), hash_refresh_token(&first));
+    }
+}
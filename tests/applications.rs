@@ -0,0 +1,266 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+
+use common::{apply, create_job, register, register_business, send, TestDb, TestUser};
+use server::config::ReapplyPolicy;
+
+fn statuses(history: &Value) -> Vec<(Value, &str)> {
+    history
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|event| (event["from_status"].clone(), event["to_status"].as_str().unwrap()))
+        .collect()
+}
+
+#[actix_web::test]
+async fn the_poster_moves_applications_forward_and_the_history_keeps_every_step() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let rival = register_business(&app, "rival").await;
+    let seeker = register(&app, "seeker").await;
+    let job = create_job(&app, &acme, json!({})).await;
+    let application = apply(&app, &seeker, job).await;
+    let status_uri = format!("/jobApplications/{}/status", application);
+
+    // Only the business that posted the job may change the status.
+    for outsider in [&rival, &seeker] {
+        let (status, _) = send(
+            &app,
+            test::TestRequest::patch()
+                .uri(&status_uri)
+                .insert_header(outsider.bearer())
+                .set_json(json!({ "status": "under_review" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::patch()
+            .uri(&status_uri)
+            .insert_header(acme.bearer())
+            .set_json(json!({ "status": "interview", "note": "  See you on Monday  " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["application"]["status"], "interview");
+    assert_eq!(body["event"]["changed_by_business"], acme.id.to_string());
+    assert_eq!(body["event"]["note"], "See you on Monday");
+
+    // No going back, and nothing after a final status.
+    for (next, expected) in [
+        ("shortlisted", StatusCode::CONFLICT),
+        ("rejected", StatusCode::OK),
+        ("offered", StatusCode::CONFLICT),
+    ] {
+        let (status, body) = send(
+            &app,
+            test::TestRequest::patch()
+                .uri(&status_uri)
+                .insert_header(acme.bearer())
+                .set_json(json!({ "status": next })),
+        )
+        .await;
+        assert_eq!(status, expected, "{}: {}", next, body);
+    }
+    let (status, _) = send(
+        &app,
+        test::TestRequest::patch()
+            .uri(&status_uri)
+            .insert_header(acme.bearer())
+            .set_json(json!({ "status": "on_hold" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let history_uri = format!("/jobApplications/{}/history", application);
+    let (status, history) = send(&app, test::TestRequest::get().uri(&history_uri).insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::OK, "{}", history);
+    assert_eq!(
+        statuses(&history),
+        vec![(Value::Null, "applied"), (json!("applied"), "interview"), (json!("interview"), "rejected")]
+    );
+    assert_eq!(history[0]["changed_by_user"], seeker.id.to_string());
+    assert_eq!(history[1]["note"], "See you on Monday");
+
+    let (status, _) = send(&app, test::TestRequest::get().uri(&history_uri).insert_header(rival.bearer())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn applicants_apply_once_to_open_jobs_and_can_withdraw() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let seeker = register(&app, "seeker").await;
+    let other = register(&app, "other").await;
+    let job = create_job(&app, &acme, json!({})).await;
+    let closed = create_job(&app, &acme, json!({ "is_active": false })).await;
+    let application = apply(&app, &seeker, job).await;
+
+    for (job_id, message) in [
+        (job, "You have already applied to this job"),
+        (closed, "This job is closed and no longer accepts applications"),
+    ] {
+        let (status, body) = send(
+            &app,
+            test::TestRequest::post()
+                .uri("/jobApplications/create")
+                .insert_header(seeker.bearer())
+                .set_json(json!({ "job_id": job_id })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["message"], message);
+    }
+
+    let withdraw_uri = format!("/jobApplications/{}/withdraw", application);
+    let (status, _) = send(&app, test::TestRequest::post().uri(&withdraw_uri).insert_header(other.bearer())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = send(&app, test::TestRequest::post().uri(&withdraw_uri).insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["application"]["status"], "withdrawn");
+    assert_eq!(body["application"]["is_active"], false);
+    assert_eq!(body["event"]["changed_by_user"], seeker.id.to_string());
+    let (status, _) = send(&app, test::TestRequest::post().uri(&withdraw_uri).insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // The default policy does not let them back in.
+    let (status, body) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(seeker.bearer())
+            .set_json(json!({ "job_id": job })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "You withdrew your application to this job and cannot apply again");
+}
+
+#[actix_web::test]
+async fn the_reapply_policy_decides_whether_a_withdrawn_applicant_may_return() {
+    for (policy, reopened) in [(ReapplyPolicy::Always, true), (ReapplyPolicy::AfterHours(24), false)] {
+        let mut db = TestDb::new().await;
+        db.config.reapply_policy = policy;
+        let app = test::init_service(db.app()).await;
+        let acme = register_business(&app, "acme").await;
+        let seeker = register(&app, "seeker").await;
+        let job = create_job(&app, &acme, json!({})).await;
+        let application = apply(&app, &seeker, job).await;
+        let uri = format!("/jobApplications/{}/withdraw", application);
+        let (status, _) = send(&app, test::TestRequest::post().uri(&uri).insert_header(seeker.bearer())).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, body) = send(
+            &app,
+            test::TestRequest::post()
+                .uri("/jobApplications/create")
+                .insert_header(seeker.bearer())
+                .set_json(json!({ "job_id": job, "cover_letter": "Second try" })),
+        )
+        .await;
+        if !reopened {
+            assert_eq!(status, StatusCode::CONFLICT);
+            assert!(body["message"].as_str().unwrap().starts_with("You can apply to this job again from "));
+            continue;
+        }
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["id"], application.to_string());
+        assert_eq!(body["status"], "applied");
+        assert_eq!(body["is_active"], true);
+
+        let uri = format!("/jobApplications/{}/history", application);
+        let (_, history) = send(&app, test::TestRequest::get().uri(&uri).insert_header(seeker.bearer())).await;
+        assert_eq!(
+            statuses(&history),
+            vec![(Value::Null, "applied"), (json!("applied"), "withdrawn"), (json!("withdrawn"), "applied")]
+        );
+    }
+}
+
+#[actix_web::test]
+async fn application_lists_are_only_shown_to_their_parties() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let rival = register_business(&app, "rival").await;
+    let seeker = register(&app, "seeker").await;
+    let snoop = register(&app, "snoop").await;
+    let job = create_job(&app, &acme, json!({})).await;
+    let application = apply(&app, &seeker, job).await;
+
+    let applicants_uri = format!("/jobApplications/job/{}", job);
+    let applied_uri = format!("/jobApplications/{}", seeker.id);
+    let count_uri = format!("/jobApplications/count/{}", seeker.id);
+    let single_uri = format!("/jobApplications/job/{}/user/{}", job, seeker.id);
+    for uri in [&applicants_uri, &applied_uri, &count_uri, &single_uri] {
+        let (status, _) = send(&app, test::TestRequest::get().uri(uri)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+    for (uri, outsiders) in [
+        (&applicants_uri, [&rival, &seeker]),
+        (&applied_uri, [&acme, &snoop]),
+        (&count_uri, [&acme, &snoop]),
+        (&single_uri, [&rival, &snoop]),
+    ] {
+        for outsider in outsiders {
+            let (status, _) = send(&app, test::TestRequest::get().uri(uri).insert_header(outsider.bearer())).await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+        }
+    }
+
+    let (status, page) = send(&app, test::TestRequest::get().uri(&applicants_uri).insert_header(acme.bearer())).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(page["items"][0]["id"], application.to_string());
+    let (_, page) = send(&app, test::TestRequest::get().uri(&applied_uri).insert_header(seeker.bearer())).await;
+    assert_eq!(page["items"][0]["application_id"], application.to_string());
+    let (_, body) = send(&app, test::TestRequest::get().uri(&count_uri).insert_header(seeker.bearer())).await;
+    assert_eq!(body["count"], 1);
+    for party in [&acme, &seeker] {
+        let (status, body) = send(&app, test::TestRequest::get().uri(&single_uri).insert_header(party.bearer())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], application.to_string());
+    }
+}
+
+#[actix_web::test]
+async fn applicant_contact_details_follow_their_privacy_settings() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let hidden = register(&app, "hidden").await;
+    let open = register(&app, "open").await;
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri("/users/update/open@example.com")
+            .insert_header(open.bearer())
+            .set_json(json!({ "show_email": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let job = create_job(&app, &acme, json!({})).await;
+    apply(&app, &hidden, job).await;
+    apply(&app, &open, job).await;
+
+    for (uri, user_field) in [
+        (format!("/jobApplications/job/{}", job), "user_id"),
+        (format!("/ats/jobs/{}/applications", job), "applicant_id"),
+    ] {
+        let (status, page) = send(&app, test::TestRequest::get().uri(&uri).insert_header(acme.bearer())).await;
+        assert_eq!(status, StatusCode::OK, "{}", page);
+        let email_of = |user: &TestUser| {
+            let items = page["items"].as_array().unwrap();
+            items.iter().find(|a| a[user_field] == user.id.to_string()).unwrap()["email"].clone()
+        };
+        assert_eq!(email_of(&hidden), Value::Null, "{}", uri);
+        assert_eq!(email_of(&open), "open@example.com", "{}", uri);
+    }
+}
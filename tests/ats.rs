@@ -0,0 +1,149 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+
+use common::{apply, create_job, ids, register, register_business, send, set_skills, TestDb};
+
+#[actix_web::test]
+async fn the_dashboard_filters_sorts_and_moves_applicants_in_bulk() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let job = create_job(&app, &acme, json!({ "skills": ["Rust", "Go", "SQL", "Docker"] })).await;
+
+    let mut applications = Vec::new();
+    for (name, skills) in [("ada", vec!["rust", "go", "sql"]), ("bob", vec!["Rust"]), ("cy", vec!["Java"])] {
+        let seeker = register(&app, name).await;
+        set_skills(&app, &seeker, name, &skills).await;
+        applications.push(apply(&app, &seeker, job).await);
+    }
+    let [ada, bob, cy] = applications[..] else { unreachable!() };
+    let applicants_uri = format!("/ats/jobs/{}/applications", job);
+
+    let (status, page) = send(
+        &app,
+        test::TestRequest::get().uri(&format!("{}?sort=match", applicants_uri)).insert_header(acme.bearer()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(ids(&page), vec![ada, bob, cy]);
+    assert_eq!(page["items"][0]["match_score"], 75);
+    assert_eq!(page["items"][0]["matched_skills"], json!(["Rust", "Go", "SQL"]));
+    assert_eq!(page["items"][1]["match_score"], 25);
+
+    // Match order pages on (score, date, id).
+    let (_, first) = send(
+        &app,
+        test::TestRequest::get().uri(&format!("{}?sort=match&limit=2", applicants_uri)).insert_header(acme.bearer()),
+    )
+    .await;
+    let next = format!("{}?sort=match&limit=2&cursor={}", applicants_uri, first["next_cursor"].as_str().unwrap());
+    let (_, second) = send(&app, test::TestRequest::get().uri(&next).insert_header(acme.bearer())).await;
+    assert_eq!(ids(&second), vec![cy]);
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::patch()
+            .uri(&format!("{}/status", applicants_uri))
+            .insert_header(acme.bearer())
+            .set_json(json!({ "application_ids": [ada, bob], "status": "shortlisted" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["updated"].as_array().unwrap().len(), 2);
+
+    // A rejected application stays rejected; the others of the batch still move.
+    let (_, body) = send(
+        &app,
+        test::TestRequest::patch()
+            .uri(&format!("{}/status", applicants_uri))
+            .insert_header(acme.bearer())
+            .set_json(json!({ "application_ids": [cy], "status": "rejected", "note": "Thanks for applying" })),
+    )
+    .await;
+    assert_eq!(body["updated"][0]["event"]["note"], "Thanks for applying");
+    let (_, body) = send(
+        &app,
+        test::TestRequest::patch()
+            .uri(&format!("{}/status", applicants_uri))
+            .insert_header(acme.bearer())
+            .set_json(json!({ "application_ids": [ada, cy], "status": "interview" })),
+    )
+    .await;
+    assert_eq!(body["updated"][0]["application"]["id"], ada.to_string());
+    assert_eq!(body["skipped"], json!([{
+        "id": cy, "status": "rejected", "reason": "Cannot move an application from rejected to interview"
+    }]));
+
+    let uri = format!("{}?status=shortlisted,interview&min_match=50", applicants_uri);
+    let (_, page) = send(&app, test::TestRequest::get().uri(&uri).insert_header(acme.bearer())).await;
+    assert_eq!(ids(&page), vec![ada]);
+
+    let (status, funnel) =
+        send(&app, test::TestRequest::get().uri(&format!("/ats/jobs/{}/funnel", job)).insert_header(acme.bearer()))
+            .await;
+    assert_eq!(status, StatusCode::OK, "{}", funnel);
+    assert_eq!(funnel["total"], 3);
+    assert_eq!(funnel["rejected"], 1);
+    let stages: Vec<(&str, i64, i64)> = funnel["stages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["status"].as_str().unwrap(), s["current"].as_i64().unwrap(), s["reached"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(&stages[..4], &[("applied", 0, 3), ("under_review", 0, 2), ("shortlisted", 1, 2), ("interview", 1, 1)]);
+}
+
+#[actix_web::test]
+async fn recruiter_notes_and_ratings_stay_with_the_poster() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let rival = register_business(&app, "rival").await;
+    let seeker = register(&app, "seeker").await;
+    let job = create_job(&app, &acme, json!({})).await;
+    let application = apply(&app, &seeker, job).await;
+    let notes_uri = format!("/ats/applications/{}/notes", application);
+    let rating_uri = format!("/ats/applications/{}/rating", application);
+
+    let (status, note) = send(
+        &app,
+        test::TestRequest::post()
+            .uri(&notes_uri)
+            .insert_header(acme.bearer())
+            .set_json(json!({ "note": " Strong systems background " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", note);
+    assert_eq!(note["note"], "Strong systems background");
+    assert_eq!(note["written_by_business"], acme.id.to_string());
+
+    let (status, _) =
+        send(&app, test::TestRequest::put().uri(&rating_uri).insert_header(acme.bearer()).set_json(json!({ "rating": 6 })))
+            .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) =
+        send(&app, test::TestRequest::put().uri(&rating_uri).insert_header(acme.bearer()).set_json(json!({ "rating": 4 })))
+            .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for outsider in [&seeker, &rival] {
+        let (status, _) = send(&app, test::TestRequest::get().uri(&notes_uri).insert_header(outsider.bearer())).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = send(
+            &app,
+            test::TestRequest::put().uri(&rating_uri).insert_header(outsider.bearer()).set_json(json!({ "rating": 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    let (_, page) = send(&app, test::TestRequest::get().uri(&notes_uri).insert_header(acme.bearer())).await;
+    assert_eq!(page["items"].as_array().unwrap().len(), 1);
+    let uri = format!("/ats/jobs/{}/applications", job);
+    let (_, page) = send(&app, test::TestRequest::get().uri(&uri).insert_header(acme.bearer())).await;
+    assert_eq!(page["items"][0]["recruiter_rating"], 4);
+    assert_eq!(page["items"][0]["note_count"], 1);
+}
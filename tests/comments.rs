@@ -0,0 +1,65 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::{create_community, create_post, register, send, TestDb, TestUser};
+
+async fn comment<S, B>(app: &S, user: &TestUser, post: Uuid, parent: Option<&Value>, content: &str) -> Value
+where
+    S: actix_web::dev::Service<
+            actix_http::Request,
+            Response = actix_web::dev::ServiceResponse<B>,
+            Error = actix_web::Error,
+        >,
+    B: actix_web::body::MessageBody,
+{
+    let (status, body) = send(
+        app,
+        test::TestRequest::post()
+            .uri("/comment/create")
+            .insert_header(user.bearer())
+            .set_json(json!({ "post_id": post, "content": content, "parent_id": parent.map(|c| &c["id"]) })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create comment failed: {}", body);
+    body
+}
+
+#[actix_web::test]
+async fn replies_are_nested_at_every_depth() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let reader = register(&app, "reader").await;
+    let community = create_community(&app, &author).await;
+    let post = create_post(&app, &author, community).await;
+
+    let root = comment(&app, &reader, post, None, "root").await;
+    let reply = comment(&app, &author, post, Some(&root), "reply").await;
+    let second_reply = comment(&app, &reader, post, Some(&root), "second reply").await;
+    let deep = comment(&app, &reader, post, Some(&reply), "reply to reply").await;
+    let deeper = comment(&app, &author, post, Some(&deep), "deeper").await;
+    let newer_root = comment(&app, &author, post, None, "newer root").await;
+
+    let (status, tree) = send(&app, test::TestRequest::get().uri(&format!("/comment/post/{}", post))).await;
+    assert_eq!(status, StatusCode::OK);
+    let roots = tree.as_array().unwrap();
+
+    // Top-level comments come newest first, replies oldest first.
+    assert_eq!(roots.len(), 2);
+    assert_eq!(roots[0]["id"], newer_root["id"]);
+    assert_eq!(roots[1]["id"], root["id"]);
+
+    let replies = roots[1]["replies"].as_array().unwrap();
+    assert_eq!(replies.len(), 2);
+    assert_eq!(replies[0]["id"], reply["id"]);
+    assert_eq!(replies[1]["id"], second_reply["id"]);
+
+    let level_three = &replies[0]["replies"][0];
+    assert_eq!(level_three["id"], deep["id"]);
+    assert_eq!(level_three["replies"][0]["id"], deeper["id"]);
+    assert_eq!(level_three["replies"][0]["replies"].as_array().unwrap().len(), 0);
+}
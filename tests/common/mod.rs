@@ -0,0 +1,314 @@
+//! Shared harness for the integration tests.
+//!
+//! Every test gets its own database (`pb_test_<uuid>`), created next to the one in
+//! `TEST_DATABASE_URL` (falling back to `DATABASE_URL`), migrated from scratch and dropped
+//! when the [`TestDb`] goes out of scope, along with its resume directory. The app under
+//! test comes from the same [`create_app`] factory the server uses.
+
+#![allow(dead_code)]
+
+use actix_http::Request;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use url::Url;
+use uuid::Uuid;
+
+use server::app::create_app;
+use server::config::Config;
+use server::db::MIGRATOR;
+use server::oauth::OAuthProviders;
+
+pub struct TestDb {
+    pub pool: PgPool,
+    pub config: Config,
+    name: String,
+    admin_url: String,
+}
+
+impl TestDb {
+    pub async fn new() -> TestDb {
+        dotenv::dotenv().ok();
+        let admin_url = std::env::var("TEST_DATABASE_URL")
+            .or_else(|_| std::env::var("DATABASE_URL"))
+            .unwrap_or_else(|_| Config::default().database_url);
+        let name = format!("pb_test_{}", Uuid::new_v4().simple());
+
+        let mut admin = PgConnection::connect(&admin_url)
+            .await
+            .expect("Failed to connect to the test database server");
+        admin
+            .execute(format!(r#"CREATE DATABASE "{}""#, name).as_str())
+            .await
+            .expect("Failed to create the test database");
+        admin.close().await.ok();
+
+        let mut url = Url::parse(&admin_url).expect("Invalid test database URL");
+        url.set_path(&name);
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect(url.as_str())
+            .await
+            .expect("Failed to connect to the test database");
+        MIGRATOR.run(&pool).await.expect("Failed to migrate the test database");
+
+        let config = Config {
+            database_url: url.to_string(),
+            resume_dir: std::env::temp_dir().join(&name).to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        TestDb { pool, config, name, admin_url }
+    }
+
+    /// The application exactly as `main.rs` builds it, bound to this database.
+    pub fn app(
+        &self,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<impl MessageBody + use<>>,
+            Error = actix_web::Error,
+            InitError = (),
+        > + use<>,
+    > {
+        self.app_with(OAuthProviders::from_config(&self.config))
+    }
+
+    /// [`TestDb::app`] with other login providers than the configured ones.
+    pub fn app_with(
+        &self,
+        providers: OAuthProviders,
+    ) -> App<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<impl MessageBody + use<>>,
+            Error = actix_web::Error,
+            InitError = (),
+        > + use<>,
+    > {
+        create_app(self.pool.clone(), self.config.clone(), web::Data::new(providers))
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        // Drop cannot be async; run the cleanup on its own runtime so it also happens
+        // when the test panicked.
+        std::fs::remove_dir_all(&self.config.resume_dir).ok();
+        let admin_url = self.admin_url.clone();
+        let name = self.name.clone();
+        std::thread::spawn(move || {
+            actix_rt::System::new().block_on(async move {
+                if let Ok(mut admin) = PgConnection::connect(&admin_url).await {
+                    let drop = format!(r#"DROP DATABASE IF EXISTS "{}" WITH (FORCE)"#, name);
+                    admin.execute(drop.as_str()).await.ok();
+                }
+            })
+        })
+        .join()
+        .ok();
+    }
+}
+
+pub struct TestUser {
+    pub id: Uuid,
+    pub token: String,
+}
+
+impl TestUser {
+    pub fn bearer(&self) -> (&'static str, String) {
+        ("Authorization", format!("Bearer {}", self.token))
+    }
+}
+
+/// The ids of a page's items, in order.
+pub fn ids(page: &Value) -> Vec<Uuid> {
+    page["items"].as_array().unwrap().iter().map(|item| item["id"].as_str().unwrap().parse().unwrap()).collect()
+}
+
+/// Sends the request and returns the status together with the JSON body (`Value::Null` when
+/// the body is empty or not JSON).
+pub async fn send<S, B>(app: &S, req: test::TestRequest) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let response = test::call_service(app, req.to_request()).await;
+    let status = response.status();
+    let body = test::read_body(response).await;
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+pub async fn register<S, B>(app: &S, username: &str) -> TestUser
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = send(
+        app,
+        test::TestRequest::post().uri("/auth/register").set_json(json!({
+            "first_name": username,
+            "last_name": "Tester",
+            "username": username,
+            "email": format!("{}@example.com", username),
+            "password": "correct horse battery"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "register failed: {}", body);
+    TestUser {
+        id: body["id"].as_str().unwrap().parse().unwrap(),
+        token: body["token"].as_str().unwrap().to_string(),
+    }
+}
+
+pub async fn create_community<S, B>(app: &S, owner: &TestUser) -> Uuid
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = send(
+        app,
+        test::TestRequest::post()
+            .uri("/communities/create")
+            .insert_header(owner.bearer())
+            .set_json(json!({ "name": "Test community", "description": { "en": "For tests" } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create community failed: {}", body);
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+pub async fn create_post<S, B>(app: &S, author: &TestUser, community_id: Uuid) -> Uuid
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    create_tagged_post(app, author, community_id, &["rust"]).await
+}
+
+pub async fn create_tagged_post<S, B>(app: &S, author: &TestUser, community_id: Uuid, tags: &[&str]) -> Uuid
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = send(
+        app,
+        test::TestRequest::post()
+            .uri("/post/create")
+            .insert_header(author.bearer())
+            .set_json(json!({
+                "community_id": community_id,
+                "title": { "en": "Hello" },
+                "content": { "en": "First post" },
+                "post_type": "text",
+                "tags": tags
+            })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create post failed: {}", body);
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+/// Creates a business account and logs it in.
+pub async fn register_business<S, B>(app: &S, name: &str) -> TestUser
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let email = format!("{}@example.com", name);
+    let (status, body) = send(
+        app,
+        test::TestRequest::post().uri("/business-accounts/create").set_json(json!({
+            "company_name": name,
+            "contact_person_name": "Recruiter",
+            "email": email,
+            "password": "correct horse battery",
+            "address": "1 Test Street"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create business failed: {}", body);
+
+    let (status, body) = send(
+        app,
+        test::TestRequest::post()
+            .uri("/auth/business/login")
+            .set_json(json!({ "email": email, "password": "correct horse battery" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "business login failed: {}", body);
+    TestUser {
+        id: body["id"].as_str().unwrap().parse().unwrap(),
+        token: body["token"].as_str().unwrap().to_string(),
+    }
+}
+
+/// Posts a job; `fields` override the defaults (an active remote Rust job).
+pub async fn create_job<S, B>(app: &S, business: &TestUser, fields: Value) -> Uuid
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let mut job = json!({
+        "organisation": "Test Org",
+        "title": { "en": "Engineer" },
+        "description": { "en": "Builds things" },
+        "location": "Berlin",
+        "job_type": "remote",
+        "skills": ["Rust"],
+        "notice_period": "2 weeks",
+        "min_experience": 2,
+        "salary_range": "50000-70000 EUR",
+        "job_posted_by": "Recruiter"
+    });
+    job.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+    let (status, body) = send(
+        app,
+        test::TestRequest::post().uri("/jobs/create").insert_header(business.bearer()).set_json(job),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "create job failed: {}", body);
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+/// Applies to a job and returns the application id.
+pub async fn apply<S, B>(app: &S, applicant: &TestUser, job_id: Uuid) -> Uuid
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = send(
+        app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(applicant.bearer())
+            .set_json(json!({ "job_id": job_id, "cover_letter": "Hire me" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "apply failed: {}", body);
+    body["id"].as_str().unwrap().parse().unwrap()
+}
+
+/// Replaces the skills on the profile of the user registered as `username`.
+pub async fn set_skills<S, B>(app: &S, user: &TestUser, username: &str, skills: &[&str])
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let (status, body) = send(
+        app,
+        test::TestRequest::put()
+            .uri(&format!("/users/update/{}@example.com", username))
+            .insert_header(user.bearer())
+            .set_json(json!({ "skills": skills })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "update skills failed: {}", body);
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+
+use common::{create_community, create_tagged_post, ids, register, send, TestDb};
+
+#[actix_web::test]
+async fn feed_blends_follows_and_interests_and_skips_seen_posts() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let reader = register(&app, "reader").await;
+    let friend = register(&app, "friend").await;
+    let stranger = register(&app, "stranger").await;
+    let community = create_community(&app, &stranger).await;
+
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri("/users/update/reader@example.com")
+            .insert_header(reader.bearer())
+            .set_json(json!({ "interest": "rust" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    send(
+        &app,
+        test::TestRequest::post()
+            .uri("/follower/toggle")
+            .insert_header(reader.bearer())
+            .set_json(json!({ "followed_id": friend.id })),
+    )
+    .await;
+
+    let from_friend = create_tagged_post(&app, &friend, community, &["cooking"]).await;
+    let on_interest = create_tagged_post(&app, &stranger, community, &["rust"]).await;
+    let unrelated = create_tagged_post(&app, &stranger, community, &["cooking"]).await;
+    let own = create_tagged_post(&app, &reader, community, &["rust"]).await;
+
+    let feed_uri = format!("/feed/{}", reader.id);
+    let (status, page) = send(&app, test::TestRequest::get().uri(&feed_uri).insert_header(reader.bearer())).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    // Following the author outweighs one matching tag.
+    assert_eq!(ids(&page), vec![from_friend, on_interest]);
+    assert!(!ids(&page).contains(&unrelated) && !ids(&page).contains(&own));
+    assert_eq!(page["items"][0]["from_followed_author"], true);
+    assert_eq!(page["items"][1]["interest_matches"], 1);
+
+    // Paging keeps the same order.
+    let (_, first) = send(&app, test::TestRequest::get().uri(&format!("{}?limit=1", feed_uri)).insert_header(reader.bearer())).await;
+    assert_eq!(ids(&first), vec![from_friend]);
+    assert_eq!(first["has_more"], true);
+    let next = format!("{}?limit=1&cursor={}", feed_uri, first["next_cursor"].as_str().unwrap());
+    let (_, second) = send(&app, test::TestRequest::get().uri(&next).insert_header(reader.bearer())).await;
+    assert_eq!(ids(&second), vec![on_interest]);
+    assert_eq!(second["has_more"], false);
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/feed/seen")
+            .insert_header(reader.bearer())
+            .set_json(json!({ "post_ids": [from_friend] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["marked"], 1);
+    let (_, page) = send(&app, test::TestRequest::get().uri(&feed_uri).insert_header(reader.bearer())).await;
+    assert_eq!(ids(&page), vec![on_interest]);
+}
+
+#[actix_web::test]
+async fn feed_is_private_to_its_owner() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let reader = register(&app, "reader").await;
+    let other = register(&app, "other").await;
+
+    let uri = format!("/feed/{}", reader.id);
+    let (status, _) = send(&app, test::TestRequest::get().uri(&uri)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send(&app, test::TestRequest::get().uri(&uri).insert_header(other.bearer())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "forbidden");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+use uuid::Uuid;
+
+use common::{register, send, TestDb};
+
+#[actix_web::test]
+async fn follow_toggle_follows_then_unfollows() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let asha = register(&app, "asha").await;
+    let ravi = register(&app, "ravi").await;
+
+    let toggle = || {
+        test::TestRequest::post()
+            .uri("/follower/toggle")
+            .insert_header(asha.bearer())
+            .set_json(json!({ "followed_id": ravi.id }))
+    };
+    let status_uri = format!("/follower/status/{}/{}", asha.id, ravi.id);
+    let count_uri = format!("/follower/count/{}", ravi.id);
+
+    let (status, body) = send(&app, toggle()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "followed");
+    let (_, body) = send(&app, test::TestRequest::get().uri(&status_uri)).await;
+    assert_eq!(body["is_following"], true);
+    let (_, body) = send(&app, test::TestRequest::get().uri(&count_uri)).await;
+    assert_eq!(body["followers"], 1);
+
+    let (_, body) = send(&app, toggle()).await;
+    assert_eq!(body, "unfollowed");
+    let (_, body) = send(&app, test::TestRequest::get().uri(&status_uri)).await;
+    assert_eq!(body["is_following"], false);
+    let (_, body) = send(&app, test::TestRequest::get().uri(&count_uri)).await;
+    assert_eq!(body["followers"], 0);
+}
+
+#[actix_web::test]
+async fn following_an_unknown_user_is_rejected() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let asha = register(&app, "asha").await;
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/follower/toggle")
+            .insert_header(asha.bearer())
+            .set_json(json!({ "followed_id": Uuid::new_v4() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], "unprocessable_entity");
+}
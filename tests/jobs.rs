@@ -0,0 +1,495 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use common::{apply, create_job, ids, register, register_business, send, TestDb};
+use server::job_lifecycle;
+
+fn facet(page: &Value, name: &str) -> Vec<(String, i64)> {
+    page["facets"][name]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| (f["value"].as_str().unwrap().to_string(), f["count"].as_i64().unwrap()))
+        .collect()
+}
+
+#[actix_web::test]
+async fn salary_labels_become_structured() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+
+    let from_label = create_job(&app, &acme, json!({ "salary_range": "$60,000 - $80,000" })).await;
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", from_label))).await;
+    assert_eq!((job["salary_min"].clone(), job["salary_max"].clone()), (json!(60000), json!(80000)));
+    assert_eq!(job["salary_currency"], "USD");
+
+    // A currency sent alone keeps the band and relabels it.
+    let (status, body) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&format!("/jobs/update/{}", from_label))
+            .insert_header(acme.bearer())
+            .set_json(json!({ "salary_currency": "EUR" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", from_label))).await;
+    assert_eq!((job["salary_min"].clone(), job["salary_max"].clone()), (json!(60000), json!(80000)));
+    assert_eq!(job["salary_currency"], "EUR");
+    assert_eq!(job["salary_range"], "60000-80000 EUR");
+
+    let from_numbers = create_job(
+        &app,
+        &acme,
+        json!({ "salary_range": null, "salary_min": 40000, "salary_max": 45000, "salary_currency": "gbp" }),
+    )
+    .await;
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", from_numbers))).await;
+    assert_eq!(job["salary_range"], "40000-45000 GBP");
+    assert_eq!(job["salary_currency"], "GBP");
+
+    // A label without numbers clears the old band.
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&format!("/jobs/update/{}", from_numbers))
+            .insert_header(acme.bearer())
+            .set_json(json!({ "salary_range": "Competitive" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", from_numbers))).await;
+    assert_eq!(job["salary_range"], "Competitive");
+    assert_eq!(job["salary_min"], Value::Null);
+    // ...and there is no band left to put a currency on.
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&format!("/jobs/update/{}", from_numbers))
+            .insert_header(acme.bearer())
+            .set_json(json!({ "salary_currency": "USD" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobs/create")
+            .insert_header(acme.bearer())
+            .set_json(json!({
+                "organisation": "Acme", "title": { "en": "Engineer" }, "description": { "en": "x" },
+                "location": "Berlin", "job_type": "remote", "skills": [], "notice_period": "none",
+                "job_posted_by": "Recruiter", "salary_min": 90000, "salary_max": 10000
+            })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn job_list_filters_and_counts_facets() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+
+    let rust_berlin = create_job(&app, &acme, json!({ "skills": ["Rust", "SQL"], "salary_range": "60000-80000 EUR" })).await;
+    let go_berlin = create_job(&app, &acme, json!({ "skills": ["Go"], "job_type": "onsite", "min_experience": 6 })).await;
+    let rust_paris = create_job(&app, &acme, json!({ "location": "Paris", "skills": ["rust"], "salary_range": "30000 EUR" })).await;
+    let closed = create_job(&app, &acme, json!({ "is_active": false })).await;
+
+    let (status, page) = send(&app, test::TestRequest::get().uri("/jobs?location=berlin&active_only=true")).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(ids(&page), vec![go_berlin, rust_berlin]);
+    // Each facet ignores its own filter: Paris still shows up as an alternative location.
+    assert_eq!(facet(&page, "location"), vec![("Berlin".into(), 2), ("Paris".into(), 1)]);
+    assert_eq!(facet(&page, "job_type"), vec![("onsite".into(), 1), ("remote".into(), 1)]);
+    assert_eq!(facet(&page, "experience"), vec![("2-4".into(), 1), ("5-9".into(), 1)]);
+
+    // Values differing only in case are counted together.
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs?active_only=true")).await;
+    assert_eq!(facet(&page, "skills"), vec![("Rust".into(), 2), ("Go".into(), 1), ("SQL".into(), 1)]);
+
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs?skills=RUST,sql&skills_match=all")).await;
+    assert_eq!(ids(&page), vec![rust_berlin]);
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs?skills=rust,go")).await;
+    assert_eq!(ids(&page), vec![closed, rust_paris, go_berlin, rust_berlin]);
+
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs?salary_min=55000&currency=eur")).await;
+    assert_eq!(ids(&page), vec![closed, go_berlin, rust_berlin]);
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs?experience_min=5&experience_max=9")).await;
+    assert_eq!(ids(&page), vec![go_berlin]);
+
+    // Later pages carry no facets.
+    let (_, first) = send(&app, test::TestRequest::get().uri("/jobs?limit=1")).await;
+    let next = format!("/jobs?limit=1&cursor={}", first["next_cursor"].as_str().unwrap());
+    let (_, second) = send(&app, test::TestRequest::get().uri(&next)).await;
+    assert_eq!(ids(&second), vec![rust_paris]);
+    assert!(second.get("facets").is_none());
+
+    let (status, _) = send(&app, test::TestRequest::get().uri("/jobs?experience_min=5&experience_max=1")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, test::TestRequest::get().uri("/jobs?salary_min=55000")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn recommendations_rank_by_profile_and_skip_applied_jobs() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let seeker = register(&app, "seeker").await;
+    let other = register(&app, "other").await;
+
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri("/users/update/seeker@example.com")
+            .insert_header(seeker.bearer())
+            .set_json(json!({
+                "skills": ["rust", "SQL"],
+                "location": "Berlin, Germany",
+                "available_for": "Remote work",
+                "work_experiences": [{ "company": "Acme", "start_date": "2015-01", "end_date": "2018-01" }]
+            })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let perfect = create_job(&app, &acme, json!({ "skills": ["Rust", "SQL"], "min_experience": 2 })).await;
+    let partial = create_job(
+        &app,
+        &acme,
+        json!({ "skills": ["Rust", "Go", "Docker", "Kubernetes"], "location": "Paris", "job_type": "onsite", "min_experience": 5 }),
+    )
+    .await;
+    create_job(&app, &acme, json!({ "skills": ["Go"], "location": "Paris", "job_type": "onsite" })).await;
+    let applied = create_job(&app, &acme, json!({})).await;
+    let (status, _) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(seeker.bearer())
+            .set_json(json!({ "job_id": applied })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let uri = format!("/jobs/recommended/{}", seeker.id);
+    let (status, page) = send(&app, test::TestRequest::get().uri(&uri).insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(ids(&page), vec![perfect, partial]);
+    assert_eq!(page["items"][0]["score"], 100);
+    assert_eq!(
+        page["items"][0]["reasons"],
+        json!([
+            "matches 2 of 2 skills",
+            "located in Berlin",
+            "asks for 2 years of experience, you have 3",
+            "remote, as you prefer"
+        ])
+    );
+    assert_eq!(page["items"][1]["matched_skills"], json!(["Rust"]));
+    assert_eq!(page["items"][1]["reasons"][0], "matches 1 of 4 skills");
+
+    let (status, _) = send(&app, test::TestRequest::get().uri(&uri).insert_header(other.bearer())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn drafts_stay_private_until_published() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let seeker = register(&app, "seeker").await;
+    let draft = create_job(&app, &acme, json!({ "status": "draft" })).await;
+    let job_uri = format!("/jobs/{}", draft);
+    let poster_uri = format!("/jobs/by-poster/{}", acme.id);
+
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs")).await;
+    assert!(ids(&page).is_empty());
+    let (status, _) = send(&app, test::TestRequest::get().uri(&job_uri)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, page) = send(&app, test::TestRequest::get().uri(&poster_uri)).await;
+    assert!(ids(&page).is_empty());
+    let (status, job) = send(&app, test::TestRequest::get().uri(&job_uri).insert_header(acme.bearer())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((job["status"].as_str(), job["is_active"].as_bool()), (Some("draft"), Some(false)));
+    let (_, page) = send(&app, test::TestRequest::get().uri(&poster_uri).insert_header(acme.bearer())).await;
+    assert_eq!(ids(&page), vec![draft]);
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(seeker.bearer())
+            .set_json(json!({ "job_id": draft })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "This job is closed and no longer accepts applications");
+
+    let update_uri = format!("/jobs/update/{}", draft);
+    let (status, body) = send(
+        &app,
+        test::TestRequest::put().uri(&update_uri).insert_header(acme.bearer()).set_json(json!({ "status": "paused" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "A draft job cannot be moved to paused");
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put().uri(&update_uri).insert_header(acme.bearer()).set_json(json!({ "status": "published" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs?active_only=true")).await;
+    assert_eq!(ids(&page), vec![draft]);
+
+    let yesterday = Utc::now().naive_utc() - Duration::days(1);
+    for (fields, message) in [
+        (json!({ "status": "expired" }), "New jobs must be draft or published"),
+        (json!({ "closes_at": yesterday }), "closes_at must be in the future"),
+        (json!({ "max_applicants": 0 }), "max_applicants must be at least 1"),
+    ] {
+        let mut job = json!({
+            "organisation": "Acme", "title": { "en": "Engineer" }, "description": { "en": "x" },
+            "location": "Berlin", "job_type": "remote", "skills": [], "notice_period": "none",
+            "job_posted_by": "Recruiter"
+        });
+        job.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+        let (status, body) =
+            send(&app, test::TestRequest::post().uri("/jobs/create").insert_header(acme.bearer()).set_json(job)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], message);
+    }
+}
+
+#[actix_web::test]
+async fn expired_jobs_are_hidden_and_pending_applicants_notified() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let seeker = register(&app, "seeker").await;
+    let quitter = register(&app, "quitter").await;
+    let late = register(&app, "late").await;
+    let now = Utc::now().naive_utc();
+    let expiring = create_job(&app, &acme, json!({ "closes_at": now + Duration::hours(1) })).await;
+    let open = create_job(&app, &acme, json!({})).await;
+    apply(&app, &seeker, expiring).await;
+    let withdrawn = apply(&app, &quitter, expiring).await;
+    let uri = format!("/jobApplications/{}/withdraw", withdrawn);
+    let (status, _) = send(&app, test::TestRequest::post().uri(&uri).insert_header(quitter.bearer())).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The deadline passes.
+    sqlx::query("UPDATE job_listings SET closes_at = $1 WHERE id = $2")
+        .bind(now - Duration::minutes(1))
+        .bind(expiring)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let summary = job_lifecycle::close_expired(&db.pool, now).await.unwrap();
+    assert_eq!((summary.expired_jobs, summary.notified_applicants), (1, 1));
+    // Nothing left to expire on the next run.
+    let summary = job_lifecycle::close_expired(&db.pool, now).await.unwrap();
+    assert_eq!(summary.expired_jobs, 0);
+
+    let (_, page) = send(&app, test::TestRequest::get().uri("/jobs")).await;
+    assert_eq!(ids(&page), vec![open]);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", expiring))).await;
+    assert_eq!((job["status"].as_str(), job["is_active"].as_bool()), (Some("expired"), Some(false)));
+    let (status, _) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(late.bearer())
+            .set_json(json!({ "job_id": expiring })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (_, page) = send(&app, test::TestRequest::get().uri("/notifications").insert_header(quitter.bearer())).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+    let (status, page) = send(&app, test::TestRequest::get().uri("/notifications").insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    let notification = &page["items"][0];
+    assert_eq!(notification["kind"], "job_expired");
+    assert_eq!(notification["job_id"], expiring.to_string());
+    assert!(notification["message"].as_str().unwrap().contains("\"Engineer\" at Test Org"));
+
+    let read_uri = format!("/notifications/{}/read", notification["id"].as_str().unwrap());
+    let (status, _) = send(&app, test::TestRequest::post().uri(&read_uri).insert_header(late.bearer())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, body) = send(&app, test::TestRequest::post().uri(&read_uri).insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["read_at"].is_string());
+    let (_, page) =
+        send(&app, test::TestRequest::get().uri("/notifications?unread=true").insert_header(seeker.bearer())).await;
+    assert!(page["items"].as_array().unwrap().is_empty());
+
+    // Reopening needs a new deadline.
+    let update_uri = format!("/jobs/update/{}", expiring);
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put().uri(&update_uri).insert_header(acme.bearer()).set_json(json!({ "status": "published" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&update_uri)
+            .insert_header(acme.bearer())
+            .set_json(json!({ "status": "published", "closes_at": Utc::now().naive_utc() + Duration::days(7) })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    apply(&app, &late, expiring).await;
+}
+
+#[actix_web::test]
+async fn full_jobs_close_and_closing_notifies_applicants() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let first = register(&app, "first").await;
+    let second = register(&app, "second").await;
+    let third = register(&app, "third").await;
+    let job = create_job(&app, &acme, json!({ "max_applicants": 2 })).await;
+    let job_uri = format!("/jobs/{}", job);
+
+    apply(&app, &first, job).await;
+    let (_, body) = send(&app, test::TestRequest::get().uri(&job_uri)).await;
+    assert_eq!(body["status"], "published");
+    apply(&app, &second, job).await;
+    let (_, body) = send(&app, test::TestRequest::get().uri(&job_uri)).await;
+    assert_eq!(body["status"], "closed");
+    let (status, _) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(third.bearer())
+            .set_json(json!({ "job_id": job })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // A full job cannot be reopened without room for more applicants.
+    let update_uri = format!("/jobs/update/{}", job);
+    let (status, body) = send(
+        &app,
+        test::TestRequest::put().uri(&update_uri).insert_header(acme.bearer()).set_json(json!({ "status": "published" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "This job already has its maximum number of applicants");
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&update_uri)
+            .insert_header(acme.bearer())
+            .set_json(json!({ "status": "published", "max_applicants": 3 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put().uri(&update_uri).insert_header(acme.bearer()).set_json(json!({ "status": "closed" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    for user in [&first, &second] {
+        let (_, page) = send(&app, test::TestRequest::get().uri("/notifications").insert_header(user.bearer())).await;
+        let kinds: Vec<&str> = page["items"].as_array().unwrap().iter().map(|n| n["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, vec!["job_closed"]);
+    }
+}
+
+#[actix_web::test]
+async fn updates_cannot_hand_a_job_to_another_business() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let globex = register_business(&app, "globex").await;
+    let job = create_job(&app, &acme, json!({})).await;
+    let update_uri = format!("/jobs/update/{}", job);
+
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&update_uri)
+            .insert_header(acme.bearer())
+            .set_json(json!({ "posted_by": globex.id, "organisation": "Acme GmbH" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, body) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", job))).await;
+    assert_eq!(body["posted_by"], acme.id.to_string());
+    assert_eq!(body["organisation"], "Acme GmbH");
+
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri(&update_uri)
+            .insert_header(globex.bearer())
+            .set_json(json!({ "organisation": "Globex" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn lifting_a_limit_reopens_the_jobs_it_closed() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let seeker = register(&app, "seeker").await;
+    let now = Utc::now().naive_utc();
+    let full = create_job(&app, &acme, json!({ "max_applicants": 1, "closes_at": now + Duration::hours(1) })).await;
+    let expiring = create_job(&app, &acme, json!({ "closes_at": now + Duration::hours(1) })).await;
+    let closed = create_job(&app, &acme, json!({ "max_applicants": 1 })).await;
+    let update = |job: Uuid, fields: Value| {
+        test::TestRequest::put().uri(&format!("/jobs/update/{}", job)).insert_header(acme.bearer()).set_json(fields)
+    };
+
+    apply(&app, &seeker, full).await;
+    sqlx::query("UPDATE job_listings SET closes_at = $1 WHERE id = $2")
+        .bind(now - Duration::minutes(1))
+        .bind(expiring)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    job_lifecycle::close_expired(&db.pool, now).await.unwrap();
+    let (status, _) = send(&app, update(closed, json!({ "status": "closed" }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Leaving a field out keeps it; null removes it.
+    let (status, _) = send(&app, update(full, json!({ "organisation": "Acme GmbH" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", full))).await;
+    assert_eq!((job["status"].as_str(), job["max_applicants"].as_i64()), (Some("closed"), Some(1)));
+    let (status, _) = send(&app, update(full, json!({ "max_applicants": null, "closes_at": null }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", full))).await;
+    assert_eq!(job["status"], "published");
+    assert_eq!((job["max_applicants"].clone(), job["closes_at"].clone()), (Value::Null, Value::Null));
+
+    let (status, _) = send(&app, update(expiring, json!({ "closes_at": null }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", expiring))).await;
+    assert_eq!((job["status"].as_str(), job["is_active"].as_bool()), (Some("published"), Some(true)));
+
+    // The employer closed this one, so it stays closed.
+    let (status, _) = send(&app, update(closed, json!({ "max_applicants": null }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, job) = send(&app, test::TestRequest::get().uri(&format!("/jobs/{}", closed))).await;
+    assert_eq!(job["status"], "closed");
+}
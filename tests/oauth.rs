@@ -0,0 +1,227 @@
+mod common;
+
+use std::sync::Arc;
+
+use actix_http::Request;
+use actix_web::body::MessageBody;
+use actix_web::cookie::Cookie;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web, App, HttpResponse, HttpServer};
+use futures::future::BoxFuture;
+use oauth2::basic::BasicClient;
+use oauth2::{AuthUrl, ClientId, ClientSecret, TokenUrl};
+use serde_json::{json, Value};
+use url::Url;
+
+use common::{register, send, TestDb};
+use server::oauth::{OAuthProfile, OAuthProvider, OAuthProviders, OAUTH_STATE_COOKIE};
+
+/// A provider whose token and userinfo endpoints are served by [`start_stub`].
+struct StubProvider {
+    client: BasicClient,
+    userinfo_url: String,
+}
+
+impl OAuthProvider for StubProvider {
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+
+    fn client(&self) -> &BasicClient {
+        &self.client
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        &["profile"]
+    }
+
+    fn fetch_profile<'a>(
+        &'a self,
+        http: &'a reqwest::Client,
+        access_token: &'a str,
+    ) -> BoxFuture<'a, Result<OAuthProfile, String>> {
+        Box::pin(async move {
+            let info: Value = http
+                .get(&self.userinfo_url)
+                .bearer_auth(access_token)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(OAuthProfile {
+                provider_user_id: info["sub"].as_str().unwrap().to_string(),
+                email: info["email"].as_str().unwrap().to_string(),
+                email_verified: info["email_verified"].as_bool().unwrap_or(false),
+                first_name: "Stub".to_string(),
+                last_name: "User".to_string(),
+                picture: None,
+                profile_url: None,
+            })
+        })
+    }
+}
+
+/// Serves `POST /token` and `GET /userinfo` (answering `profile`) on a free local port and
+/// returns the providers to build the app with.
+async fn start_stub(profile: Value) -> OAuthProviders {
+    let profile = web::Data::new(profile);
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(profile.clone())
+            .route(
+                "/token",
+                web::post().to(|| async {
+                    HttpResponse::Ok().json(json!({ "access_token": "stub-token", "token_type": "bearer" }))
+                }),
+            )
+            .route(
+                "/userinfo",
+                web::get().to(|profile: web::Data<Value>| async move { HttpResponse::Ok().json(profile.get_ref()) }),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let base = format!("http://{}", server.addrs()[0]);
+    actix_rt::spawn(server.run());
+
+    let client = BasicClient::new(
+        ClientId::new("client".to_string()),
+        Some(ClientSecret::new("secret".to_string())),
+        AuthUrl::new(format!("{}/authorize", base)).unwrap(),
+        Some(TokenUrl::new(format!("{}/token", base)).unwrap()),
+    );
+    OAuthProviders::new(vec![Arc::new(StubProvider { client, userinfo_url: format!("{}/userinfo", base) })])
+}
+
+/// The state cookie a login or link request set.
+fn state_cookie<B>(response: &ServiceResponse<B>) -> String {
+    response.response().cookies().find(|c| c.name() == OAUTH_STATE_COOKIE).unwrap().value().to_string()
+}
+
+/// Follows a callback to its login code and trades it for the signed-in user's id.
+async fn exchange<S, B>(app: &S, state: &str) -> (StatusCode, Value)
+where
+    S: Service<Request, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    B: MessageBody,
+{
+    let response = test::call_service(app, callback(state, Some(state)).to_request()).await;
+    if response.status() != StatusCode::FOUND {
+        let status = response.status();
+        return (status, serde_json::from_slice(&test::read_body(response).await).unwrap_or(Value::Null));
+    }
+    let target = Url::parse(response.headers().get(header::LOCATION).unwrap().to_str().unwrap()).unwrap();
+    let code = target.query_pairs().find(|(k, _)| k == "code").unwrap().1.into_owned();
+    send(app, test::TestRequest::post().uri("/auth/exchange").set_json(json!({ "code": code }))).await
+}
+
+fn callback(state: &str, cookie: Option<&str>) -> test::TestRequest {
+    let request = test::TestRequest::get().uri(&format!("/auth/stub/callback?code=stub-code&state={}", state));
+    match cookie {
+        Some(value) => request.cookie(Cookie::new(OAUTH_STATE_COOKIE, value.to_string())),
+        None => request,
+    }
+}
+
+#[actix_web::test]
+async fn callback_rejects_mismatched_unknown_and_reused_state() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app_with(
+        start_stub(json!({ "sub": "42", "email": "ada@example.com", "email_verified": true })).await,
+    ))
+    .await;
+
+    let login = test::call_service(&app, test::TestRequest::get().uri("/auth/stub/login").to_request()).await;
+    assert_eq!(login.status(), StatusCode::FOUND);
+    let cookie = state_cookie(&login);
+    let location = Url::parse(login.headers().get(header::LOCATION).unwrap().to_str().unwrap()).unwrap();
+    let state = location.query_pairs().find(|(k, _)| k == "state").unwrap().1.into_owned();
+    assert_eq!(state, cookie);
+
+    for cookie in [Some("someone-elses-state"), None] {
+        let (status, body) = send(&app, callback(&state, cookie)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "Invalid OAuth state");
+    }
+    let (status, body) = send(&app, callback("unknown", Some("unknown"))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Invalid or expired OAuth state");
+
+    // The mismatches above did not use the state up.
+    let response = test::call_service(&app, callback(&state, Some(&cookie)).to_request()).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    let (status, body) = send(&app, callback(&state, Some(&cookie))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Invalid or expired OAuth state");
+}
+
+#[actix_web::test]
+async fn callback_creates_the_user_and_issues_a_login_code() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app_with(
+        start_stub(json!({ "sub": "42", "email": "ada@example.com", "email_verified": true })).await,
+    ))
+    .await;
+
+    let login = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/auth/stub/login?redirect_to=http://localhost:3000/welcome").to_request(),
+    )
+    .await;
+    let state = state_cookie(&login);
+
+    let response = test::call_service(&app, callback(&state, Some(&state)).to_request()).await;
+    assert_eq!(response.status(), StatusCode::FOUND);
+    let cleared = response.response().cookies().find(|c| c.name() == OAUTH_STATE_COOKIE).unwrap();
+    assert_eq!(cleared.value(), "");
+    let target = Url::parse(response.headers().get(header::LOCATION).unwrap().to_str().unwrap()).unwrap();
+    assert_eq!(target.path(), "/welcome");
+    let code = target.query_pairs().find(|(k, _)| k == "code").unwrap().1.into_owned();
+
+    let (status, body) =
+        send(&app, test::TestRequest::post().uri("/auth/exchange").set_json(json!({ "code": code }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["token"].is_string());
+    let (status, _) =
+        send(&app, test::TestRequest::post().uri("/auth/exchange").set_json(json!({ "code": code }))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn password_accounts_are_only_linked_from_a_signed_in_session() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app_with(
+        start_stub(json!({ "sub": "42", "email": "ada@example.com", "email_verified": true })).await,
+    ))
+    .await;
+    // Registered with a password, so nobody checked that this account owns the email.
+    let ada = register(&app, "ada").await;
+
+    let login = test::call_service(&app, test::TestRequest::get().uri("/auth/stub/login").to_request()).await;
+    let (status, body) = exchange(&app, &state_cookie(&login)).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", body);
+
+    let (status, _) = send(&app, test::TestRequest::post().uri("/auth/stub/link")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let link = test::call_service(
+        &app,
+        test::TestRequest::post().uri("/auth/stub/link").insert_header(ada.bearer()).to_request(),
+    )
+    .await;
+    assert_eq!(link.status(), StatusCode::OK);
+    let state = state_cookie(&link);
+    let body: Value = test::read_body_json(link).await;
+    assert!(body["authorization_url"].as_str().unwrap().contains(&state));
+    let (status, body) = exchange(&app, &state).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["id"], ada.id.to_string());
+
+    // From now on the provider login signs in to the linked account.
+    let login = test::call_service(&app, test::TestRequest::get().uri("/auth/stub/login").to_request()).await;
+    let (status, body) = exchange(&app, &state_cookie(&login)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["id"], ada.id.to_string());
+}
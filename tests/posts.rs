@@ -0,0 +1,119 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+
+use common::{create_community, create_post, register, send, TestDb};
+
+#[actix_web::test]
+async fn created_post_can_be_fetched() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let community = create_community(&app, &author).await;
+    let post = create_post(&app, &author, community).await;
+
+    let (status, body) = send(&app, test::TestRequest::get().uri(&format!("/post/{}", post))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["title"]["en"], "Hello");
+    assert_eq!(body["author_id"], author.id.to_string());
+    assert_eq!(body["like_count"], 0);
+}
+
+#[actix_web::test]
+async fn only_the_author_can_delete_a_post() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let other = register(&app, "other").await;
+    let community = create_community(&app, &author).await;
+    let post = create_post(&app, &author, community).await;
+
+    let delete = |user: &common::TestUser| {
+        test::TestRequest::delete().uri(&format!("/post/delete/{}", post)).insert_header(user.bearer())
+    };
+    let (status, body) = send(&app, delete(&other)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "forbidden");
+
+    let (status, _) = send(&app, delete(&author)).await;
+    assert!(status.is_success());
+    let (status, _) = send(&app, test::TestRequest::get().uri(&format!("/post/{}", post))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn like_toggle_likes_then_unlikes() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let reader = register(&app, "reader").await;
+    let community = create_community(&app, &author).await;
+    let post = create_post(&app, &author, community).await;
+
+    let toggle = || {
+        test::TestRequest::post()
+            .uri("/post_like/like/toggle")
+            .insert_header(reader.bearer())
+            .set_json(json!({ "post_id": post }))
+    };
+    let count = || test::TestRequest::get().uri(&format!("/post_like/count/{}", post));
+
+    let (status, likes) = send(&app, toggle()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(likes, 1);
+    let (_, body) = send(&app, count()).await;
+    assert_eq!(body["like_count"], 1);
+
+    let (_, likes) = send(&app, toggle()).await;
+    assert_eq!(likes, 0);
+    let (_, body) = send(&app, count()).await;
+    assert_eq!(body["like_count"], 0);
+}
+
+#[actix_web::test]
+async fn post_list_pages_with_a_cursor() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let community = create_community(&app, &author).await;
+    let mut created = Vec::new();
+    for _ in 0..3 {
+        created.push(create_post(&app, &author, community).await.to_string());
+    }
+
+    for sort in ["latest", "top"] {
+        let (status, first) = send(&app, test::TestRequest::get().uri(&format!("/post?sort={}&limit=2", sort))).await;
+        assert_eq!(status, StatusCode::OK, "{}", first);
+        assert_eq!(first["items"].as_array().unwrap().len(), 2);
+        assert_eq!(first["has_more"], true);
+
+        let cursor = first["next_cursor"].as_str().unwrap();
+        let uri = format!("/post?sort={}&limit=2&cursor={}", sort, cursor);
+        let (_, second) = send(&app, test::TestRequest::get().uri(&uri)).await;
+        assert_eq!(second["items"].as_array().unwrap().len(), 1);
+        assert_eq!(second["has_more"], false);
+        assert!(second["next_cursor"].is_null());
+
+        let mut seen: Vec<String> = first["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .chain(second["items"].as_array().unwrap())
+            .map(|post| post["id"].as_str().unwrap().to_string())
+            .collect();
+        if sort == "latest" {
+            let newest_first: Vec<String> = created.iter().rev().cloned().collect();
+            assert_eq!(seen, newest_first);
+        }
+        seen.sort();
+        let mut expected = created.clone();
+        expected.sort();
+        assert_eq!(seen, expected);
+    }
+
+    let (status, body) = send(&app, test::TestRequest::get().uri("/post?cursor=garbage")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "bad_request");
+}
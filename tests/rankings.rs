@@ -0,0 +1,107 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+use common::{create_community, create_tagged_post, ids, register, send, TestDb};
+use server::rankings;
+
+fn tags(body: &Value) -> Vec<&str> {
+    body["tags"].as_array().unwrap().iter().map(|t| t["tag"].as_str().unwrap()).collect()
+}
+
+#[actix_web::test]
+async fn hot_posts_and_trending_tags_follow_engagement() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let fans = [register(&app, "fan1").await, register(&app, "fan2").await];
+    let cooking = create_community(&app, &author).await;
+    let gophers = create_community(&app, &author).await;
+
+    let liked = create_tagged_post(&app, &author, cooking, &["rust"]).await;
+    let quiet = create_tagged_post(&app, &author, cooking, &["cooking"]).await;
+    let elsewhere = create_tagged_post(&app, &author, gophers, &["go"]).await;
+    for fan in &fans {
+        let (status, _) = send(
+            &app,
+            test::TestRequest::post()
+                .uri("/post_like/like/toggle")
+                .insert_header(fan.bearer())
+                .set_json(json!({ "post_id": liked })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let now = Utc::now().naive_utc() + Duration::minutes(1);
+    let summary = rankings::refresh(&db.pool, &db.config.trending_tag_windows, now).await.unwrap();
+    assert_eq!(summary.scored_posts, 1);
+
+    // Posts without engagement follow the scored one, newest first; paging keeps that order.
+    for sort in ["hot", "trending"] {
+        let (status, page) = send(&app, test::TestRequest::get().uri(&format!("/post?sort={}", sort))).await;
+        assert_eq!(status, StatusCode::OK, "{}", page);
+        assert_eq!(ids(&page), vec![liked, elsewhere, quiet]);
+
+        let (_, first) = send(&app, test::TestRequest::get().uri(&format!("/post?sort={}&limit=2", sort))).await;
+        let next = format!("/post?sort={}&limit=2&cursor={}", sort, first["next_cursor"].as_str().unwrap());
+        let (_, second) = send(&app, test::TestRequest::get().uri(&next)).await;
+        assert_eq!(ids(&second), vec![quiet]);
+    }
+
+    let (status, body) = send(&app, test::TestRequest::get().uri("/post/trending_tags?window=24h")).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["window"], "24h");
+    assert_eq!(tags(&body), vec!["rust", "cooking", "go"]);
+    assert_eq!(body["tags"][0]["post_count"], 1);
+
+    let uri = format!("/post/trending_tags?window=7d&community_id={}", gophers);
+    let (_, body) = send(&app, test::TestRequest::get().uri(&uri)).await;
+    assert_eq!(tags(&body), vec!["go"]);
+
+    let (status, body) = send(&app, test::TestRequest::get().uri("/post/trending_tags?window=3h")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Unknown window; use one of: 24h, 7d");
+}
+
+#[actix_web::test]
+async fn ranked_cursors_stop_at_the_next_refresh() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let community = create_community(&app, &author).await;
+    let liked = create_tagged_post(&app, &author, community, &["rust"]).await;
+    for _ in 0..2 {
+        create_tagged_post(&app, &author, community, &["rust"]).await;
+    }
+    let (status, _) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/post_like/like/toggle")
+            .insert_header(author.bearer())
+            .set_json(json!({ "post_id": liked })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let now = Utc::now().naive_utc() + Duration::minutes(1);
+    rankings::refresh(&db.pool, &db.config.trending_tag_windows, now).await.unwrap();
+
+    let (_, first) = send(&app, test::TestRequest::get().uri("/post?sort=hot&limit=2")).await;
+    let next = format!("/post?sort=hot&limit=2&cursor={}", first["next_cursor"].as_str().unwrap());
+    let (status, second) = send(&app, test::TestRequest::get().uri(&next)).await;
+    assert_eq!(status, StatusCode::OK, "{}", second);
+    assert_eq!(ids(&second).len(), 1);
+
+    // The scores the cursor points into are gone after a refresh.
+    rankings::refresh(&db.pool, &db.config.trending_tag_windows, now + Duration::minutes(5)).await.unwrap();
+    let (status, body) = send(&app, test::TestRequest::get().uri(&next)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["message"], "The ranking was refreshed since the first page; load it again without a cursor");
+    let (_, again) = send(&app, test::TestRequest::get().uri("/post?sort=hot&limit=2")).await;
+    let next = format!("/post?sort=hot&limit=2&cursor={}", again["next_cursor"].as_str().unwrap());
+    let (status, _) = send(&app, test::TestRequest::get().uri(&next)).await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use actix_web::http::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+
+use common::{apply, create_job, register, register_business, send, TestDb};
+
+const PDF: &[u8] = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n1 0 obj << /Type /Catalog >> endobj\n%%EOF\n";
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+
+#[actix_web::test]
+async fn resumes_are_picked_from_the_library_and_only_shown_to_the_parties() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let rival = register_business(&app, "rival").await;
+    let seeker = register(&app, "seeker").await;
+    let other = register(&app, "other").await;
+    let job = create_job(&app, &acme, json!({})).await;
+
+    let (status, body) =
+        send(&app, test::TestRequest::post().uri("/resumes").insert_header(seeker.bearer()).set_payload(PNG)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Resumes must be PDF or DOCX files");
+
+    let (status, resume) = send(
+        &app,
+        test::TestRequest::post().uri("/resumes?name=Backend%20CV").insert_header(seeker.bearer()).set_payload(PDF),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", resume);
+    assert_eq!(resume["name"], "Backend CV");
+    assert_eq!(resume["content_type"], "application/pdf");
+    let resume_id = resume["id"].as_str().unwrap();
+    let file_uri = resume["url"].as_str().unwrap().to_string();
+    assert_eq!(file_uri, format!("/resumes/{}/file", resume_id));
+
+    // Nobody else can apply with it.
+    let (status, _) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(other.bearer())
+            .set_json(json!({ "job_id": job, "resume_id": resume_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Before it is sent, only the owner can download it.
+    let (status, _) = send(&app, test::TestRequest::get().uri(&file_uri).insert_header(acme.bearer())).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, application) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/jobApplications/create")
+            .insert_header(seeker.bearer())
+            .set_json(json!({ "job_id": job, "resume_id": resume_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", application);
+    assert_eq!(application["resume_id"], resume_id);
+    assert_eq!(application["resume_url"], file_uri.as_str());
+
+    for (viewer, expected) in [
+        (&seeker, StatusCode::OK),
+        (&acme, StatusCode::OK),
+        (&rival, StatusCode::FORBIDDEN),
+        (&other, StatusCode::FORBIDDEN),
+    ] {
+        let req = test::TestRequest::get().uri(&file_uri).insert_header(viewer.bearer()).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected);
+        if expected == StatusCode::OK {
+            assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "application/pdf");
+            assert_eq!(resp.headers().get(CONTENT_DISPOSITION).unwrap(), "attachment; filename=\"Backend CV.pdf\"");
+            assert_eq!(test::read_body(resp).await.as_ref(), PDF);
+        }
+    }
+    let (status, _) = send(&app, test::TestRequest::get().uri(&file_uri)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // Removing it from the library keeps the file for the application.
+    let uri = format!("/resumes/{}", resume_id);
+    let (status, _) = send(&app, test::TestRequest::delete().uri(&uri).insert_header(seeker.bearer())).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, library) = send(&app, test::TestRequest::get().uri("/resumes").insert_header(seeker.bearer())).await;
+    assert_eq!(library["items"], json!([]));
+    let (status, _) = send(&app, test::TestRequest::get().uri(&file_uri).insert_header(acme.bearer())).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn an_application_can_get_its_own_resume() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let acme = register_business(&app, "acme").await;
+    let seeker = register(&app, "seeker").await;
+    let other = register(&app, "other").await;
+    let job = create_job(&app, &acme, json!({})).await;
+    // A profile link is not a resume: it could point at anything, e.g. the public /uploads.
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri("/users/update/seeker@example.com")
+            .insert_header(seeker.bearer())
+            .set_json(json!({ "resume_url": "/uploads/cv.pdf" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let application = apply(&app, &seeker, job).await;
+    let (_, body) = send(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/jobApplications/job/{}/user/{}", job, seeker.id))
+            .insert_header(seeker.bearer()),
+    )
+    .await;
+    assert_eq!(body["resume_url"], "");
+    let (status, _) = send(&app, test::TestRequest::get().uri("/uploads/")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let uri = format!("/jobApplications/{}/resume?name=For%20Acme", application);
+
+    let (status, _) =
+        send(&app, test::TestRequest::put().uri(&uri).insert_header(other.bearer()).set_payload(PDF)).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, body) =
+        send(&app, test::TestRequest::put().uri(&uri).insert_header(seeker.bearer()).set_payload(PDF)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["resume"]["in_library"], false);
+    assert_eq!(body["application"]["resume_url"], body["resume"]["url"]);
+
+    let (_, library) = send(&app, test::TestRequest::get().uri("/resumes").insert_header(seeker.bearer())).await;
+    assert_eq!(library["items"], json!([]));
+    let file_uri = body["resume"]["url"].as_str().unwrap();
+    let (status, _) = send(&app, test::TestRequest::get().uri(file_uri).insert_header(acme.bearer())).await;
+    assert_eq!(status, StatusCode::OK);
+}
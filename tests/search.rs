@@ -0,0 +1,84 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+use uuid::Uuid;
+
+use common::{create_community, create_post, ids, register, send, TestDb};
+
+#[actix_web::test]
+async fn search_matches_every_language_and_highlights_hits() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let author = register(&app, "author").await;
+    let community = create_community(&app, &author).await;
+    create_post(&app, &author, community).await;
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::post()
+            .uri("/post/create")
+            .insert_header(author.bearer())
+            .set_json(json!({
+                "community_id": community,
+                "title": { "en": "Weekend meetup", "fr": "Rencontre du week-end" },
+                "content": { "en": "Bring a laptop", "fr": "Apportez un ordinateur portable" },
+                "post_type": "text",
+                "tags": ["events"]
+            })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let meetup: Uuid = body["id"].as_str().unwrap().parse().unwrap();
+
+    let (status, page) = send(&app, test::TestRequest::get().uri("/search?q=ordinateur&type=posts")).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(ids(&page), vec![meetup]);
+    let highlights = &page["items"][0]["highlights"];
+    assert_eq!(highlights["content"]["fr"], "Apportez un <mark>ordinateur</mark> portable");
+    assert_eq!(highlights["title"]["en"], "Weekend meetup");
+
+    // A match in the title outranks one in the content.
+    let (_, page) = send(&app, test::TestRequest::get().uri("/search?q=laptop%20or%20hello&type=posts")).await;
+    assert_eq!(page["items"].as_array().unwrap().len(), 2);
+    assert_eq!(page["items"][0]["highlights"]["title"]["en"], "<mark>Hello</mark>");
+    assert_eq!(page["items"][1]["id"], meetup.to_string());
+}
+
+#[actix_web::test]
+async fn search_finds_people_by_skill_and_pages_every_type() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let rustacean = register(&app, "rustacean").await;
+    register(&app, "gopher").await;
+
+    let (status, _) = send(
+        &app,
+        test::TestRequest::put()
+            .uri("/users/update/rustacean@example.com")
+            .insert_header(rustacean.bearer())
+            .set_json(json!({ "skills": ["Rust", "PostgreSQL"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, page) = send(&app, test::TestRequest::get().uri("/search?q=postgresql&type=users")).await;
+    assert_eq!(status, StatusCode::OK, "{}", page);
+    assert_eq!(ids(&page), vec![rustacean.id]);
+    assert_eq!(page["items"][0]["highlights"]["skills"], "Rust, <mark>PostgreSQL</mark>");
+
+    // Without a type every type gets its own page.
+    let (status, all) = send(&app, test::TestRequest::get().uri("/search?q=rust")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(all["users"]["items"].as_array().unwrap().len(), 1);
+    for kind in ["posts", "communities", "jobs"] {
+        assert_eq!(all[kind]["items"], json!([]));
+        assert_eq!(all[kind]["has_more"], false);
+    }
+
+    let (status, _) = send(&app, test::TestRequest::get().uri("/search?q=%20&type=users")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, test::TestRequest::get().uri("/search?q=rust&type=badges")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
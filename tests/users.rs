@@ -0,0 +1,61 @@
+mod common;
+
+use actix_web::http::StatusCode;
+use actix_web::test;
+use serde_json::json;
+use uuid::Uuid;
+
+use common::{register, send, TestDb};
+
+#[actix_web::test]
+async fn profile_shows_email_to_its_owner_only() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    let asha = register(&app, "asha").await;
+
+    let (status, own) = send(
+        &app,
+        test::TestRequest::get().uri(&format!("/users/{}", asha.id)).insert_header(asha.bearer()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(own["email"], "asha@example.com");
+
+    let (status, public) = send(&app, test::TestRequest::get().uri(&format!("/users/{}", asha.id))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(public["username"], "asha");
+    assert!(public.get("email").is_none_or(|email| email.is_null()));
+    assert!(public.get("password").is_none());
+}
+
+#[actix_web::test]
+async fn registering_a_taken_email_is_a_conflict() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+    register(&app, "asha").await;
+
+    let (status, body) = send(
+        &app,
+        test::TestRequest::post().uri("/auth/register").set_json(json!({
+            "first_name": "Other",
+            "last_name": "Person",
+            "username": "asha2",
+            "email": "asha@example.com",
+            "password": "another long password"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["status"], "error");
+    assert_eq!(body["code"], "conflict");
+}
+
+#[actix_web::test]
+async fn unknown_user_is_not_found() {
+    let db = TestDb::new().await;
+    let app = test::init_service(db.app()).await;
+
+    let (status, body) = send(&app, test::TestRequest::get().uri(&format!("/users/{}", Uuid::new_v4()))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+}